@@ -0,0 +1,251 @@
+pub mod configuration;
+
+use crate::configuration::Configuration;
+use helix_memoriz_domain::business::dump::{dump, restore};
+use helix_memoriz_domain::business::error::EntryDomainResult;
+use helix_memoriz_domain::business::search::reindex;
+use helix_memoriz_domain::core::dump::DumpReport;
+use helix_memoriz_domain::storage::traits::{SearchEngineTrait, StorageTrait};
+use meilisearch_search_engine::MsMemorizSearchEngine;
+use pg_db_storage::PgDbMemorizStorage;
+use sled_db_storage::SledDbMemorizStorage;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::{env, process};
+
+const APP_NAME: &str = "MEMORIZ_ADMIN";
+const USAGE: &str = "USAGE:
+    helix-memoriz-admin dump [--owner <uuid>] [--output <file>]
+    helix-memoriz-admin restore [--input <file>]
+    helix-memoriz-admin reindex [--owner <uuid>]
+    helix-memoriz-admin migrate --from <postgres|sled> --to <postgres|sled> [--owner <uuid>]
+
+The dump is written to the standard output and the restore reads the standard input
+when no file is given. The storage is chosen by HELIX_DB_BACKEND (postgres or sled),
+Postgres is configured by the HELIX_DB_* variables and sled by HELIX_SLED_PATH.
+The search engine of the reindex is configured by the HELIX_SEARCH_* variables.
+The migration dumps one backend and restores the dump into the other one.";
+
+const BACKENDS: [&str; 2] = ["postgres", "sled"];
+
+enum Command {
+    Dump {
+        owner: Option<uuid::Uuid>,
+        output: Option<String>,
+    },
+    Restore {
+        input: Option<String>,
+    },
+    Reindex {
+        owner: Option<uuid::Uuid>,
+    },
+    Migrate {
+        from: String,
+        to: String,
+        owner: Option<uuid::Uuid>,
+    },
+}
+
+#[actix_rt::main]
+async fn main() {
+    //Messages go to the error output: the standard one may hold the dump.
+    eprintln!("[HELIX {} {}]", APP_NAME, env!("CARGO_PKG_VERSION"));
+    dotenv::dotenv().ok();
+
+    let command = match parse_command(env::args().skip(1).collect()) {
+        Ok(command) => command,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+
+    let backend = Configuration::get_database_backend();
+    let result = match command {
+        Command::Dump { owner, output } => run_dump(get_storage(&backend).as_ref(), owner, output)
+            .await
+            .map(|report| print_report(&report)),
+        Command::Restore { input } => run_restore(get_storage(&backend).as_ref(), input)
+            .await
+            .map(|report| print_report(&report)),
+        Command::Reindex { owner } => reindex(
+            get_storage(&backend).as_ref(),
+            get_search_engine().as_ref(),
+            owner,
+        )
+        .await
+        .map(|indexed| eprintln!("{} entries indexed", indexed)),
+        Command::Migrate { from, to, owner } => run_migrate(&from, &to, owner)
+            .await
+            .map(|report| print_report(&report)),
+    };
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
+
+fn print_report(report: &DumpReport) {
+    eprintln!(
+        "{} owners, {} boards, {} labels, {} entries, {} entry labels, {} links, {} shares, \
+         {} calendar tokens, {} public links, {} changes, {} documents, {} attachments, \
+         {} templates, {} saved searches",
+        report.owners,
+        report.boards,
+        report.labels,
+        report.entries,
+        report.entry_labels,
+        report.entry_links,
+        report.shares,
+        report.calendar_tokens,
+        report.public_links,
+        report.changes,
+        report.documents,
+        report.attachments,
+        report.templates,
+        report.saved_searches
+    );
+}
+
+fn parse_command(arguments: Vec<String>) -> Result<Command, String> {
+    let mut arguments = arguments.into_iter();
+    let name = arguments.next().ok_or("Missing command")?;
+
+    let mut owner: Option<uuid::Uuid> = None;
+    let mut file: Option<String> = None;
+    let mut from: Option<String> = None;
+    let mut to: Option<String> = None;
+    while let Some(option) = arguments.next() {
+        let value = arguments
+            .next()
+            .ok_or(format!("Missing value of {}", option))?;
+        match (name.as_str(), option.as_str()) {
+            ("dump", "--owner") | ("reindex", "--owner") | ("migrate", "--owner") => {
+                owner = Some(
+                    uuid::Uuid::parse_str(&value)
+                        .map_err(|_| format!("Invalid owner: {}", value))?,
+                )
+            }
+            ("dump", "--output") | ("restore", "--input") => file = Some(value),
+            ("migrate", "--from") => from = Some(parse_backend(value)?),
+            ("migrate", "--to") => to = Some(parse_backend(value)?),
+            _ => return Err(format!("Unknown option: {}", option)),
+        }
+    }
+
+    match name.as_str() {
+        "dump" => Ok(Command::Dump {
+            owner: owner,
+            output: file,
+        }),
+        "restore" => Ok(Command::Restore { input: file }),
+        "reindex" => Ok(Command::Reindex { owner: owner }),
+        "migrate" => {
+            let from = from.ok_or("Missing value of --from")?;
+            let to = to.ok_or("Missing value of --to")?;
+            if from == to {
+                return Err(format!("Nothing to migrate from {} to {}", from, to));
+            }
+            Ok(Command::Migrate {
+                from: from,
+                to: to,
+                owner: owner,
+            })
+        }
+        _ => Err(format!("Unknown command: {}", name)),
+    }
+}
+
+fn parse_backend(value: String) -> Result<String, String> {
+    if BACKENDS.contains(&value.as_str()) {
+        Ok(value)
+    } else {
+        Err(format!("Unknown backend: {}", value))
+    }
+}
+
+async fn run_dump(
+    storage: &dyn StorageTrait,
+    owner: Option<uuid::Uuid>,
+    output: Option<String>,
+) -> EntryDomainResult<DumpReport> {
+    match output {
+        Some(path) => {
+            let file = File::create(&path).unwrap_or_else(|e| exit_on_file_error(&path, e));
+            dump(storage, owner, &mut BufWriter::new(file)).await
+        }
+        None => dump(storage, owner, &mut BufWriter::new(io::stdout())).await,
+    }
+}
+
+async fn run_restore(
+    storage: &dyn StorageTrait,
+    input: Option<String>,
+) -> EntryDomainResult<DumpReport> {
+    match input {
+        Some(path) => {
+            let file = File::open(&path).unwrap_or_else(|e| exit_on_file_error(&path, e));
+            restore(storage, BufReader::new(file)).await
+        }
+        None => restore(storage, BufReader::new(io::stdin())).await,
+    }
+}
+
+//The dump goes through a file: an owner with many documents would not fit in memory.
+async fn run_migrate(
+    from: &str,
+    to: &str,
+    owner: Option<uuid::Uuid>,
+) -> EntryDomainResult<DumpReport> {
+    let path = env::temp_dir().join(format!("helix-memoriz-migrate-{}.jsonl", process::id()));
+    let path = path.to_string_lossy().to_string();
+
+    let result = match run_dump(get_storage(from).as_ref(), owner, Some(path.to_owned())).await {
+        Ok(dumped) => {
+            eprintln!("Dumped from {}:", from);
+            print_report(&dumped);
+            eprintln!("Restored into {}:", to);
+            run_restore(get_storage(to).as_ref(), Some(path.to_owned())).await
+        }
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = fs::remove_file(&path) {
+        eprintln!("{} not removed: {}", path, e);
+    }
+    result
+}
+
+fn exit_on_file_error(path: &str, error: io::Error) -> ! {
+    eprintln!("{}: {}", path, error);
+    process::exit(1);
+}
+
+fn get_storage(backend: &str) -> Box<dyn StorageTrait> {
+    match backend {
+        "sled" => Box::new(SledDbMemorizStorage::new(Configuration::get_sled_path()).unwrap()),
+        _ => Box::new(
+            PgDbMemorizStorage::new(
+                Configuration::get_database_name(),
+                Configuration::get_database_host(),
+                Configuration::get_database_port(),
+                Configuration::get_database_user(),
+                Configuration::get_database_password(),
+            )
+            .unwrap(),
+        ),
+    }
+}
+
+fn get_search_engine() -> Box<dyn SearchEngineTrait> {
+    Box::new(
+        MsMemorizSearchEngine::new(
+            Configuration::get_search_index(),
+            Configuration::get_search_host(),
+            Configuration::get_search_port(),
+            Configuration::get_search_token(),
+        )
+        .unwrap(),
+    )
+}
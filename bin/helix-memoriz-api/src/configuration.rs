@@ -48,4 +48,152 @@ impl Configuration {
     pub fn get_search_token() -> String {
         env::var("HELIX_SEARCH_TOKEN").expect("HELIX_SEARCH_TOKEN not found.")
     }
+
+    pub fn get_notifier() -> String {
+        env::var("HELIX_NOTIFIER").unwrap_or_else(|_| "log".to_string())
+    }
+
+    pub fn get_reminder_interval() -> u64 {
+        env::var("HELIX_REMINDER_INTERVAL")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_events_keep_alive_interval() -> u64 {
+        env::var("HELIX_EVENTS_KEEP_ALIVE_INTERVAL")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_smtp_host() -> String {
+        env::var("HELIX_SMTP_HOST").expect("HELIX_SMTP_HOST not found.")
+    }
+
+    pub fn get_smtp_port() -> u16 {
+        env::var("HELIX_SMTP_PORT")
+            .expect("HELIX_SMTP_PORT not found.")
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_smtp_tls() -> bool {
+        env::var("HELIX_SMTP_TLS").unwrap_or_else(|_| "true".to_string()) == "true"
+    }
+
+    pub fn get_smtp_credentials() -> Option<(String, String)> {
+        match (env::var("HELIX_SMTP_USER"), env::var("HELIX_SMTP_PASSWORD")) {
+            (Ok(user), Ok(password)) => Some((user, password)),
+            _ => None,
+        }
+    }
+
+    pub fn get_smtp_from() -> String {
+        env::var("HELIX_SMTP_FROM").expect("HELIX_SMTP_FROM not found.")
+    }
+
+    pub fn get_smtp_default_recipient() -> Option<String> {
+        env::var("HELIX_SMTP_DEFAULT_RECIPIENT").ok()
+    }
+
+    pub fn get_max_upload_size() -> usize {
+        env::var("HELIX_MAX_UPLOAD_SIZE")
+            .unwrap_or_else(|_| "52428800".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_max_attachment_size() -> usize {
+        env::var("HELIX_MAX_ATTACHMENT_SIZE")
+            .unwrap_or_else(|_| "10485760".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_max_clip_size() -> usize {
+        env::var("HELIX_MAX_CLIP_SIZE")
+            .unwrap_or_else(|_| "5242880".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_attachment_interval() -> u64 {
+        env::var("HELIX_ATTACHMENT_INTERVAL")
+            .unwrap_or_else(|_| "5".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_attachment_batch_size() -> i64 {
+        env::var("HELIX_ATTACHMENT_BATCH_SIZE")
+            .unwrap_or_else(|_| "4".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_link_fetcher() -> String {
+        env::var("HELIX_LINK_FETCHER").unwrap_or_else(|_| "none".to_string())
+    }
+
+    pub fn get_link_preview_interval() -> u64 {
+        env::var("HELIX_LINK_PREVIEW_INTERVAL")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_link_preview_batch_size() -> i64 {
+        env::var("HELIX_LINK_PREVIEW_BATCH_SIZE")
+            .unwrap_or_else(|_| "8".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_link_preview_timeout() -> u64 {
+        env::var("HELIX_LINK_PREVIEW_TIMEOUT")
+            .unwrap_or_else(|_| "10".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_link_preview_max_size() -> usize {
+        env::var("HELIX_LINK_PREVIEW_MAX_SIZE")
+            .unwrap_or_else(|_| "1048576".to_string())
+            .parse()
+            .unwrap()
+    }
+
+    pub fn get_blob_store() -> String {
+        env::var("HELIX_BLOB_STORE").unwrap_or_else(|_| "fs".to_string())
+    }
+
+    pub fn get_blob_folder() -> String {
+        env::var("HELIX_BLOB_FOLDER").unwrap_or_else(|_| "./blobs".to_string())
+    }
+
+    #[cfg(feature = "s3")]
+    pub fn get_s3_bucket() -> String {
+        env::var("HELIX_S3_BUCKET").expect("HELIX_S3_BUCKET not found.")
+    }
+
+    #[cfg(feature = "s3")]
+    pub fn get_s3_region() -> String {
+        env::var("HELIX_S3_REGION").unwrap_or_else(|_| "us-east-1".to_string())
+    }
+
+    #[cfg(feature = "s3")]
+    pub fn get_s3_endpoint() -> Option<String> {
+        env::var("HELIX_S3_ENDPOINT").ok()
+    }
+
+    #[cfg(feature = "s3")]
+    pub fn get_s3_access_key() -> String {
+        env::var("HELIX_S3_ACCESS_KEY").expect("HELIX_S3_ACCESS_KEY not found.")
+    }
+
+    #[cfg(feature = "s3")]
+    pub fn get_s3_secret_key() -> String {
+        env::var("HELIX_S3_SECRET_KEY").expect("HELIX_S3_SECRET_KEY not found.")
+    }
 }
@@ -1,2 +1,12 @@
+pub mod attachment_controller;
 pub mod business_controller;
+pub mod document_controller;
+pub mod event_controller;
+pub mod export_controller;
+pub mod import_controller;
 pub mod internal_controller;
+pub mod saved_search_controller;
+pub mod share_controller;
+pub mod sync_controller;
+pub mod template_controller;
+pub mod upload;
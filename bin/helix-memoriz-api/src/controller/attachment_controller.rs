@@ -0,0 +1,166 @@
+use crate::configuration::Configuration;
+use crate::controller::upload::read_uploaded_file_up_to;
+use crate::state::AppState;
+use actix_multipart::Multipart;
+use actix_web::http::header::{ContentDisposition, DispositionParam, DispositionType};
+use actix_web::{web::Data, HttpRequest, HttpResponse};
+use helix_auth_lib::HelixAuth;
+use helix_memoriz_domain::business::error::MemorizDomainError;
+use helix_memoriz_domain::business::thumbnail::THUMBNAIL_CONTENT_TYPE;
+use std::sync::{Arc, Mutex};
+
+fn get_uuids(req: &HttpRequest) -> (uuid::Uuid, Option<uuid::Uuid>) {
+    let entry_uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+    let attachment_uuid = req
+        .match_info()
+        .get("attachment")
+        .map(|uuid| uuid::Uuid::parse_str(uuid).unwrap());
+    (entry_uuid, attachment_uuid)
+}
+
+fn error_response(error: MemorizDomainError) -> HttpResponse {
+    match error {
+        MemorizDomainError::NotFoundError => HttpResponse::NotFound().body("Not Found."),
+        MemorizDomainError::Forbidden => HttpResponse::Forbidden().body("Forbidden."),
+        MemorizDomainError::Attachment(message) => {
+            HttpResponse::UnsupportedMediaType().body(message)
+        }
+        _ => HttpResponse::InternalServerError().body("Internal Server Error."),
+    }
+}
+
+pub async fn add_attachment(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+    mut payload: Multipart,
+) -> HttpResponse {
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+    let (entry_uuid, _) = get_uuids(&req);
+
+    //The upload is read before locking the state.
+    let upload = match read_uploaded_file_up_to(
+        &mut payload,
+        Configuration::get_max_attachment_size(),
+    )
+    .await
+    {
+        Ok(upload) => upload,
+        Err(response) => return response,
+    };
+
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+
+    match domain
+        .add_attachment(
+            claimer.user_uuid,
+            entry_uuid,
+            upload.file_name,
+            upload.content_type,
+            upload.content,
+        )
+        .await
+    {
+        Ok(attachment) => HttpResponse::Created().json(attachment),
+        Err(e) => error_response(e),
+    }
+}
+
+pub async fn get_entry_attachments(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+    let (entry_uuid, _) = get_uuids(&req);
+
+    match domain
+        .get_entry_attachments(claimer.user_uuid, entry_uuid)
+        .await
+    {
+        Ok(attachments) => HttpResponse::Ok().json(attachments),
+        Err(e) => error_response(e),
+    }
+}
+
+//Images are shown in place, other files are downloaded.
+pub async fn get_attachment_content(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+    let (entry_uuid, attachment_uuid) = get_uuids(&req);
+
+    match domain
+        .get_attachment_content(claimer.user_uuid, entry_uuid, attachment_uuid.unwrap())
+        .await
+    {
+        Ok((attachment, content)) => {
+            let disposition = match attachment.content_type.starts_with("image/") {
+                true => DispositionType::Inline,
+                false => DispositionType::Attachment,
+            };
+            HttpResponse::Ok()
+                .content_type(attachment.content_type.as_str())
+                .header("X-Content-Type-Options", "nosniff")
+                .set(ContentDisposition {
+                    disposition: disposition,
+                    parameters: vec![DispositionParam::Filename(attachment.file_name)],
+                })
+                .body(content)
+        }
+        Err(e) => error_response(e),
+    }
+}
+
+pub async fn get_attachment_thumbnail(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+    let (entry_uuid, attachment_uuid) = get_uuids(&req);
+
+    let size: i32 = match req.match_info().get("size").unwrap().parse() {
+        Ok(size) => size,
+        Err(_) => return HttpResponse::NotFound().body("Not Found."),
+    };
+
+    match domain
+        .get_attachment_thumbnail(
+            claimer.user_uuid,
+            entry_uuid,
+            attachment_uuid.unwrap(),
+            size,
+        )
+        .await
+    {
+        Ok(content) => HttpResponse::Ok()
+            .content_type(THUMBNAIL_CONTENT_TYPE)
+            .header("X-Content-Type-Options", "nosniff")
+            .body(content),
+        Err(e) => error_response(e),
+    }
+}
+
+pub async fn delete_attachment(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+    let (entry_uuid, attachment_uuid) = get_uuids(&req);
+
+    match domain
+        .delete_attachment(claimer.user_uuid, entry_uuid, attachment_uuid.unwrap())
+        .await
+    {
+        Ok(_) => HttpResponse::Ok().finish(),
+        Err(e) => error_response(e),
+    }
+}
@@ -1,17 +1,87 @@
 use crate::state::AppState;
 use actix_web::{web, web::Data, HttpRequest, HttpResponse};
 use helix_auth_lib::HelixAuth;
-use helix_memoriz_domain::core::{board::Board, entry::Entry};
+use helix_memoriz_domain::business::calendar::CalendarComponent;
+use helix_memoriz_domain::business::error::MemorizDomainError;
+use helix_memoriz_domain::business::markdown::render_entry_html;
+use helix_memoriz_domain::business::search::DEFAULT_SEARCH_LIMIT;
+use helix_memoriz_domain::core::{
+    board::Board,
+    clip::ClipRequest,
+    entry::{DueFilter, Entry},
+};
 use std::sync::{Arc, Mutex};
 
+//Ten years: further is no longer "upcoming", and days out of range would overflow the dates.
+const MAX_UPCOMING_DAYS: i64 = 3650;
+
 #[derive(Deserialize)]
 pub struct EntriesFilter {
     archived: Option<String>,
+    overdue: Option<String>,
+    upcoming: Option<i64>,
+    format: Option<String>,
 }
 
+impl EntriesFilter {
+    //"overdue=true" wins over "upcoming=<days>".
+    fn get_due_filter(&self) -> Result<Option<DueFilter>, String> {
+        match (&self.overdue, self.upcoming) {
+            (Some(overdue), _) if overdue == "true" => Ok(Some(DueFilter::Overdue)),
+            (_, Some(days)) if !(0..=MAX_UPCOMING_DAYS).contains(&days) => Err(format!(
+                "upcoming must be between 0 and {} days",
+                MAX_UPCOMING_DAYS
+            )),
+            (_, Some(days)) => Ok(Some(DueFilter::Upcoming(chrono::Duration::days(days)))),
+            _ => Ok(None),
+        }
+    }
+}
+
+//"q" takes the syntax of the saved searches: words, "quoted phrases" and filters.
 #[derive(Deserialize)]
 pub struct SearchParameters {
     q: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RenderParameters {
+    format: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateParameters {
+    rewrite_links: Option<String>,
+}
+
+//"format=html" adds the sanitized HTML rendering of the Markdown content.
+fn render_entries(mut entries: Vec<Entry>, format: &Option<String>) -> Vec<Entry> {
+    if format.as_deref() == Some("html") {
+        entries.iter_mut().for_each(render_entry_html);
+    }
+    entries
+}
+
+#[derive(Deserialize)]
+pub struct GraphParameters {
+    entry: Option<String>,
+    depth: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct CalendarParameters {
+    token: String,
+    board: Option<String>,
+    archived: Option<String>,
+    component: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CalendarTokenResponse {
+    token: String,
 }
 
 pub async fn search_entries(
@@ -24,11 +94,25 @@ pub async fn search_entries(
     let claimer = HelixAuth::get_claimer(&req).unwrap();
 
     match domain
-        .search(claimer.user_uuid, search_parameters.q.to_string())
+        .search(
+            claimer.user_uuid,
+            search_parameters.q.to_string(),
+            search_parameters.offset.unwrap_or(0),
+            search_parameters.limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+        )
         .await
     {
+        Err(MemorizDomainError::Query(message)) => HttpResponse::BadRequest().body(message),
         Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
-        Ok(entries) => HttpResponse::Ok().json(entries),
+        Ok(mut results) => {
+            if search_parameters.format.as_deref() == Some("html") {
+                results
+                    .hits
+                    .iter_mut()
+                    .for_each(|hit| render_entry_html(&mut hit.entry));
+            }
+            HttpResponse::Ok().json(results)
+        }
     }
 }
 
@@ -37,6 +121,10 @@ pub async fn get_all_entries(
     req: HttpRequest,
     filter: web::Query<EntriesFilter>,
 ) -> HttpResponse {
+    let due_filter = match filter.get_due_filter() {
+        Ok(due_filter) => due_filter,
+        Err(message) => return HttpResponse::BadRequest().body(message),
+    };
     let state = wrap_state.lock().unwrap();
     let domain = state.get_domain();
     let claimer = HelixAuth::get_claimer(&req).unwrap();
@@ -48,11 +136,12 @@ pub async fn get_all_entries(
                 Some(filter) => Some(filter.to_string() == "true"),
                 None => None,
             },
+            due_filter,
         )
         .await
     {
         Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
-        Ok(entries) => HttpResponse::Ok().json(entries),
+        Ok(entries) => HttpResponse::Ok().json(render_entries(entries, &filter.format)),
     }
 }
 
@@ -61,6 +150,10 @@ pub async fn get_all_entries_by_board(
     req: HttpRequest,
     filter: web::Query<EntriesFilter>,
 ) -> HttpResponse {
+    let due_filter = match filter.get_due_filter() {
+        Ok(due_filter) => due_filter,
+        Err(message) => return HttpResponse::BadRequest().body(message),
+    };
     let state = wrap_state.lock().unwrap();
     let domain = state.get_domain();
     let claimer = HelixAuth::get_claimer(&req).unwrap();
@@ -75,15 +168,55 @@ pub async fn get_all_entries_by_board(
                 Some(filter) => Some(filter.to_string() == "true"),
                 None => None,
             },
+            due_filter,
         )
         .await
     {
         Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
-        Ok(entries) => HttpResponse::Ok().json(entries),
+        Ok(entries) => HttpResponse::Ok().json(render_entries(entries, &filter.format)),
+    }
+}
+
+//Entries matching a saved search, filtered like the entries of a board.
+pub async fn get_all_entries_by_saved_search(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+    filter: web::Query<EntriesFilter>,
+) -> HttpResponse {
+    let due_filter = match filter.get_due_filter() {
+        Ok(due_filter) => due_filter,
+        Err(message) => return HttpResponse::BadRequest().body(message),
+    };
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    match domain
+        .get_all_entries_by_saved_search(
+            claimer.user_uuid,
+            uuid,
+            match &filter.archived {
+                Some(filter) => Some(filter.to_string() == "true"),
+                None => None,
+            },
+            due_filter,
+        )
+        .await
+    {
+        Err(MemorizDomainError::NotFoundError) => HttpResponse::NotFound().body("Not Found."),
+        Err(MemorizDomainError::Query(message)) => HttpResponse::BadRequest().body(message),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(entries) => HttpResponse::Ok().json(render_entries(entries, &filter.format)),
     }
 }
 
-pub async fn get_entry(wrap_state: Data<Arc<Mutex<AppState>>>, req: HttpRequest) -> HttpResponse {
+pub async fn get_entry(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+    parameters: web::Query<RenderParameters>,
+) -> HttpResponse {
     let state = wrap_state.lock().unwrap();
     let domain = state.get_domain();
     let claimer = HelixAuth::get_claimer(&req).unwrap();
@@ -92,7 +225,12 @@ pub async fn get_entry(wrap_state: Data<Arc<Mutex<AppState>>>, req: HttpRequest)
 
     match domain.get_entry(claimer.user_uuid, uuid).await {
         Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
-        Ok(entry) => HttpResponse::Ok().json(entry),
+        Ok(mut entry) => {
+            if parameters.format.as_deref() == Some("html") {
+                render_entry_html(&mut entry);
+            }
+            HttpResponse::Ok().json(entry)
+        }
     }
 }
 
@@ -106,7 +244,8 @@ pub async fn archive_entry(
 
     let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
 
-    match domain.archive_entry(uuid, claimer.user_uuid).await {
+    match domain.archive_entry(claimer.user_uuid, uuid).await {
+        Err(MemorizDomainError::Forbidden) => HttpResponse::Forbidden().body("Forbidden."),
         Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
         Ok(entry) => HttpResponse::Ok().json(entry),
     }
@@ -122,7 +261,8 @@ pub async fn undo_archive_entry(
 
     let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
 
-    match domain.undo_archive_entry(uuid, claimer.user_uuid).await {
+    match domain.undo_archive_entry(claimer.user_uuid, uuid).await {
+        Err(MemorizDomainError::Forbidden) => HttpResponse::Forbidden().body("Forbidden."),
         Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
         Ok(entry) => HttpResponse::Ok().json(entry),
     }
@@ -138,6 +278,9 @@ pub async fn create_entry(
 
     let mut entry: Entry = json.into_inner();
     entry.owner = Some(claimer.user_uuid);
+    //Timestamps given by the storage, only imports keep their own.
+    entry.created_on = None;
+    entry.updated_on = None;
 
     match domain.create_entry(entry).await {
         Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
@@ -145,20 +288,79 @@ pub async fn create_entry(
     }
 }
 
+//Article of a page sent by the browser extension, saved as a new entry.
+pub async fn clip_entry(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (json, req): (web::Json<ClipRequest>, HttpRequest),
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    match domain
+        .clip_entry(claimer.user_uuid, json.into_inner())
+        .await
+    {
+        Err(MemorizDomainError::Clip(message)) => HttpResponse::BadRequest().body(message),
+        Err(MemorizDomainError::Forbidden) => HttpResponse::Forbidden().body("Forbidden."),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(entry) => HttpResponse::Ok().json(entry),
+    }
+}
+
 pub async fn update_entry(
     wrap_state: Data<Arc<Mutex<AppState>>>,
-    json: web::Json<Entry>,
+    (json, req): (web::Json<Entry>, HttpRequest),
+    parameters: web::Query<UpdateParameters>,
 ) -> HttpResponse {
     let state = wrap_state.lock().unwrap();
     let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
 
-    let entry: Entry = json.into_inner();
-    match domain.update_entry(entry).await {
+    let mut entry: Entry = json.into_inner();
+    entry.owner = Some(claimer.user_uuid);
+
+    //"rewrite_links=true" renames the "[[links]]" pointing to a renamed entry.
+    let rewrite_links = parameters.rewrite_links.as_deref() == Some("true");
+    match domain.update_entry(entry, rewrite_links).await {
+        Err(MemorizDomainError::Forbidden) => HttpResponse::Forbidden().body("Forbidden."),
         Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
         Ok(entry) => HttpResponse::Ok().json(entry),
     }
 }
 
+pub async fn get_entry_links(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    match domain.get_entry_links(claimer.user_uuid, uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(links) => HttpResponse::Ok().json(links),
+    }
+}
+
+pub async fn get_entry_backlinks(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    match domain.get_entry_backlinks(claimer.user_uuid, uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(entries) => HttpResponse::Ok().json(entries),
+    }
+}
+
 pub async fn delete_entry(
     wrap_state: Data<Arc<Mutex<AppState>>>,
     req: HttpRequest,
@@ -175,6 +377,36 @@ pub async fn delete_entry(
     }
 }
 
+pub async fn get_graph(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+    parameters: web::Query<GraphParameters>,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let center_uuid = match &parameters.entry {
+        Some(entry) => match uuid::Uuid::parse_str(entry) {
+            Ok(entry_uuid) => Some(entry_uuid),
+            Err(_) => return HttpResponse::BadRequest().body("Invalid entry."),
+        },
+        None => None,
+    };
+
+    match domain
+        .get_graph(
+            claimer.user_uuid,
+            center_uuid,
+            parameters.depth.unwrap_or(1),
+        )
+        .await
+    {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(graph) => HttpResponse::Ok().json(graph),
+    }
+}
+
 pub async fn get_all_boards(
     wrap_state: Data<Arc<Mutex<AppState>>>,
     req: HttpRequest,
@@ -221,13 +453,17 @@ pub async fn create_board(
 
 pub async fn update_board(
     wrap_state: Data<Arc<Mutex<AppState>>>,
-    json: web::Json<Board>,
+    (json, req): (web::Json<Board>, HttpRequest),
 ) -> HttpResponse {
     let state = wrap_state.lock().unwrap();
     let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let mut board: Board = json.into_inner();
+    board.owner = Some(claimer.user_uuid);
 
-    let board: Board = json.into_inner();
     match domain.update_board(board).await {
+        Err(MemorizDomainError::Forbidden) => HttpResponse::Forbidden().body("Forbidden."),
         Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
         Ok(board) => HttpResponse::Ok().json(board),
     }
@@ -248,3 +484,60 @@ pub async fn delete_board(
         Ok(board) => HttpResponse::Ok().json(board),
     }
 }
+
+pub async fn renew_calendar_token(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    match domain.renew_calendar_token(claimer.user_uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(token) => HttpResponse::Ok().json(CalendarTokenResponse { token }),
+    }
+}
+
+//Not behind the authentication middleware: calendar apps only know the feed URL.
+pub async fn get_calendar(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    parameters: web::Query<CalendarParameters>,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+
+    let board_filter = match &parameters.board {
+        Some(board) => match uuid::Uuid::parse_str(board) {
+            Ok(board_uuid) => Some(board_uuid),
+            Err(_) => return HttpResponse::BadRequest().body("Invalid board."),
+        },
+        None => None,
+    };
+
+    let component = match parameters.component.as_deref() {
+        Some("vevent") => CalendarComponent::Event,
+        _ => CalendarComponent::Todo,
+    };
+
+    match domain
+        .get_calendar(
+            parameters.token.to_string(),
+            board_filter,
+            match &parameters.archived {
+                Some(filter) => Some(filter.to_string() == "true"),
+                None => None,
+            },
+            component,
+        )
+        .await
+    {
+        Err(MemorizDomainError::NotFoundError) => {
+            HttpResponse::Unauthorized().body("Unknown calendar token.")
+        }
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(calendar) => HttpResponse::Ok()
+            .content_type("text/calendar; charset=utf-8")
+            .body(calendar),
+    }
+}
@@ -0,0 +1,69 @@
+use crate::state::AppState;
+use actix_web::{web, web::Bytes, web::Data, HttpRequest, HttpResponse};
+use helix_auth_lib::HelixAuth;
+use helix_memoriz_domain::business::error::{EntryDomainResult, MemorizDomainError};
+use helix_memoriz_domain::core::document::EntryDocument;
+use std::sync::{Arc, Mutex};
+
+//Heads of the document, comma separated, next to the binary Automerge data.
+const HEADS_HEADER: &str = "X-Document-Heads";
+
+#[derive(Deserialize)]
+pub struct MergeParameters {
+    heads: Option<String>,
+}
+
+fn document_response(result: EntryDomainResult<EntryDocument>) -> HttpResponse {
+    match result {
+        Err(MemorizDomainError::NotFoundError) => HttpResponse::NotFound().body("Not Found."),
+        Err(MemorizDomainError::Forbidden) => HttpResponse::Forbidden().body("Forbidden."),
+        Err(MemorizDomainError::Document(message)) => HttpResponse::BadRequest().body(message),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(document) => HttpResponse::Ok()
+            .content_type("application/octet-stream")
+            .header(HEADS_HEADER, document.heads.join(","))
+            .body(document.document),
+    }
+}
+
+//Whole Automerge document of the entry content, to start editing from.
+pub async fn get_entry_document(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    document_response(domain.get_entry_document(claimer.user_uuid, uuid).await)
+}
+
+//The body holds the changes of the client, "heads" the heads it knew.
+//Answers with the changes made since by the others.
+pub async fn merge_entry_document(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (body, req): (Bytes, HttpRequest),
+    parameters: web::Query<MergeParameters>,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+    let heads: Vec<String> = match &parameters.heads {
+        Some(heads) => heads
+            .split(',')
+            .filter(|head| !head.is_empty())
+            .map(|head| head.to_string())
+            .collect(),
+        None => Vec::new(),
+    };
+
+    document_response(
+        domain
+            .merge_entry_document(claimer.user_uuid, uuid, body.to_vec(), heads)
+            .await,
+    )
+}
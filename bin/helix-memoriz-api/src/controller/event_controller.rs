@@ -0,0 +1,22 @@
+use crate::state::AppState;
+use actix_web::dev::BodyEncoding;
+use actix_web::http::ContentEncoding;
+use actix_web::{web::Bytes, web::Data, Error, HttpRequest, HttpResponse};
+use futures::StreamExt;
+use helix_auth_lib::HelixAuth;
+use std::sync::{Arc, Mutex};
+
+//Server-sent events stream of the changes made to the items of the user.
+pub async fn get_events(wrap_state: Data<Arc<Mutex<AppState>>>, req: HttpRequest) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let events = state.get_event_broadcaster().subscribe(claimer.user_uuid);
+
+    //Not compressed: the compression would hold the events back.
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .header("Cache-Control", "no-cache")
+        .encoding(ContentEncoding::Identity)
+        .streaming(events.map(Ok::<Bytes, Error>))
+}
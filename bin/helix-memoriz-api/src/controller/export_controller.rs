@@ -0,0 +1,43 @@
+use crate::state::AppState;
+use actix_web::{error, web::Bytes, web::Data, Error, HttpRequest, HttpResponse};
+use futures::stream;
+use helix_auth_lib::HelixAuth;
+use std::sync::{Arc, Mutex};
+
+const MARKDOWN_EXPORT_FILE_NAME: &str = "memoriz-markdown.zip";
+
+pub async fn export_markdown(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    //The export outlives the request handler: it uses the domain without the state locked.
+    let domain = wrap_state.lock().unwrap().get_shared_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    match domain.export_markdown(claimer.user_uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        //The archive is built chunk by chunk while the response is sent.
+        Ok(export) => {
+            let chunks = stream::unfold(Some(export), move |export| {
+                let domain = domain.clone();
+                async move {
+                    let mut export = export?;
+                    match domain.next_markdown_export_chunk(&mut export).await {
+                        Ok(Some(chunk)) => Some((Ok(Bytes::from(chunk)), Some(export))),
+                        Ok(None) => None,
+                        //The client gets a truncated archive, which cannot be opened.
+                        Err(e) => Some((Err(error::ErrorInternalServerError(e)), None)),
+                    }
+                }
+            });
+
+            HttpResponse::Ok()
+                .content_type("application/zip")
+                .header(
+                    "Content-Disposition",
+                    format!("attachment; filename=\"{}\"", MARKDOWN_EXPORT_FILE_NAME),
+                )
+                .streaming::<_, Error>(Box::pin(chunks))
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::controller::upload::read_uploaded_file;
+use crate::state::AppState;
+use actix_multipart::Multipart;
+use actix_web::{web::Data, HttpRequest, HttpResponse};
+use helix_auth_lib::HelixAuth;
+use helix_memoriz_domain::business::error::{EntryDomainResult, MemorizDomainError};
+use helix_memoriz_domain::core::import::ImportReport;
+use std::sync::{Arc, Mutex};
+
+fn import_response(result: EntryDomainResult<ImportReport>) -> HttpResponse {
+    match result {
+        Err(MemorizDomainError::Import(message)) => HttpResponse::BadRequest().body(message),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(report) => HttpResponse::Ok().json(report),
+    }
+}
+
+pub async fn import_keep(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+    mut payload: Multipart,
+) -> HttpResponse {
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    //The upload is read before locking the state, the import runs without it locked:
+    //a large archive would hold every other request meanwhile.
+    let upload = match read_uploaded_file(&mut payload).await {
+        Ok(upload) => upload,
+        Err(response) => return response,
+    };
+
+    let domain = wrap_state.lock().unwrap().get_shared_domain();
+
+    import_response(domain.import_keep(claimer.user_uuid, upload.content).await)
+}
+
+pub async fn import_markdown_vault(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+    mut payload: Multipart,
+) -> HttpResponse {
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let upload = match read_uploaded_file(&mut payload).await {
+        Ok(upload) => upload,
+        Err(response) => return response,
+    };
+
+    let domain = wrap_state.lock().unwrap().get_shared_domain();
+
+    import_response(
+        domain
+            .import_markdown_vault(claimer.user_uuid, upload.content)
+            .await,
+    )
+}
+
+pub async fn import_trello(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+    mut payload: Multipart,
+) -> HttpResponse {
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let upload = match read_uploaded_file(&mut payload).await {
+        Ok(upload) => upload,
+        Err(response) => return response,
+    };
+
+    let domain = wrap_state.lock().unwrap().get_shared_domain();
+
+    import_response(
+        domain
+            .import_trello(claimer.user_uuid, upload.content)
+            .await,
+    )
+}
+
+pub async fn import_enex(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+    mut payload: Multipart,
+) -> HttpResponse {
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let upload = match read_uploaded_file(&mut payload).await {
+        Ok(upload) => upload,
+        Err(response) => return response,
+    };
+
+    let domain = wrap_state.lock().unwrap().get_shared_domain();
+
+    //The notebook is named after the uploaded file.
+    import_response(
+        domain
+            .import_enex(
+                claimer.user_uuid,
+                upload.file_name.unwrap_or_default(),
+                upload.content,
+            )
+            .await,
+    )
+}
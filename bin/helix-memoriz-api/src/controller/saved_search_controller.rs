@@ -0,0 +1,90 @@
+use crate::state::AppState;
+use actix_web::{web, web::Data, HttpRequest, HttpResponse};
+use helix_auth_lib::HelixAuth;
+use helix_memoriz_domain::business::error::MemorizDomainError;
+use helix_memoriz_domain::core::search::SavedSearch;
+use std::sync::{Arc, Mutex};
+
+pub async fn get_all_saved_searches(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    match domain.get_all_saved_searches(claimer.user_uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(saved_searches) => HttpResponse::Ok().json(saved_searches),
+    }
+}
+
+pub async fn get_saved_search(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    match domain.get_saved_search(claimer.user_uuid, uuid).await {
+        Err(MemorizDomainError::NotFoundError) => HttpResponse::NotFound().body("Not Found."),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(saved_search) => HttpResponse::Ok().json(saved_search),
+    }
+}
+
+pub async fn create_saved_search(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (json, req): (web::Json<SavedSearch>, HttpRequest),
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let mut saved_search: SavedSearch = json.into_inner();
+    saved_search.owner = Some(claimer.user_uuid);
+
+    match domain.create_saved_search(saved_search).await {
+        Err(MemorizDomainError::Query(message)) => HttpResponse::BadRequest().body(message),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(saved_search) => HttpResponse::Ok().json(saved_search),
+    }
+}
+
+pub async fn update_saved_search(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (json, req): (web::Json<SavedSearch>, HttpRequest),
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let mut saved_search: SavedSearch = json.into_inner();
+    saved_search.owner = Some(claimer.user_uuid);
+
+    match domain.update_saved_search(saved_search).await {
+        Err(MemorizDomainError::Query(message)) => HttpResponse::BadRequest().body(message),
+        Err(MemorizDomainError::NotFoundError) => HttpResponse::NotFound().body("Not Found."),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(saved_search) => HttpResponse::Ok().json(saved_search),
+    }
+}
+
+pub async fn delete_saved_search(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    match domain.delete_saved_search(claimer.user_uuid, uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(_) => HttpResponse::Ok().finish(),
+    }
+}
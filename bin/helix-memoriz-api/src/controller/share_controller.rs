@@ -0,0 +1,228 @@
+use crate::state::AppState;
+use actix_web::{web, web::Data, HttpRequest, HttpResponse};
+use chrono::prelude::*;
+use helix_auth_lib::HelixAuth;
+use helix_memoriz_domain::business::error::MemorizDomainError;
+use helix_memoriz_domain::business::markdown::render_public_html;
+use helix_memoriz_domain::core::share::{ShareRole, SharedItemKind};
+use std::sync::{Arc, Mutex};
+
+#[derive(Deserialize)]
+pub struct ShareRequest {
+    item: uuid::Uuid,
+    kind: SharedItemKind,
+    recipient: uuid::Uuid,
+    role: ShareRole,
+}
+
+#[derive(Deserialize)]
+pub struct PublicLinkRequest {
+    item: uuid::Uuid,
+    kind: SharedItemKind,
+    expires_on: Option<DateTime<Utc>>,
+    password: Option<String>,
+}
+
+//Header of the password of a protected link, kept out of the logged query string.
+const PUBLIC_LINK_PASSWORD_HEADER: &str = "X-Link-Password";
+
+#[derive(Deserialize)]
+pub struct PublicItemParameters {
+    token: String,
+    format: Option<String>,
+}
+
+//Form of the HTML password page, which cannot set a header.
+#[derive(Deserialize)]
+pub struct PublicItemForm {
+    token: String,
+    password: Option<String>,
+    format: Option<String>,
+}
+
+pub async fn share_item(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (json, req): (web::Json<ShareRequest>, HttpRequest),
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let request: ShareRequest = json.into_inner();
+    match domain
+        .share_item(
+            claimer.user_uuid,
+            request.kind,
+            request.item,
+            request.recipient,
+            request.role,
+        )
+        .await
+    {
+        Err(MemorizDomainError::NotFoundError) => HttpResponse::NotFound().body("Not Found."),
+        Err(MemorizDomainError::Forbidden) => HttpResponse::Forbidden().body("Forbidden."),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(share) => HttpResponse::Ok().json(share),
+    }
+}
+
+pub async fn get_item_shares(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    match domain.get_item_shares(claimer.user_uuid, uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(shares) => HttpResponse::Ok().json(shares),
+    }
+}
+
+pub async fn unshare_item(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+    let recipient: uuid::Uuid =
+        uuid::Uuid::parse_str(req.match_info().get("recipient").unwrap()).unwrap();
+
+    match domain
+        .unshare_item(claimer.user_uuid, uuid, recipient)
+        .await
+    {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(result) => HttpResponse::Ok().json(result),
+    }
+}
+
+//Boards and entries other users shared with the user.
+pub async fn get_shared_with_me(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    match domain.get_shared_with_me(claimer.user_uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(shared) => HttpResponse::Ok().json(shared),
+    }
+}
+
+pub async fn create_public_link(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (json, req): (web::Json<PublicLinkRequest>, HttpRequest),
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let request: PublicLinkRequest = json.into_inner();
+    match domain
+        .create_public_link(
+            claimer.user_uuid,
+            request.kind,
+            request.item,
+            request.expires_on,
+            request.password,
+        )
+        .await
+    {
+        Err(MemorizDomainError::NotFoundError) => HttpResponse::NotFound().body("Not Found."),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(link) => HttpResponse::Ok().json(link),
+    }
+}
+
+pub async fn get_item_public_links(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    match domain.get_item_public_links(claimer.user_uuid, uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(links) => HttpResponse::Ok().json(links),
+    }
+}
+
+pub async fn revoke_public_link(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let token = req.match_info().get("token").unwrap().to_string();
+
+    match domain.revoke_public_link(claimer.user_uuid, token).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(result) => HttpResponse::Ok().json(result),
+    }
+}
+
+//Not behind the authentication middleware: the token is the only credential.
+pub async fn get_public_item(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (parameters, req): (web::Query<PublicItemParameters>, HttpRequest),
+) -> HttpResponse {
+    let parameters = parameters.into_inner();
+    let password = req
+        .headers()
+        .get(PUBLIC_LINK_PASSWORD_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.to_string());
+
+    public_item_response(wrap_state, parameters.token, password, parameters.format).await
+}
+
+pub async fn post_public_item(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    form: web::Form<PublicItemForm>,
+) -> HttpResponse {
+    let form = form.into_inner();
+    public_item_response(wrap_state, form.token, form.password, form.format).await
+}
+
+async fn public_item_response(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    token: String,
+    password: Option<String>,
+    format: Option<String>,
+) -> HttpResponse {
+    //Checking the password is slow: other requests must not wait for it.
+    let domain = wrap_state.lock().unwrap().get_shared_domain();
+
+    match domain.get_public_item(token, password).await {
+        Err(MemorizDomainError::NotFoundError) => {
+            HttpResponse::NotFound().body("Unknown or expired link.")
+        }
+        Err(MemorizDomainError::Forbidden) => {
+            HttpResponse::Unauthorized().body("Missing or wrong password.")
+        }
+        Err(MemorizDomainError::TooManyAttempts) => {
+            HttpResponse::TooManyRequests().body("Too many wrong passwords, try again later.")
+        }
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(item) => match format.as_deref() {
+            Some("html") => HttpResponse::Ok()
+                .content_type("text/html; charset=utf-8")
+                .body(render_public_html(&item)),
+            _ => HttpResponse::Ok().json(item),
+        },
+    }
+}
@@ -0,0 +1,20 @@
+use crate::state::AppState;
+use actix_web::{web, web::Data, HttpRequest, HttpResponse};
+use helix_auth_lib::HelixAuth;
+use helix_memoriz_domain::core::sync::SyncRequest;
+use std::sync::{Arc, Mutex};
+
+//Apply the mutations made offline by a client, then send what changed since its cursor.
+pub async fn sync(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (json, req): (web::Json<SyncRequest>, HttpRequest),
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    match domain.sync(claimer.user_uuid, json.into_inner()).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(response) => HttpResponse::Ok().json(response),
+    }
+}
@@ -0,0 +1,115 @@
+use crate::state::AppState;
+use actix_web::{web, web::Data, HttpRequest, HttpResponse};
+use helix_auth_lib::HelixAuth;
+use helix_memoriz_domain::business::error::MemorizDomainError;
+use helix_memoriz_domain::core::template::{Template, TemplateEntryRequest};
+use std::sync::{Arc, Mutex};
+
+pub async fn get_all_templates(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    match domain.get_all_templates(claimer.user_uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(templates) => HttpResponse::Ok().json(templates),
+    }
+}
+
+pub async fn get_template(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    match domain.get_template(claimer.user_uuid, uuid).await {
+        Err(MemorizDomainError::NotFoundError) => HttpResponse::NotFound().body("Not Found."),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(template) => HttpResponse::Ok().json(template),
+    }
+}
+
+pub async fn create_template(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (json, req): (web::Json<Template>, HttpRequest),
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let mut template: Template = json.into_inner();
+    template.owner = Some(claimer.user_uuid);
+
+    match domain.create_template(template).await {
+        Err(MemorizDomainError::Template(message)) => HttpResponse::BadRequest().body(message),
+        Err(MemorizDomainError::NotFoundError) => HttpResponse::NotFound().body("Not Found."),
+        Err(MemorizDomainError::Forbidden) => HttpResponse::Forbidden().body("Forbidden."),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(template) => HttpResponse::Ok().json(template),
+    }
+}
+
+pub async fn update_template(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (json, req): (web::Json<Template>, HttpRequest),
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let mut template: Template = json.into_inner();
+    template.owner = Some(claimer.user_uuid);
+
+    match domain.update_template(template).await {
+        Err(MemorizDomainError::Template(message)) => HttpResponse::BadRequest().body(message),
+        Err(MemorizDomainError::NotFoundError) => HttpResponse::NotFound().body("Not Found."),
+        Err(MemorizDomainError::Forbidden) => HttpResponse::Forbidden().body("Forbidden."),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(template) => HttpResponse::Ok().json(template),
+    }
+}
+
+pub async fn delete_template(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    req: HttpRequest,
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    match domain.delete_template(claimer.user_uuid, uuid).await {
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(_) => HttpResponse::Ok().finish(),
+    }
+}
+
+//New entry laid out by the template, "{}" takes the defaults of the template.
+pub async fn create_entry_from_template(
+    wrap_state: Data<Arc<Mutex<AppState>>>,
+    (json, req): (web::Json<TemplateEntryRequest>, HttpRequest),
+) -> HttpResponse {
+    let state = wrap_state.lock().unwrap();
+    let domain = state.get_domain();
+    let claimer = HelixAuth::get_claimer(&req).unwrap();
+
+    let uuid: uuid::Uuid = uuid::Uuid::parse_str(req.match_info().get("uuid").unwrap()).unwrap();
+
+    match domain
+        .create_entry_from_template(claimer.user_uuid, uuid, json.into_inner())
+        .await
+    {
+        Err(MemorizDomainError::NotFoundError) => HttpResponse::NotFound().body("Not Found."),
+        Err(MemorizDomainError::Forbidden) => HttpResponse::Forbidden().body("Forbidden."),
+        Err(_) => HttpResponse::InternalServerError().body("Internal Server Error."),
+        Ok(entry) => HttpResponse::Ok().json(entry),
+    }
+}
@@ -0,0 +1,56 @@
+use crate::configuration::Configuration;
+use actix_multipart::Multipart;
+use actix_web::HttpResponse;
+use futures::TryStreamExt;
+
+pub struct UploadedFile {
+    pub file_name: Option<String>,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+//Read the first file of a multipart upload, up to the configured size.
+//The error is the response to send back.
+pub async fn read_uploaded_file(payload: &mut Multipart) -> Result<UploadedFile, HttpResponse> {
+    read_uploaded_file_up_to(payload, Configuration::get_max_upload_size()).await
+}
+
+pub async fn read_uploaded_file_up_to(
+    payload: &mut Multipart,
+    max_size: usize,
+) -> Result<UploadedFile, HttpResponse> {
+    let mut field = match payload.try_next().await {
+        Ok(Some(field)) => field,
+        Ok(None) => return Err(HttpResponse::BadRequest().body("No file uploaded.")),
+        Err(_) => return Err(HttpResponse::BadRequest().body("Invalid upload.")),
+    };
+
+    let file_name = field
+        .content_disposition()
+        .and_then(|disposition| disposition.get_filename().map(|name| name.to_string()));
+    let content_type = field.content_type().to_string();
+
+    let mut content: Vec<u8> = Vec::new();
+    loop {
+        match field.try_next().await {
+            Ok(Some(chunk)) => {
+                if content.len() + chunk.len() > max_size {
+                    return Err(HttpResponse::PayloadTooLarge().body("Upload too large."));
+                }
+                content.extend_from_slice(&chunk);
+            }
+            Ok(None) => break,
+            Err(_) => return Err(HttpResponse::BadRequest().body("Invalid upload.")),
+        }
+    }
+
+    if content.is_empty() {
+        return Err(HttpResponse::BadRequest().body("Empty upload."));
+    }
+
+    Ok(UploadedFile {
+        file_name,
+        content_type,
+        content,
+    })
+}
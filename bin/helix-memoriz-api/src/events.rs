@@ -0,0 +1,81 @@
+use actix_rt::time::interval;
+use actix_web::web::Bytes;
+use futures::channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender};
+use helix_memoriz_domain::core::event::ChangeEvent;
+use helix_memoriz_domain::notifier::traits::EventPublisherTrait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+//Server-sent events streams of the connected clients, by user.
+#[derive(Clone, Default)]
+pub struct EventBroadcaster {
+    clients: Arc<Mutex<HashMap<uuid::Uuid, Vec<UnboundedSender<Bytes>>>>>,
+}
+
+impl EventBroadcaster {
+    pub fn new() -> Self {
+        EventBroadcaster {
+            clients: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    //Open the stream of a new client of the user.
+    pub fn subscribe(&self, user_uuid: uuid::Uuid) -> UnboundedReceiver<Bytes> {
+        let (sender, receiver) = unbounded();
+
+        //Sent at once: the client knows the stream is open.
+        sender
+            .unbounded_send(Bytes::from_static(b": connected\n\n"))
+            .ok();
+        self.clients
+            .lock()
+            .unwrap()
+            .entry(user_uuid)
+            .or_default()
+            .push(sender);
+        receiver
+    }
+
+    //Comment line keeping idle streams open through proxies.
+    pub fn keep_alive(&self) {
+        let users: Vec<uuid::Uuid> = self.clients.lock().unwrap().keys().copied().collect();
+        self.send(&users, Bytes::from_static(b": ping\n\n"));
+    }
+
+    //The stream of a disconnected client is closed: its sender is dropped.
+    fn send(&self, users: &[uuid::Uuid], message: Bytes) {
+        let mut clients = self.clients.lock().unwrap();
+        for user_uuid in users {
+            if let Some(senders) = clients.get_mut(user_uuid) {
+                senders.retain(|sender| sender.unbounded_send(message.clone()).is_ok());
+                if senders.is_empty() {
+                    clients.remove(user_uuid);
+                }
+            }
+        }
+    }
+}
+
+impl EventPublisherTrait for EventBroadcaster {
+    fn publish(&self, recipients: &[uuid::Uuid], event: &ChangeEvent) {
+        match serde_json::to_string(event) {
+            Ok(data) => self.send(
+                recipients,
+                Bytes::from(format!("event: change\ndata: {}\n\n", data)),
+            ),
+            Err(e) => println!("EVENT {:?} not published: {}", event, e),
+        }
+    }
+}
+
+//Periodically ping the connected clients, dropping the disconnected ones.
+pub fn start_events_keep_alive(broadcaster: EventBroadcaster, period_in_seconds: u64) {
+    actix_rt::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(period_in_seconds));
+        loop {
+            ticker.tick().await;
+            broadcaster.keep_alive();
+        }
+    });
+}
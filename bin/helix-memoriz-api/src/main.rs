@@ -3,9 +3,20 @@ extern crate serde_derive;
 
 pub mod configuration;
 pub mod controller;
+pub mod events;
+pub mod scheduler;
 pub mod state;
 
-use crate::controller::{business_controller::*, internal_controller::*};
+use crate::configuration::Configuration;
+use crate::controller::{
+    attachment_controller::*, business_controller::*, document_controller::*, event_controller::*,
+    export_controller::*, import_controller::*, internal_controller::*, saved_search_controller::*,
+    share_controller::*, sync_controller::*, template_controller::*,
+};
+use crate::events::start_events_keep_alive;
+use crate::scheduler::{
+    start_attachment_processor, start_link_preview_processor, start_reminder_scheduler,
+};
 use crate::state::AppState;
 use actix_web::{middleware, web, App, HttpServer};
 use helix_auth_lib::middleware::AuthValidator;
@@ -34,6 +45,26 @@ async fn main() -> io::Result<()> {
     //Define a global state for all the Actix-Worker
     let app_state = Arc::new(Mutex::new(AppState::new()));
 
+    //Reminders are dispatched in the background.
+    start_reminder_scheduler(app_state.clone(), Configuration::get_reminder_interval());
+    start_attachment_processor(
+        app_state.clone(),
+        Configuration::get_attachment_interval(),
+        Configuration::get_attachment_batch_size(),
+    );
+    //Link previews need requests to other sites: they are only fetched when allowed.
+    if Configuration::get_link_fetcher() == "http" {
+        start_link_preview_processor(
+            app_state.clone(),
+            Configuration::get_link_preview_interval(),
+            Configuration::get_link_preview_batch_size(),
+        );
+    }
+    start_events_keep_alive(
+        app_state.lock().unwrap().get_event_broadcaster().clone(),
+        Configuration::get_events_keep_alive_interval(),
+    );
+
     //Start server
     HttpServer::new(move || {
         App::new()
@@ -44,6 +75,9 @@ async fn main() -> io::Result<()> {
             .service(
                 web::scope("/api")
                     .route("/_", web::get().to(healthcheck))
+                    .route("/calendar.ics", web::get().to(get_calendar))
+                    .route("/public", web::get().to(get_public_item))
+                    .route("/public", web::post().to(post_public_item))
                     .service(web::scope("/").configure(get_routes_configuration)),
             )
             .service(web::scope("").route("/{filename:.*}", web::get().to(serve_static_file)))
@@ -69,14 +103,45 @@ fn get_routes_configuration(cfg: &mut web::ServiceConfig) {
                     .route("", web::post().to(create_entry))
                     .route("", web::put().to(update_entry))
                     .route("/search", web::get().to(search_entries))
+                    //Whole pages are bigger than the default JSON limit.
+                    .service(
+                        web::resource("/clip")
+                            .app_data(
+                                web::JsonConfig::default()
+                                    .limit(Configuration::get_max_clip_size()),
+                            )
+                            .route(web::post().to(clip_entry)),
+                    )
                     .route("/by-board/{uuid}", web::get().to(get_all_entries_by_board))
+                    .route(
+                        "/by-saved-search/{uuid}",
+                        web::get().to(get_all_entries_by_saved_search),
+                    )
                     .route("/by-label/{id}", web::get().to(unimplemented))
                     .service(
                         web::scope("/{uuid}")
                             .route("", web::get().to(get_entry))
                             .route("", web::delete().to(delete_entry))
                             .route("/do-archive", web::post().to(archive_entry))
-                            .route("/undo-archive", web::post().to(undo_archive_entry)),
+                            .route("/undo-archive", web::post().to(undo_archive_entry))
+                            .route("/document", web::get().to(get_entry_document))
+                            .route("/document", web::post().to(merge_entry_document))
+                            .route("/attachments", web::get().to(get_entry_attachments))
+                            .route("/attachments", web::post().to(add_attachment))
+                            .route(
+                                "/attachments/{attachment}",
+                                web::get().to(get_attachment_content),
+                            )
+                            .route(
+                                "/attachments/{attachment}",
+                                web::delete().to(delete_attachment),
+                            )
+                            .route(
+                                "/attachments/{attachment}/thumbnails/{size}",
+                                web::get().to(get_attachment_thumbnail),
+                            )
+                            .route("/links", web::get().to(get_entry_links))
+                            .route("/backlinks", web::get().to(get_entry_backlinks)),
                     ),
             )
             .service(
@@ -90,6 +155,54 @@ fn get_routes_configuration(cfg: &mut web::ServiceConfig) {
                             .route("", web::delete().to(delete_board)),
                     ),
             )
+            .service(
+                web::scope("/saved-searches")
+                    .route("", web::get().to(get_all_saved_searches))
+                    .route("", web::post().to(create_saved_search))
+                    .route("", web::put().to(update_saved_search))
+                    .service(
+                        web::scope("/{uuid}")
+                            .route("", web::get().to(get_saved_search))
+                            .route("", web::delete().to(delete_saved_search)),
+                    ),
+            )
+            .service(
+                web::scope("/templates")
+                    .route("", web::get().to(get_all_templates))
+                    .route("", web::post().to(create_template))
+                    .route("", web::put().to(update_template))
+                    .service(
+                        web::scope("/{uuid}")
+                            .route("", web::get().to(get_template))
+                            .route("", web::delete().to(delete_template))
+                            .route("/entries", web::post().to(create_entry_from_template)),
+                    ),
+            )
+            .service(
+                web::scope("/import")
+                    .route("/keep", web::post().to(import_keep))
+                    .route("/markdown", web::post().to(import_markdown_vault))
+                    .route("/trello", web::post().to(import_trello))
+                    .route("/enex", web::post().to(import_enex)),
+            )
+            .service(web::scope("/export").route("/markdown", web::get().to(export_markdown)))
+            .service(
+                web::scope("/shares")
+                    .route("", web::post().to(share_item))
+                    .route("/{uuid}", web::get().to(get_item_shares))
+                    .route("/{uuid}/{recipient}", web::delete().to(unshare_item)),
+            )
+            .service(
+                web::scope("/public-links")
+                    .route("", web::post().to(create_public_link))
+                    .route("/by-item/{uuid}", web::get().to(get_item_public_links))
+                    .route("/{token}", web::delete().to(revoke_public_link)),
+            )
+            .service(web::scope("/shared").route("", web::get().to(get_shared_with_me)))
+            .service(web::scope("/sync").route("", web::post().to(sync)))
+            .service(web::scope("/events").route("", web::get().to(get_events)))
+            .service(web::scope("/graph").route("", web::get().to(get_graph)))
+            .service(web::scope("/calendar").route("/token", web::post().to(renew_calendar_token)))
             .service(
                 web::scope("/labels")
                     .route("", web::get().to(unimplemented))
@@ -108,6 +221,8 @@ fn get_exception_uri() -> Vec<String> {
     let mut exception_uri = Vec::new();
     exception_uri.push("/api/_".to_string());
     exception_uri.push("/api/version".to_string());
+    exception_uri.push("/api/calendar.ics".to_string());
+    exception_uri.push("/api/public".to_string());
 
     exception_uri
 }
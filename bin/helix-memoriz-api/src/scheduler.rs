@@ -0,0 +1,66 @@
+use crate::state::AppState;
+use actix_rt::time::interval;
+use chrono::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+//Periodically dispatch the reminders coming due.
+pub fn start_reminder_scheduler(app_state: Arc<Mutex<AppState>>, period_in_seconds: u64) {
+    actix_rt::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(period_in_seconds));
+        loop {
+            ticker.tick().await;
+
+            let domain = app_state.lock().unwrap().get_shared_domain();
+            match domain.dispatch_due_reminders(Utc::now()).await {
+                Ok(0) => (),
+                Ok(dispatched) => println!("--> {} reminder(s) dispatched", dispatched),
+                Err(e) => println!("--> Reminders dispatch failed: {}", e),
+            }
+        }
+    });
+}
+
+//Periodically process a bounded batch of new attachments: thumbnails never
+//hold an upload, and a burst of uploads never takes over the workers.
+pub fn start_attachment_processor(
+    app_state: Arc<Mutex<AppState>>,
+    period_in_seconds: u64,
+    batch_size: i64,
+) {
+    actix_rt::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(period_in_seconds));
+        loop {
+            ticker.tick().await;
+
+            let domain = app_state.lock().unwrap().get_shared_domain();
+            match domain.process_attachments(batch_size).await {
+                Ok(0) => (),
+                Ok(processed) => println!("--> {} attachment(s) processed", processed),
+                Err(e) => println!("--> Attachments processing failed: {}", e),
+            }
+        }
+    });
+}
+
+//Periodically fetch a bounded batch of the link previews waiting.
+pub fn start_link_preview_processor(
+    app_state: Arc<Mutex<AppState>>,
+    period_in_seconds: u64,
+    batch_size: i64,
+) {
+    actix_rt::spawn(async move {
+        let mut ticker = interval(Duration::from_secs(period_in_seconds));
+        loop {
+            ticker.tick().await;
+
+            //Pages are fetched without the state locked: a slow site holds no request.
+            let domain = app_state.lock().unwrap().get_shared_domain();
+            match domain.process_link_previews(batch_size).await {
+                Ok(0) => (),
+                Ok(fetched) => println!("--> {} link preview(s) fetched", fetched),
+                Err(e) => println!("--> Link previews fetching failed: {}", e),
+            }
+        }
+    });
+}
@@ -1,28 +1,58 @@
 use crate::configuration::Configuration;
+use crate::events::EventBroadcaster;
+use helix_memoriz_domain::blob::fs_blob_store::FsBlobStore;
+use helix_memoriz_domain::blob::traits::BlobStoreTrait;
 use helix_memoriz_domain::business::domain::MemorizDomain;
 use helix_memoriz_domain::business::traits::DomainTrait;
+use helix_memoriz_domain::fetcher::static_link_fetcher::StaticLinkFetcher;
+use helix_memoriz_domain::fetcher::traits::LinkFetcherTrait;
+use helix_memoriz_domain::notifier::log_notifier::LogNotifier;
+use helix_memoriz_domain::notifier::traits::NotifierTrait;
+use http_link_fetcher::HttpLinkFetcher;
 use meilisearch_search_engine::MsMemorizSearchEngine;
 use pg_db_storage::PgDbMemorizStorage;
+#[cfg(feature = "s3")]
+use s3_blob_store::S3BlobStore;
+use smtp_notifier::SmtpNotifier;
 use std::boxed::Box;
+use std::collections::HashMap;
+use std::sync::Arc;
 
 pub struct AppState {
-    memoriz_domain: Box<dyn DomainTrait + Send>,
+    memoriz_domain: Arc<dyn DomainTrait>,
+    event_broadcaster: EventBroadcaster,
 }
 
 impl AppState {
     pub fn new() -> Self {
+        let event_broadcaster = EventBroadcaster::new();
         AppState {
-            memoriz_domain: Box::new(MemorizDomain::new(
+            memoriz_domain: Arc::new(MemorizDomain::new(
                 AppState::get_pg_storage(),
                 AppState::get_ms_search_engine(),
+                AppState::get_notifier(),
+                Box::new(event_broadcaster.clone()),
+                AppState::get_blob_store(),
+                AppState::get_link_fetcher(),
             )),
+            event_broadcaster: event_broadcaster,
         }
     }
 
-    pub fn get_domain(&self) -> &Box<dyn DomainTrait + Send> {
+    pub fn get_domain(&self) -> &Arc<dyn DomainTrait> {
         &self.memoriz_domain
     }
 
+    //Domain of the background tasks: they use it without holding the state locked,
+    //which would hold every request meanwhile.
+    pub fn get_shared_domain(&self) -> Arc<dyn DomainTrait> {
+        self.memoriz_domain.clone()
+    }
+
+    pub fn get_event_broadcaster(&self) -> &EventBroadcaster {
+        &self.event_broadcaster
+    }
+
     fn get_pg_storage() -> Box<PgDbMemorizStorage> {
         Box::new(
             PgDbMemorizStorage::new(
@@ -47,4 +77,52 @@ impl AppState {
             .unwrap(),
         )
     }
+
+    fn get_notifier() -> Box<dyn NotifierTrait> {
+        match Configuration::get_notifier().as_str() {
+            "smtp" => Box::new(
+                SmtpNotifier::new(
+                    Configuration::get_smtp_host(),
+                    Configuration::get_smtp_port(),
+                    Configuration::get_smtp_tls(),
+                    Configuration::get_smtp_credentials(),
+                    Configuration::get_smtp_from(),
+                    Configuration::get_smtp_default_recipient(),
+                )
+                .unwrap(),
+            ),
+            _ => Box::new(LogNotifier::new()),
+        }
+    }
+
+    fn get_blob_store() -> Box<dyn BlobStoreTrait> {
+        match Configuration::get_blob_store().as_str() {
+            #[cfg(feature = "s3")]
+            "s3" => Box::new(
+                S3BlobStore::new(
+                    Configuration::get_s3_bucket(),
+                    Configuration::get_s3_region(),
+                    Configuration::get_s3_endpoint(),
+                    Configuration::get_s3_access_key(),
+                    Configuration::get_s3_secret_key(),
+                )
+                .unwrap(),
+            ),
+            _ => Box::new(FsBlobStore::new(Configuration::get_blob_folder()).unwrap()),
+        }
+    }
+
+    //Without a fetcher, no page is known: the previews are never fetched.
+    fn get_link_fetcher() -> Box<dyn LinkFetcherTrait> {
+        match Configuration::get_link_fetcher().as_str() {
+            "http" => Box::new(
+                HttpLinkFetcher::new(
+                    Configuration::get_link_preview_timeout(),
+                    Configuration::get_link_preview_max_size(),
+                )
+                .unwrap(),
+            ),
+            _ => Box::new(StaticLinkFetcher::new(HashMap::new())),
+        }
+    }
 }
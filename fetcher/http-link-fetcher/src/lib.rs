@@ -0,0 +1,167 @@
+use async_trait::async_trait;
+use helix_memoriz_domain::fetcher::error::*;
+use helix_memoriz_domain::fetcher::traits::LinkFetcherTrait;
+use reqwest::header::{CONTENT_TYPE, LOCATION};
+use reqwest::{redirect::Policy, Client, StatusCode};
+use std::net::IpAddr;
+use std::time::Duration;
+use url::{Host, Url};
+
+const MAX_REDIRECTIONS: usize = 5;
+const HTML_CONTENT_TYPES: &[&str] = &["text/html", "application/xhtml+xml"];
+const USER_AGENT: &str = concat!(
+    "helix-memoriz/",
+    env!("CARGO_PKG_VERSION"),
+    " (link preview)"
+);
+
+pub struct HttpLinkFetcher {
+    client: Client,
+    max_size: usize,
+}
+
+impl HttpLinkFetcher {
+    pub fn new(timeout_in_seconds: u64, max_size: usize) -> FetcherResult<HttpLinkFetcher> {
+        //Redirections are followed by hand, to check the address of each one.
+        let client = Client::builder()
+            .redirect(Policy::none())
+            .timeout(Duration::from_secs(timeout_in_seconds))
+            .user_agent(USER_AGENT)
+            .build()
+            .map_err(|e| FetcherError::Request(e.to_string()))?;
+
+        Ok(HttpLinkFetcher {
+            client: client,
+            max_size: max_size,
+        })
+    }
+}
+
+//URLs written in the entries must not reach the services of the private network
+//of the server: only public addresses are fetched. The name is resolved again by
+//the request, this is a best effort against a name changing its address meanwhile.
+async fn check_url(url: &Url) -> FetcherResult<()> {
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return Err(FetcherError::InvalidUrl(url.to_string()));
+    }
+
+    let addresses: Vec<IpAddr> = match url.host() {
+        Some(Host::Ipv4(address)) => vec![IpAddr::V4(address)],
+        Some(Host::Ipv6(address)) => vec![IpAddr::V6(address)],
+        Some(Host::Domain(domain)) => {
+            let port = url.port_or_known_default().unwrap_or(80);
+            tokio::net::lookup_host((domain, port))
+                .await
+                .map_err(|e| FetcherError::Request(format!("{}: {}", domain, e)))?
+                .map(|address| address.ip())
+                .collect()
+        }
+        None => return Err(FetcherError::InvalidUrl(url.to_string())),
+    };
+
+    match !addresses.is_empty() && addresses.iter().all(is_public_address) {
+        true => Ok(()),
+        false => Err(FetcherError::ForbiddenAddress(url.to_string())),
+    }
+}
+
+fn is_public_address(address: &IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            let octets = address.octets();
+            !(address.is_private()
+                || address.is_loopback()
+                || address.is_link_local()
+                || address.is_broadcast()
+                || address.is_documentation()
+                || octets[0] == 0
+                //Shared address space of the carrier-grade NATs, 100.64.0.0/10.
+                || (octets[0] == 100 && octets[1] & 0xc0 == 64)
+                || octets[0] >= 240)
+        }
+        IpAddr::V6(address) => {
+            let first_segment = address.segments()[0];
+            !(address.is_loopback()
+                || address.is_unspecified()
+                //Unique local fc00::/7 and link local fe80::/10 addresses.
+                || first_segment & 0xfe00 == 0xfc00
+                || first_segment & 0xffc0 == 0xfe80)
+                && address
+                    .to_ipv4()
+                    .map(|address| is_public_address(&IpAddr::V4(address)))
+                    .unwrap_or(true)
+        }
+    }
+}
+
+fn request_error(error: reqwest::Error) -> FetcherError {
+    FetcherError::Request(error.to_string())
+}
+
+#[async_trait]
+impl LinkFetcherTrait for HttpLinkFetcher {
+    async fn fetch_page(&self, url: &str) -> FetcherResult<String> {
+        let mut url = Url::parse(url).map_err(|_| FetcherError::InvalidUrl(url.to_string()))?;
+
+        for _ in 0..=MAX_REDIRECTIONS {
+            check_url(&url).await?;
+            let mut response = self
+                .client
+                .get(url.clone())
+                .send()
+                .await
+                .map_err(request_error)?;
+
+            let status = response.status();
+            if status.is_redirection() {
+                let location = response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|location| location.to_str().ok())
+                    .ok_or_else(|| FetcherError::Request(format!("{} answered {}", url, status)))?;
+                url = url
+                    .join(location)
+                    .map_err(|_| FetcherError::InvalidUrl(location.to_string()))?;
+                continue;
+            }
+            if status == StatusCode::NOT_FOUND {
+                return Err(FetcherError::NotFound(url.to_string()));
+            }
+            if !status.is_success() {
+                return Err(FetcherError::Request(format!(
+                    "{} answered {}",
+                    url, status
+                )));
+            }
+
+            let content_type = response
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|content_type| content_type.to_str().ok())
+                .unwrap_or_default()
+                .to_lowercase();
+            if !HTML_CONTENT_TYPES
+                .iter()
+                .any(|html| content_type.starts_with(html))
+            {
+                return Err(FetcherError::NotHtml(url.to_string()));
+            }
+
+            //Only the beginning of a big page is read.
+            let mut page: Vec<u8> = Vec::new();
+            while let Some(chunk) = response.chunk().await.map_err(request_error)? {
+                page.extend_from_slice(&chunk);
+                if page.len() >= self.max_size {
+                    page.truncate(self.max_size);
+                    break;
+                }
+            }
+            return Ok(String::from_utf8_lossy(&page).into_owned());
+        }
+
+        Err(FetcherError::Request(format!(
+            "Too many redirections from {}",
+            url
+        )))
+    }
+}
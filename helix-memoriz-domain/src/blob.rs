@@ -0,0 +1,3 @@
+pub mod error;
+pub mod fs_blob_store;
+pub mod traits;
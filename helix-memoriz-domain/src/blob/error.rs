@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+//Define the possible errors
+#[derive(Error, Debug)]
+pub enum BlobError {
+    #[error("NotImplemented")]
+    NotImplemented,
+    #[error("Blob not found: {0}")]
+    NotFound(String),
+    #[error("Invalid blob key: {0}")]
+    InvalidKey(String),
+    #[error("IO error: {source}")]
+    Io {
+        #[from]
+        source: std::io::Error,
+    },
+    #[error("Blob store error: {0}")]
+    Store(String),
+}
+
+pub type BlobResult<T> = std::result::Result<T, BlobError>;
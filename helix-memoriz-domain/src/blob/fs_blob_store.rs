@@ -0,0 +1,110 @@
+use crate::blob::error::{BlobError, BlobResult};
+use crate::blob::traits::BlobStoreTrait;
+use async_trait::async_trait;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs as async_fs;
+
+//Blob store writing one file per blob under a root directory.
+//Files are read and written on blocking threads, never on the executor.
+pub struct FsBlobStore {
+    root: PathBuf,
+}
+
+impl FsBlobStore {
+    pub fn new(root: String) -> BlobResult<FsBlobStore> {
+        fs::create_dir_all(&root)?;
+        Ok(FsBlobStore {
+            root: PathBuf::from(root),
+        })
+    }
+
+    //A key never leaves the root directory.
+    fn get_path(&self, key: &str) -> BlobResult<PathBuf> {
+        let relative = Path::new(key);
+        let valid = !key.is_empty()
+            && relative
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)));
+        match valid {
+            true => Ok(self.root.join(relative)),
+            false => Err(BlobError::InvalidKey(key.to_string())),
+        }
+    }
+}
+
+#[async_trait]
+impl BlobStoreTrait for FsBlobStore {
+    async fn put(&self, key: &str, content: Vec<u8>, _content_type: &str) -> BlobResult<()> {
+        let path = self.get_path(key)?;
+        if let Some(parent) = path.parent() {
+            async_fs::create_dir_all(parent).await?;
+        }
+
+        //Written aside then renamed: a reader never gets a partial blob.
+        let temporary = path.with_extension("part");
+        async_fs::write(&temporary, content).await?;
+        async_fs::rename(&temporary, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> BlobResult<Vec<u8>> {
+        match async_fs::read(self.get_path(key)?).await {
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(BlobError::NotFound(key.to_string())),
+            result => Ok(result?),
+        }
+    }
+
+    async fn delete(&self, key: &str) -> BlobResult<()> {
+        match async_fs::remove_file(self.get_path(key)?).await {
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            result => Ok(result?),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn store() -> FsBlobStore {
+        let root = std::env::temp_dir().join(format!("memoriz-blobs-{}", uuid::Uuid::new_v4()));
+        FsBlobStore::new(root.to_string_lossy().to_string()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn puts_gets_and_deletes_a_blob() {
+        let store = store();
+
+        store
+            .put("owner/attachment", b"content".to_vec(), "text/plain")
+            .await
+            .unwrap();
+        assert_eq!(store.get("owner/attachment").await.unwrap(), b"content");
+
+        store.delete("owner/attachment").await.unwrap();
+        assert!(matches!(
+            store.get("owner/attachment").await,
+            Err(BlobError::NotFound(_))
+        ));
+        //Deleting a missing blob is not an error.
+        store.delete("owner/attachment").await.unwrap();
+        fs::remove_dir_all(&store.root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn refuses_keys_leaving_the_root() {
+        let store = store();
+
+        assert!(matches!(
+            store.put("../outside", Vec::new(), "text/plain").await,
+            Err(BlobError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            store.get("/etc/passwd").await,
+            Err(BlobError::InvalidKey(_))
+        ));
+        fs::remove_dir_all(&store.root).unwrap();
+    }
+}
@@ -0,0 +1,12 @@
+use crate::blob::error::*;
+use async_trait::async_trait;
+
+//Stores the content of the attachments, the storage keeping their description.
+//Keys are paths made of the owner and attachment uuids.
+#[async_trait]
+pub trait BlobStoreTrait: Send + Sync {
+    async fn put(&self, key: &str, content: Vec<u8>, content_type: &str) -> BlobResult<()>;
+    async fn get(&self, key: &str) -> BlobResult<Vec<u8>>;
+    //Deleting a missing blob is not an error.
+    async fn delete(&self, key: &str) -> BlobResult<()>;
+}
@@ -1,3 +1,20 @@
+pub mod attachment;
+pub mod calendar;
+pub mod clip;
+pub mod crdt;
 pub mod domain;
+pub mod dump;
 pub mod error;
+pub mod export;
+pub mod extraction;
+pub mod graph;
+pub mod import;
+pub mod links;
+pub mod markdown;
+pub mod preview;
+pub mod query;
+pub mod search;
+pub mod template;
+pub mod throttle;
+pub mod thumbnail;
 pub mod traits;
@@ -0,0 +1,76 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+
+pub const DEFAULT_FILE_NAME: &str = "attachment";
+const MAX_FILE_NAME_LENGTH: usize = 255;
+
+//Content types accepted as attachments, with the signature their content starts with.
+//Text has no signature: it only has to be valid UTF-8.
+const ACCEPTED_CONTENT_TYPES: &[(&str, &[&[u8]])] = &[
+    ("image/png", &[b"\x89PNG\r\n\x1a\n"]),
+    ("image/jpeg", &[b"\xFF\xD8\xFF"]),
+    ("image/gif", &[b"GIF87a", b"GIF89a"]),
+    ("image/webp", &[b"RIFF"]),
+    ("application/pdf", &[b"%PDF-"]),
+    ("text/plain", &[]),
+    ("text/markdown", &[]),
+];
+
+//Check the declared content type of an upload against its content.
+//Gives the content type without its parameters.
+pub fn check_attachment_content(content_type: &str, content: &[u8]) -> EntryDomainResult<String> {
+    let content_type = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    let signatures = match ACCEPTED_CONTENT_TYPES
+        .iter()
+        .find(|(accepted, _)| *accepted == content_type)
+    {
+        Some((_, signatures)) => signatures,
+        None => {
+            return Err(MemorizDomainError::Attachment(format!(
+                "Unsupported content type: {}",
+                content_type
+            )))
+        }
+    };
+
+    let valid = match content_type.starts_with("text/") {
+        true => std::str::from_utf8(content).is_ok(),
+        false => signatures
+            .iter()
+            .any(|signature| content.starts_with(signature)),
+    };
+    //A WEBP is a RIFF container holding WEBP data.
+    let valid = valid && (content_type != "image/webp" || content.get(8..12) == Some(&b"WEBP"[..]));
+
+    match valid {
+        true => Ok(content_type),
+        false => Err(MemorizDomainError::Attachment(format!(
+            "Content does not match its type: {}",
+            content_type
+        ))),
+    }
+}
+
+//File name of an upload without its directories, as some browsers send them.
+pub fn attachment_file_name(file_name: Option<String>) -> String {
+    let file_name = file_name.unwrap_or_default();
+    let file_name: String = file_name
+        .rsplit(&['/', '\\'][..])
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_FILE_NAME_LENGTH)
+        .collect();
+
+    match file_name.is_empty() {
+        true => DEFAULT_FILE_NAME.to_string(),
+        false => file_name,
+    }
+}
@@ -0,0 +1,222 @@
+use crate::core::entry::Entry;
+use chrono::prelude::*;
+
+const PRODUCT_ID: &str = "-//Helix//Memoriz//EN";
+const MAX_LINE_LENGTH: usize = 75;
+
+//Calendar component used to render each entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CalendarComponent {
+    Todo,
+    Event,
+}
+
+//Render the entries having a due date as an iCalendar (RFC 5545) feed.
+pub fn render_calendar(
+    entries: &[Entry],
+    component: CalendarComponent,
+    now: DateTime<Utc>,
+) -> String {
+    let mut lines: Vec<String> = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODUCT_ID),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Memoriz".to_string(),
+    ];
+
+    for entry in entries.iter().filter(|entry| entry.due_on.is_some()) {
+        lines.append(&mut render_component(entry, component, now));
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut calendar = String::new();
+    for line in lines {
+        calendar.push_str(&fold_line(&line));
+        calendar.push_str("\r\n");
+    }
+    calendar
+}
+
+fn render_component(
+    entry: &Entry,
+    component: CalendarComponent,
+    now: DateTime<Utc>,
+) -> Vec<String> {
+    let name = match component {
+        CalendarComponent::Todo => "VTODO",
+        CalendarComponent::Event => "VEVENT",
+    };
+    let due_on = format_date_time(&entry.due_on.unwrap());
+
+    let mut lines = vec![
+        format!("BEGIN:{}", name),
+        format!(
+            "UID:{}@memoriz",
+            entry.uuid.map(|uuid| uuid.to_string()).unwrap_or_default()
+        ),
+        format!("DTSTAMP:{}", format_date_time(&now)),
+        format!("SUMMARY:{}", escape_text(&entry.title)),
+    ];
+
+    match component {
+        CalendarComponent::Todo => {
+            lines.push(format!("DUE:{}", due_on));
+            lines.push(format!(
+                "STATUS:{}",
+                match entry.archived {
+                    true => "COMPLETED",
+                    false => "NEEDS-ACTION",
+                }
+            ));
+        }
+        CalendarComponent::Event => lines.push(format!("DTSTART:{}", due_on)),
+    }
+
+    if let Some(content) = &entry.content {
+        lines.push(format!("DESCRIPTION:{}", escape_text(content)));
+    }
+    if let Some(created_on) = &entry.created_on {
+        lines.push(format!("CREATED:{}", format_date_time(created_on)));
+    }
+    if let Some(updated_on) = &entry.updated_on {
+        lines.push(format!("LAST-MODIFIED:{}", format_date_time(updated_on)));
+    }
+    if let Some(recurrence) = &entry.recurrence {
+        lines.push(format!("RRULE:{}", recurrence));
+    }
+    if let Some(labels) = &entry.labels {
+        if !labels.is_empty() {
+            let categories: Vec<String> = labels
+                .iter()
+                .map(|label| escape_text(&label.name))
+                .collect();
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+    }
+    if let Some(remind_at) = &entry.remind_at {
+        lines.push("BEGIN:VALARM".to_string());
+        lines.push("ACTION:DISPLAY".to_string());
+        lines.push(format!("DESCRIPTION:{}", escape_text(&entry.title)));
+        lines.push(format!(
+            "TRIGGER;VALUE=DATE-TIME:{}",
+            format_date_time(remind_at)
+        ));
+        lines.push("END:VALARM".to_string());
+    }
+
+    lines.push(format!("END:{}", name));
+    lines
+}
+
+fn format_date_time(date: &DateTime<Utc>) -> String {
+    date.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn escape_text(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+//Lines longer than 75 octets are folded, without splitting a UTF-8 character.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_length = 0;
+
+    for character in line.chars() {
+        let character_length = character.len_utf8();
+        if line_length + character_length > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(character);
+        line_length += character_length;
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::recurrence::Recurrence;
+    use std::str::FromStr;
+
+    #[test]
+    fn escapes_the_text_values() {
+        assert_eq!(escape_text("a,b;c\\d\r\ne\nf"), "a\\,b\\;c\\\\d\\ne\\nf");
+    }
+
+    #[test]
+    fn folds_the_long_lines() {
+        let line = "x".repeat(160);
+        let folded = fold_line(&line);
+        let parts: Vec<&str> = folded.split("\r\n").collect();
+
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].len(), 75);
+        assert_eq!(parts[1].len(), 75);
+        assert!(parts[1].starts_with(' '));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn folds_without_splitting_a_character() {
+        //74 octets then a 3 octets character: it goes on the next line.
+        let line = format!("{}€€", "x".repeat(74));
+        let folded = fold_line(&line);
+
+        assert_eq!(folded, format!("{}\r\n €€", "x".repeat(74)));
+        assert!(folded
+            .split("\r\n")
+            .all(|part| part.len() <= MAX_LINE_LENGTH));
+    }
+
+    #[test]
+    fn renders_a_recurring_event() {
+        let due_on = Utc.ymd(2024, 3, 1).and_hms(9, 30, 0);
+        let entry = Entry::new(
+            0,
+            Some(uuid::Uuid::parse_str("0b4f1d0e-6f4c-4d6e-9a57-3c1f2c6f6d10").unwrap()),
+            "Rent, flat".to_string(),
+            Some("Pay; then\nfile".to_string()),
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(due_on),
+            None,
+            None,
+            Some(Recurrence::from_str("FREQ=MONTHLY;INTERVAL=1").unwrap()),
+            None,
+        );
+        let now = Utc.ymd(2024, 2, 1).and_hms(8, 0, 0);
+
+        assert_eq!(
+            render_calendar(&[entry], CalendarComponent::Event, now),
+            "BEGIN:VCALENDAR\r\n\
+             VERSION:2.0\r\n\
+             PRODID:-//Helix//Memoriz//EN\r\n\
+             CALSCALE:GREGORIAN\r\n\
+             X-WR-CALNAME:Memoriz\r\n\
+             BEGIN:VEVENT\r\n\
+             UID:0b4f1d0e-6f4c-4d6e-9a57-3c1f2c6f6d10@memoriz\r\n\
+             DTSTAMP:20240201T080000Z\r\n\
+             SUMMARY:Rent\\, flat\r\n\
+             DTSTART:20240301T093000Z\r\n\
+             DESCRIPTION:Pay\\; then\\nfile\r\n\
+             RRULE:FREQ=MONTHLY;INTERVAL=1\r\n\
+             END:VEVENT\r\n\
+             END:VCALENDAR\r\n"
+        );
+    }
+}
@@ -0,0 +1,331 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use ammonia::{Builder, Url, UrlRelative};
+use scraper::{ElementRef, Html, Selector};
+use std::collections::HashMap;
+
+const MIN_PARAGRAPH_LENGTH: usize = 25;
+const EXCERPT_LENGTH: usize = 200;
+
+//Class and id words of the page furniture, and of the article itself.
+const UNLIKELY_WORDS: &[&str] = &[
+    "comment",
+    "footer",
+    "footnote",
+    "sidebar",
+    "sponsor",
+    "advert",
+    "banner",
+    "nav",
+    "menu",
+    "share",
+    "social",
+    "related",
+    "promo",
+    "cookie",
+    "popup",
+    "subscribe",
+    "breadcrumb",
+];
+const LIKELY_WORDS: &[&str] = &[
+    "article", "body", "content", "entry", "main", "page", "post", "text", "blog", "story",
+];
+//Paragraphs in these elements are never part of the article.
+const EXCLUDED_TAGS: &[&str] = &["nav", "aside", "footer", "header", "form"];
+const REMOVED_TAGS: &[&str] = &["nav", "aside", "footer", "header"];
+const REMOVED_CONTENT_TAGS: &[&str] = &["nav", "aside", "footer", "header", "form", "noscript"];
+
+#[derive(Debug)]
+pub struct ClippedArticle {
+    pub title: String,
+    pub excerpt: String,
+    pub content: String,
+}
+
+//Readable article of a web page, in Markdown: the element holding most of the text
+//is found by scoring the paragraphs and the elements around them, like Readability.
+//Links and images are made absolute against the page URL.
+pub fn clip_article(url: &str, html: &str) -> EntryDomainResult<ClippedArticle> {
+    let url = match Url::parse(url) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => url,
+        _ => return Err(MemorizDomainError::Clip(format!("Invalid URL: {}", url))),
+    };
+
+    let document = Html::parse_document(html);
+    let mut scores: HashMap<ego_tree::NodeId, f64> = HashMap::new();
+
+    for paragraph in document.select(&selector("p, pre, td")) {
+        if paragraph
+            .ancestors()
+            .filter_map(ElementRef::wrap)
+            .any(|ancestor| EXCLUDED_TAGS.contains(&ancestor.value().name()))
+        {
+            continue;
+        }
+        let text = element_text(&paragraph);
+        let length = text.chars().count();
+        if length < MIN_PARAGRAPH_LENGTH {
+            continue;
+        }
+
+        //A parent gets the whole score of the paragraph, a grand-parent half of it.
+        let score = 1.0 + text.matches(',').count() as f64 + (length / 100).min(3) as f64;
+        let ancestors = paragraph.ancestors().filter_map(ElementRef::wrap).take(2);
+        for (level, ancestor) in ancestors.enumerate() {
+            let candidate = scores
+                .entry(ancestor.id())
+                .or_insert_with(|| initial_score(&ancestor));
+            *candidate += score / (level + 1) as f64;
+        }
+    }
+
+    //Many links for little text is a list of links, not an article.
+    let best = scores
+        .iter()
+        .filter_map(|(id, score)| {
+            let element = ElementRef::wrap(document.tree.get(*id)?)?;
+            Some((element, score * (1.0 - link_density(&element))))
+        })
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap());
+
+    let (article_html, first_paragraph) = match best {
+        Some((element, score)) => (
+            article_with_siblings(&element, score, &scores),
+            first_paragraph(&element),
+        ),
+        None => (
+            document
+                .select(&selector("article, body"))
+                .next()
+                .map(|element| element.inner_html())
+                .unwrap_or_default(),
+            None,
+        ),
+    };
+
+    let clean_html = Builder::default()
+        .rm_tags(REMOVED_TAGS)
+        .add_clean_content_tags(REMOVED_CONTENT_TAGS)
+        .url_relative(UrlRelative::RewriteWithBase(url.to_owned()))
+        .clean(&article_html)
+        .to_string();
+    let content = html2md::parse_html(&clean_html).trim().to_string();
+
+    let title = meta_content(&document, "og:title")
+        .or_else(|| first_text(&document, "title"))
+        .or_else(|| first_text(&document, "h1"))
+        .unwrap_or_else(|| url.host_str().unwrap_or_default().to_string());
+    let excerpt = meta_content(&document, "og:description")
+        .or_else(|| meta_content(&document, "description"))
+        .or(first_paragraph)
+        .unwrap_or_default();
+
+    Ok(ClippedArticle {
+        title: title,
+        excerpt: truncate(&excerpt, EXCERPT_LENGTH),
+        content: content,
+    })
+}
+
+//Siblings scoring close to the best element are parts of the same article,
+//like paragraphs split in several blocks.
+fn article_with_siblings(
+    best: &ElementRef,
+    best_score: f64,
+    scores: &HashMap<ego_tree::NodeId, f64>,
+) -> String {
+    let threshold = (best_score * 0.2).max(10.0);
+    let parent = match best.parent().and_then(ElementRef::wrap) {
+        Some(parent) => parent,
+        None => return best.html(),
+    };
+
+    let mut html = String::new();
+    for sibling in parent.children().filter_map(ElementRef::wrap) {
+        let included = sibling.id() == best.id()
+            || scores
+                .get(&sibling.id())
+                .map(|score| *score >= threshold)
+                .unwrap_or(false)
+            || (sibling.value().name() == "p"
+                && element_text(&sibling).chars().count() > 80
+                && link_density(&sibling) < 0.25);
+        if included {
+            html.push_str(&sibling.html());
+        }
+    }
+    html
+}
+
+//First paragraph of the article, when the page gives no description.
+fn first_paragraph(article: &ElementRef) -> Option<String> {
+    article
+        .select(&selector("p"))
+        .map(|paragraph| element_text(&paragraph))
+        .find(|text| text.chars().count() >= MIN_PARAGRAPH_LENGTH)
+}
+
+fn initial_score(element: &ElementRef) -> f64 {
+    let tag_score = match element.value().name() {
+        "article" => 10.0,
+        "div" => 5.0,
+        "pre" | "td" | "blockquote" => 3.0,
+        "address" | "ol" | "ul" | "dl" | "dd" | "dt" | "li" | "form" => -3.0,
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "th" => -5.0,
+        _ => 0.0,
+    };
+
+    let names = format!(
+        "{} {}",
+        element.value().attr("class").unwrap_or_default(),
+        element.value().attr("id").unwrap_or_default()
+    )
+    .to_lowercase();
+    let mut class_score = 0.0;
+    if UNLIKELY_WORDS.iter().any(|word| names.contains(word)) {
+        class_score -= 25.0;
+    }
+    if LIKELY_WORDS.iter().any(|word| names.contains(word)) {
+        class_score += 25.0;
+    }
+
+    tag_score + class_score
+}
+
+fn link_density(element: &ElementRef) -> f64 {
+    let length = element_text(element).chars().count();
+    if length == 0 {
+        return 0.0;
+    }
+    let link_length: usize = element
+        .select(&selector("a"))
+        .map(|link| element_text(&link).chars().count())
+        .sum();
+    link_length as f64 / length as f64
+}
+
+fn element_text(element: &ElementRef) -> String {
+    element
+        .text()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+pub fn meta_content(document: &Html, name: &str) -> Option<String> {
+    let query = format!("meta[property=\"{0}\"], meta[name=\"{0}\"]", name);
+    document
+        .select(&selector(&query))
+        .filter_map(|meta| meta.value().attr("content"))
+        .map(|content| content.trim().to_string())
+        .find(|content| !content.is_empty())
+}
+
+pub fn first_text(document: &Html, query: &str) -> Option<String> {
+    document
+        .select(&selector(query))
+        .map(|element| element_text(&element))
+        .find(|text| !text.is_empty())
+}
+
+pub fn truncate(text: &str, length: usize) -> String {
+    match text.chars().count() > length {
+        true => format!(
+            "{}…",
+            text.chars().take(length).collect::<String>().trim_end()
+        ),
+        false => text.to_string(),
+    }
+}
+
+//Selectors are written here: they are always valid.
+fn selector(query: &str) -> Selector {
+    Selector::parse(query).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAGE: &str = r#"<html><head><title>Fallback title</title>
+        <meta property="og:title" content="Growing tomatoes">
+        </head><body>
+        <nav class="menu"><a href="/">Home</a> <a href="/blog">Blog</a></nav>
+        <div class="sidebar"><p>Subscribe to our newsletter, it is great, really.</p></div>
+        <article class="post-content">
+        <h1>Growing tomatoes</h1>
+        <p>Tomatoes need sun, water and patience, and a good soil rich in compost.</p>
+        <p>Plant them in spring, after the last frost. See <a href="/guides/stakes">the guide</a>.</p>
+        <img src="images/tomato.jpg" alt="Tomato">
+        <script>track()</script>
+        </article>
+        <footer><p>Copyright, all rights reserved, the tomato company, since 1999.</p></footer>
+        </body></html>"#;
+
+    #[test]
+    fn extracts_the_article() {
+        let article = clip_article("https://example.com/blog/tomatoes", PAGE).unwrap();
+
+        assert_eq!(article.title, "Growing tomatoes");
+        assert_eq!(
+            article.excerpt,
+            "Tomatoes need sun, water and patience, and a good soil rich in compost."
+        );
+        assert!(article
+            .content
+            .contains("Tomatoes need sun, water and patience"));
+        assert!(article
+            .content
+            .contains("[the guide](https://example.com/guides/stakes)"));
+        assert!(article
+            .content
+            .contains("![Tomato](https://example.com/blog/images/tomato.jpg)"));
+        for furniture in &["Subscribe", "Copyright", "Home", "track()"] {
+            assert!(!article.content.contains(furniture), "{}", furniture);
+        }
+    }
+
+    #[test]
+    fn falls_back_on_the_page_title_and_heading() {
+        let page = PAGE.replace(
+            "<meta property=\"og:title\" content=\"Growing tomatoes\">",
+            "",
+        );
+        let article = clip_article("https://example.com/blog/tomatoes", &page).unwrap();
+        assert_eq!(article.title, "Fallback title");
+
+        let article = clip_article(
+            "https://example.com/short",
+            "<html><body><h1>Only  heading</h1><p>Short</p></body></html>",
+        )
+        .unwrap();
+        assert_eq!(article.title, "Only heading");
+        assert_eq!(article.excerpt, "");
+
+        let article = clip_article("https://example.com/empty", "").unwrap();
+        assert_eq!(article.title, "example.com");
+    }
+
+    #[test]
+    fn prefers_the_description_of_the_page() {
+        let page = PAGE.replace(
+            "</head>",
+            "<meta name=\"description\" content=\" All about tomatoes \"></head>",
+        );
+        let article = clip_article("https://example.com/blog/tomatoes", &page).unwrap();
+        assert_eq!(article.excerpt, "All about tomatoes");
+    }
+
+    #[test]
+    fn refuses_the_other_urls() {
+        assert!(clip_article("ftp://example.com/file", PAGE).is_err());
+        assert!(clip_article("not a url", PAGE).is_err());
+    }
+
+    #[test]
+    fn truncates_on_characters() {
+        assert_eq!(truncate("été à la mer", 5), "été à…");
+        assert_eq!(truncate("court", 5), "court");
+    }
+}
@@ -0,0 +1,116 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use automerge::{transaction::Transactable, AutoCommit, ChangeHash, ObjId, ObjType, ReadDoc, ROOT};
+use std::str::FromStr;
+
+//Key of the text object holding the content in the Automerge document.
+pub const CONTENT_KEY: &str = "content";
+
+//Content of an entry as an Automerge document: concurrent edits merge instead of
+//overwriting each other. The plain content of the entry is a copy of its text.
+pub struct ContentDocument {
+    doc: AutoCommit,
+    text: ObjId,
+}
+
+impl ContentDocument {
+    //Document of an entry edited without it so far.
+    pub fn from_content(content: &str) -> EntryDomainResult<ContentDocument> {
+        let mut doc = AutoCommit::new();
+        let text = doc
+            .put_object(ROOT, CONTENT_KEY, ObjType::Text)
+            .map_err(document_error)?;
+        doc.splice_text(&text, 0, 0, content)
+            .map_err(document_error)?;
+        Ok(ContentDocument {
+            doc: doc,
+            text: text,
+        })
+    }
+
+    pub fn load(bytes: &[u8]) -> EntryDomainResult<ContentDocument> {
+        let doc = AutoCommit::load(bytes).map_err(document_error)?;
+        let text = match doc.get(ROOT, CONTENT_KEY).map_err(document_error)? {
+            Some((_, text)) => text,
+            None => {
+                return Err(MemorizDomainError::Document(format!(
+                    "Missing \"{}\" text",
+                    CONTENT_KEY
+                )))
+            }
+        };
+        Ok(ContentDocument {
+            doc: doc,
+            text: text,
+        })
+    }
+
+    //Apply changes made elsewhere: an incremental save or a whole document.
+    pub fn merge(&mut self, update: &[u8]) -> EntryDomainResult<()> {
+        self.doc.load_incremental(update).map_err(document_error)?;
+        Ok(())
+    }
+
+    pub fn content(&self) -> EntryDomainResult<String> {
+        self.doc.text(&self.text).map_err(document_error)
+    }
+
+    //Replace the text by an edit of the changed part only, so that it merges
+    //with concurrent edits of the other parts.
+    pub fn set_content(&mut self, content: &str) -> EntryDomainResult<()> {
+        let current: Vec<char> = self.content()?.chars().collect();
+        let wanted: Vec<char> = content.chars().collect();
+
+        let prefix = current
+            .iter()
+            .zip(wanted.iter())
+            .take_while(|(a, b)| a == b)
+            .count();
+        let suffix = current[prefix..]
+            .iter()
+            .rev()
+            .zip(wanted[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        let deleted = current.len() - prefix - suffix;
+        let inserted: String = wanted[prefix..wanted.len() - suffix].iter().collect();
+        if deleted > 0 || !inserted.is_empty() {
+            self.doc
+                .splice_text(&self.text, prefix, deleted as isize, &inserted)
+                .map_err(document_error)?;
+        }
+        Ok(())
+    }
+
+    pub fn heads(&mut self) -> Vec<String> {
+        self.doc
+            .get_heads()
+            .iter()
+            .map(|head| head.to_string())
+            .collect()
+    }
+
+    //Changes made after the given heads: what a client knowing them lacks.
+    //Unknown heads give the whole history.
+    pub fn changes_after(&mut self, heads: &[String]) -> Vec<u8> {
+        let heads: Vec<ChangeHash> = heads
+            .iter()
+            .filter_map(|head| ChangeHash::from_str(head).ok())
+            .collect();
+        let known = heads
+            .iter()
+            .all(|head| self.doc.get_change_by_hash(head).is_some());
+        match known {
+            true => self.doc.save_after(&heads),
+            false => self.doc.save_after(&[]),
+        }
+    }
+
+    pub fn save(&mut self) -> Vec<u8> {
+        self.doc.save()
+    }
+}
+
+fn document_error(error: impl ToString) -> MemorizDomainError {
+    MemorizDomainError::Document(error.to_string())
+}
@@ -1,23 +1,1008 @@
+use crate::blob::traits::BlobStoreTrait;
+use crate::business::attachment::{attachment_file_name, check_attachment_content};
+use crate::business::calendar::{render_calendar, CalendarComponent};
+use crate::business::clip::clip_article;
+use crate::business::crdt::ContentDocument;
 use crate::business::error::EntryDomainResult;
 use crate::business::error::MemorizDomainError;
+use crate::business::export::markdown::MarkdownExport;
+use crate::business::extraction::{extract_attachment_text, is_extractable};
+use crate::business::graph::{build_graph, entry_node_id, neighbourhood};
+use crate::business::import::enex::parse_enex_export;
+use crate::business::import::keep::parse_keep_archive;
+use crate::business::import::trello::parse_trello_export;
+use crate::business::import::vault::parse_vault_archive;
+use crate::business::import::{with_import_marker, ImportBatch};
+use crate::business::links::{parse_link_titles, rewrite_links, same_title, title_key};
+use crate::business::preview::{fetch_link_previews, find_urls};
+use crate::business::query::{matches_query, parse_query};
+use crate::business::search::{facet_counts, indexed_entry, search_request};
+use crate::business::template::{expand_placeholders, template_content, DATE_FORMAT};
+use crate::business::throttle::PasswordThrottle;
+use crate::business::thumbnail::{content_hash, describe_image, THUMBNAIL_CONTENT_TYPE};
 use crate::business::traits::DomainTrait;
+use crate::core::attachment::{Attachment, Thumbnail};
 use crate::core::board::Board;
-use crate::core::entry::Entry;
+use crate::core::clip::ClipRequest;
+use crate::core::document::EntryDocument;
+use crate::core::entry::{DueFilter, Entry};
+use crate::core::event::{ChangeEvent, ChangeKind, ChangedItem};
+use crate::core::graph::Graph;
+use crate::core::import::ImportReport;
+use crate::core::label::Label;
+use crate::core::link::EntryLink;
+use crate::core::preview::LinkPreview;
+use crate::core::search::{EntryFacets, EntrySearchHit, EntrySearchResults, SavedSearch};
+use crate::core::share::{
+    PublicItem, PublicLink, Share, ShareRole, SharedBoard, SharedEntry, SharedItemKind,
+    SharedWithMe,
+};
+use crate::core::sync::{
+    DeletedItem, MutationResult, MutationStatus, SyncMutation, SyncRequest, SyncResponse,
+};
+use crate::core::template::{Template, TemplateEntryRequest};
+use crate::fetcher::traits::LinkFetcherTrait;
+use crate::notifier::traits::{EventPublisherTrait, NotifierTrait};
 use crate::storage::traits::{SearchEngineTrait, StorageTrait};
 use async_trait::async_trait;
+use chrono::prelude::*;
+use chrono::Duration;
+use serde_json::json;
 use std::boxed::Box;
+use std::collections::{HashMap, HashSet};
+
+//Changes sent by one sync at most, the client asks for the next ones.
+const SYNC_PAGE_SIZE: i64 = 1000;
+//Wrong passwords accepted on a public link during the window, then it is blocked.
+const PUBLIC_LINK_MAX_PASSWORD_FAILURES: usize = 10;
+const PUBLIC_LINK_PASSWORD_WINDOW_MINUTES: i64 = 15;
+//Failed attachments are tried again after the delay, up to the maximum attempts.
+const ATTACHMENT_PROCESSING_MAX_ATTEMPTS: i32 = 5;
+const ATTACHMENT_PROCESSING_RETRY_DELAY_MINUTES: i64 = 10;
+//Entries read at once while an export is being sent.
+const EXPORT_PAGE_SIZE: i64 = 200;
+
+//Way a user reaches a board or an entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Access {
+    Owner,
+    Shared(ShareRole),
+}
+
+impl Access {
+    fn can_edit(&self) -> bool {
+        match self {
+            Access::Owner | Access::Shared(ShareRole::Editor) => true,
+            Access::Shared(ShareRole::Viewer) => false,
+        }
+    }
+}
 
 pub struct MemorizDomain {
     storage: Box<dyn StorageTrait>,
     search_engine: Box<dyn SearchEngineTrait>,
+    notifier: Box<dyn NotifierTrait>,
+    publisher: Box<dyn EventPublisherTrait>,
+    blob_store: Box<dyn BlobStoreTrait>,
+    link_fetcher: Box<dyn LinkFetcherTrait>,
+    password_throttle: PasswordThrottle,
 }
 
 impl MemorizDomain {
-    pub fn new(storage: Box<dyn StorageTrait>, search_engine: Box<dyn SearchEngineTrait>) -> Self {
+    pub fn new(
+        storage: Box<dyn StorageTrait>,
+        search_engine: Box<dyn SearchEngineTrait>,
+        notifier: Box<dyn NotifierTrait>,
+        publisher: Box<dyn EventPublisherTrait>,
+        blob_store: Box<dyn BlobStoreTrait>,
+        link_fetcher: Box<dyn LinkFetcherTrait>,
+    ) -> Self {
         MemorizDomain {
             storage,
             search_engine,
+            notifier,
+            publisher,
+            blob_store,
+            link_fetcher,
+            password_throttle: PasswordThrottle::new(
+                PUBLIC_LINK_MAX_PASSWORD_FAILURES,
+                Duration::minutes(PUBLIC_LINK_PASSWORD_WINDOW_MINUTES),
+            ),
+        }
+    }
+
+    //The description goes first: a blob left behind is only wasted space.
+    async fn delete_attachment_blobs(&self, attachments: &[Attachment]) {
+        for attachment in attachments {
+            let mut keys = vec![attachment.blob_key()];
+            for size in attachment.thumbnail_sizes() {
+                keys.push(attachment.thumbnail_key(size));
+            }
+            for key in keys {
+                if let Err(e) = self.blob_store.delete(&key).await {
+                    println!("ATTACHMENT {} not deleted: {}", key, e);
+                }
+            }
+        }
+    }
+
+    //Give the entries of an owner their attachments, in one query.
+    async fn fill_attachments(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entries: &mut [Entry],
+    ) -> EntryDomainResult<()> {
+        let mut attachments: HashMap<uuid::Uuid, Vec<Attachment>> = HashMap::new();
+        for attachment in self.storage.get_owner_attachments(owner_uuid).await? {
+            attachments
+                .entry(attachment.entry)
+                .or_default()
+                .push(attachment);
+        }
+
+        for entry in entries.iter_mut() {
+            entry.attachments = Some(
+                entry
+                    .uuid
+                    .and_then(|uuid| attachments.remove(&uuid))
+                    .unwrap_or_default(),
+            );
+        }
+        Ok(())
+    }
+
+    //A saved search needs a name, and a query which parses.
+    fn check_saved_search(saved_search: &SavedSearch) -> EntryDomainResult<()> {
+        if saved_search.name.trim().is_empty() {
+            return Err(MemorizDomainError::Query("Missing name".to_string()));
+        }
+        parse_query(&saved_search.query)?;
+        Ok(())
+    }
+
+    //A template needs a name, and its board must take the entries of the user.
+    async fn check_template(
+        &self,
+        owner_uuid: uuid::Uuid,
+        template: &Template,
+    ) -> EntryDomainResult<()> {
+        if template.name.trim().is_empty() {
+            return Err(MemorizDomainError::Template("Missing name".to_string()));
+        }
+        if let Some(board_uuid) = template.board {
+            let (_, access) = self.board_access(owner_uuid, board_uuid).await?;
+            if !access.can_edit() {
+                return Err(MemorizDomainError::Forbidden);
+            }
+        }
+        Ok(())
+    }
+
+    //Give the entries the previews of their URLs already fetched, in one query.
+    async fn fill_previews(&self, entries: &mut [Entry]) -> EntryDomainResult<()> {
+        let entries_urls: Vec<Vec<String>> = entries
+            .iter()
+            .map(|entry| find_urls(entry.content.as_deref().unwrap_or_default()))
+            .collect();
+        let mut urls: Vec<String> = entries_urls.iter().flatten().cloned().collect();
+        urls.sort();
+        urls.dedup();
+
+        let previews: HashMap<String, LinkPreview> = match urls.is_empty() {
+            true => HashMap::new(),
+            false => self
+                .storage
+                .get_link_previews(urls)
+                .await?
+                .into_iter()
+                .filter(|preview| !preview.is_empty())
+                .map(|preview| (preview.url.to_owned(), preview))
+                .collect(),
+        };
+
+        for (entry, urls) in entries.iter_mut().zip(entries_urls) {
+            entry.previews = Some(
+                urls.iter()
+                    .filter_map(|url| previews.get(url).cloned())
+                    .collect(),
+            );
+        }
+        Ok(())
+    }
+
+    //Size and thumbnails of an image, text of a document. An attachment that cannot
+    //be read is still marked as processed: it is not tried again.
+    async fn process_attachment(&self, mut attachment: Attachment) -> EntryDomainResult<()> {
+        let content = self.blob_store.get(&attachment.blob_key()).await?;
+
+        //Decoding is long and blocking: it runs on a blocking thread, not on the executor.
+        let mut sizes = Vec::new();
+        if is_extractable(&attachment.content_type) {
+            let content_type = attachment.content_type.to_owned();
+            let text = tokio::task::spawn_blocking(move || {
+                extract_attachment_text(&content_type, &content)
+            })
+            .await
+            .map_err(|e| MemorizDomainError::Attachment(e.to_string()))?;
+            match text {
+                Ok(text) => self.storage.save_attachment_text(&attachment, text).await?,
+                Err(e) => println!("ATTACHMENT {} text not extracted: {}", attachment.uuid, e),
+            }
+        } else if attachment.is_image() {
+            let description = tokio::task::spawn_blocking(move || describe_image(&content))
+                .await
+                .map_err(|e| MemorizDomainError::Attachment(e.to_string()))?;
+            match description {
+                Ok(description) => {
+                    attachment.width = Some(description.width as i32);
+                    attachment.height = Some(description.height as i32);
+                    for (size, thumbnail) in description.thumbnails {
+                        let size = size as i32;
+                        self.blob_store
+                            .put(
+                                &attachment.thumbnail_key(size),
+                                thumbnail,
+                                THUMBNAIL_CONTENT_TYPE,
+                            )
+                            .await?;
+                        sizes.push(size);
+                    }
+                }
+                Err(e) => println!("ATTACHMENT {} not processed: {}", attachment.uuid, e),
+            }
+        }
+
+        attachment.thumbnails = sizes
+            .into_iter()
+            .map(|size| Thumbnail::new(attachment.entry, attachment.uuid, size))
+            .collect();
+        attachment.processed_on = Some(Utc::now());
+        self.storage
+            .update_processed_attachment(&attachment)
+            .await?;
+        self.index_entry(attachment.owner, attachment.entry).await
+    }
+
+    //Send the entry to the search engine, with its labels, attachments and their text.
+    //The search engine being unavailable does not fail the change.
+    async fn index_entry(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> EntryDomainResult<()> {
+        let entry = indexed_entry(self.storage.as_ref(), owner_uuid, uuid).await?;
+        if let Err(e) = self.search_engine.index_entry(&entry).await {
+            println!("ENTRY {} not indexed: {}", uuid, e);
+        }
+        Ok(())
+    }
+
+    fn filter_entries(
+        entries: Vec<Entry>,
+        archived_filter: Option<bool>,
+        due_filter: Option<DueFilter>,
+    ) -> Vec<Entry> {
+        let now = Utc::now();
+        entries
+            .into_iter()
+            .filter(|entry| match archived_filter {
+                Some(filter) => entry.archived == filter,
+                None => true,
+            })
+            .filter(|entry| match due_filter {
+                Some(filter) => filter.matches(entry, now),
+                None => true,
+            })
+            .collect()
+    }
+
+    //Board of the user, or of another user who shared it with them.
+    async fn board_access(
+        &self,
+        user_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<(Board, Access)> {
+        if let Ok(board) = self.storage.get_board(user_uuid, uuid).await {
+            return Ok((board, Access::Owner));
+        }
+
+        for share in self.storage.get_recipient_shares(user_uuid).await? {
+            if share.kind == SharedItemKind::Board && share.item == uuid {
+                if let Ok(board) = self.storage.get_board(share.owner, uuid).await {
+                    return Ok((board, Access::Shared(share.role)));
+                }
+            }
+        }
+
+        Err(MemorizDomainError::NotFoundError)
+    }
+
+    //Entry of the user, or shared with them directly or through its board.
+    async fn entry_access(
+        &self,
+        user_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<(Entry, Access)> {
+        if let Ok(entry) = self.storage.get_entry(user_uuid, uuid).await {
+            return Ok((entry, Access::Owner));
+        }
+
+        //Shared both directly and through its board: the highest role applies.
+        let shares = self
+            .storage
+            .get_recipient_entry_shares(user_uuid, uuid)
+            .await?;
+        let share = shares
+            .iter()
+            .max_by_key(|share| share.role)
+            .ok_or(MemorizDomainError::NotFoundError)?;
+
+        let entry = self.storage.get_entry(share.owner, uuid).await?;
+        Ok((entry, Access::Shared(share.role)))
+    }
+
+    //Only the owner shares an item, not its editors.
+    async fn check_item_owner(
+        &self,
+        owner_uuid: uuid::Uuid,
+        kind: SharedItemKind,
+        item_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()> {
+        let owned = match kind {
+            SharedItemKind::Board => self.storage.get_board(owner_uuid, item_uuid).await.is_ok(),
+            SharedItemKind::Entry => self.storage.get_entry(owner_uuid, item_uuid).await.is_ok(),
+        };
+        match owned {
+            true => Ok(()),
+            false => Err(MemorizDomainError::NotFoundError),
+        }
+    }
+
+    //A public link does not tell who owns the item.
+    fn public_entry(mut entry: Entry) -> Entry {
+        entry.id = 0;
+        entry.owner = None;
+        entry
+    }
+
+    //The owner of an item and the users it, or its board, is shared with.
+    async fn change_recipients(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+        board_uuid: Option<uuid::Uuid>,
+    ) -> EntryDomainResult<Vec<uuid::Uuid>> {
+        let mut recipients = vec![owner_uuid];
+        for uuid in std::iter::once(item_uuid).chain(board_uuid) {
+            for share in self.storage.get_item_shares(owner_uuid, uuid).await? {
+                if !recipients.contains(&share.recipient) {
+                    recipients.push(share.recipient);
+                }
+            }
+        }
+        Ok(recipients)
+    }
+
+    //Log the change for the sync of each recipient, then push it to their clients.
+    //The item is already saved: failing here would make clients retry a done write.
+    async fn record_change(
+        &self,
+        recipients: &[uuid::Uuid],
+        kind: ChangeKind,
+        item: ChangedItem,
+        id: String,
+        owner_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()> {
+        let event = ChangeEvent::new(kind, item, id, owner_uuid, Utc::now());
+        if let Err(e) = self.storage.append_change(recipients, &event).await {
+            println!("CHANGE {} {} not logged: {}", event.item, event.id, e);
+        }
+        self.publisher.publish(recipients, &event);
+        Ok(())
+    }
+
+    async fn record_entry_change(&self, kind: ChangeKind, entry: &Entry) -> EntryDomainResult<()> {
+        if let (Some(owner_uuid), Some(uuid)) = (entry.owner, entry.uuid) {
+            if kind != ChangeKind::Deleted {
+                self.index_entry(owner_uuid, uuid).await?;
+            }
+            let recipients = self
+                .change_recipients(owner_uuid, uuid, entry.board)
+                .await?;
+            self.record_change(
+                &recipients,
+                kind,
+                ChangedItem::Entry,
+                uuid.to_string(),
+                owner_uuid,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    async fn record_board_change(&self, kind: ChangeKind, board: &Board) -> EntryDomainResult<()> {
+        if let (Some(owner_uuid), Some(uuid)) = (board.owner, board.uuid) {
+            let recipients = self.change_recipients(owner_uuid, uuid, None).await?;
+            self.record_change(
+                &recipients,
+                kind,
+                ChangedItem::Board,
+                uuid.to_string(),
+                owner_uuid,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    //A granted or revoked share changes the items the recipient syncs:
+    //the shared item, and the entries of a shared board.
+    async fn record_access_change(
+        &self,
+        owner_uuid: uuid::Uuid,
+        kind: SharedItemKind,
+        item_uuid: uuid::Uuid,
+        recipient_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()> {
+        let mut entries_uuid: Vec<uuid::Uuid> = Vec::new();
+        let item = match kind {
+            SharedItemKind::Board => {
+                entries_uuid = self
+                    .storage
+                    .get_all_entries_by_board(owner_uuid, item_uuid)
+                    .await?
+                    .into_iter()
+                    .filter_map(|entry| entry.uuid)
+                    .collect();
+                ChangedItem::Board
+            }
+            SharedItemKind::Entry => ChangedItem::Entry,
+        };
+
+        self.record_change(
+            &[recipient_uuid],
+            ChangeKind::Updated,
+            item,
+            item_uuid.to_string(),
+            owner_uuid,
+        )
+        .await?;
+        for entry_uuid in entries_uuid {
+            self.record_change(
+                &[recipient_uuid],
+                ChangeKind::Updated,
+                ChangedItem::Entry,
+                entry_uuid.to_string(),
+                owner_uuid,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    //Stored document of the entry, or a new one holding its content.
+    async fn load_content_document(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry: &Entry,
+    ) -> EntryDomainResult<ContentDocument> {
+        let stored = match entry.uuid {
+            Some(uuid) => self.storage.get_entry_document(owner_uuid, uuid).await?,
+            None => None,
+        };
+        match stored {
+            Some(bytes) => ContentDocument::load(&bytes),
+            None => ContentDocument::from_content(entry.content.as_deref().unwrap_or_default()),
+        }
+    }
+
+    //A plain edit of the content is an edit of the document too, when it has one.
+    async fn update_content_document(&self, entry: &Entry, content: &str) -> EntryDomainResult<()> {
+        if let (Some(owner_uuid), Some(uuid)) = (entry.owner, entry.uuid) {
+            if let Some(bytes) = self.storage.get_entry_document(owner_uuid, uuid).await? {
+                let mut document = ContentDocument::load(&bytes)?;
+                document.set_content(content)?;
+                self.storage
+                    .save_entry_document(owner_uuid, uuid, document.save())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    //Store the "[[Title]]" links found in the entry content.
+    async fn refresh_entry_links(&self, entry: &Entry) -> EntryDomainResult<()> {
+        let (owner_uuid, source_uuid) = match (entry.owner, entry.uuid) {
+            (Some(owner_uuid), Some(source_uuid)) => (owner_uuid, source_uuid),
+            _ => return Ok(()),
+        };
+
+        let mut links: Vec<EntryLink> = Vec::new();
+        for title in parse_link_titles(entry.content.as_deref().unwrap_or("")) {
+            let target = self
+                .storage
+                .get_entry_by_title(owner_uuid, title.to_owned())
+                .await?
+                .and_then(|target| target.uuid);
+            links.push(EntryLink::new(source_uuid, title, target, owner_uuid));
+        }
+        self.storage
+            .replace_entry_links(owner_uuid, source_uuid, links)
+            .await?;
+
+        //The previews of new URLs are fetched later, by the link preview processor.
+        let urls = find_urls(entry.content.as_deref().unwrap_or(""));
+        if !urls.is_empty() {
+            self.storage.queue_link_previews(urls).await?;
+        }
+        Ok(())
+    }
+
+    //Rewrite the links of the entries pointing to a renamed entry.
+    async fn rewrite_backlinks(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+        old_title: &str,
+        new_title: &str,
+    ) -> EntryDomainResult<()> {
+        let sources: Vec<uuid::Uuid> = self
+            .storage
+            .get_entry_backlinks(owner_uuid, uuid, old_title.to_string())
+            .await?
+            .into_iter()
+            .map(|link| link.source)
+            .collect();
+
+        for mut source in self.storage.get_entries_by_ids(owner_uuid, sources).await? {
+            let content = match &source.content {
+                Some(content) => rewrite_links(content, old_title, new_title),
+                None => continue,
+            };
+            //Rewritten in the document too: the next merge would bring the old links back.
+            self.update_content_document(&source, &content).await?;
+            source.content = Some(content);
+            let source = self.storage.update_entry(source).await?;
+            self.refresh_entry_links(&source).await?;
+            self.record_entry_change(ChangeKind::Updated, &source)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    //Every item the user reaches: a sync without cursor.
+    async fn full_sync(
+        &self,
+        user_uuid: uuid::Uuid,
+        response: &mut SyncResponse,
+    ) -> EntryDomainResult<()> {
+        //Read first: an item changed while reading is sent again by the next sync.
+        response.cursor = self.storage.get_last_change_seq(user_uuid).await?;
+        response.entries = self.storage.get_owner_entries(user_uuid).await?;
+        response.boards = self.storage.get_all_boards(user_uuid).await?;
+        response.labels = self.storage.get_owner_labels(user_uuid).await?;
+
+        for share in self.storage.get_recipient_shares(user_uuid).await? {
+            match share.kind {
+                SharedItemKind::Board => {
+                    if let Ok(board) = self.storage.get_board(share.owner, share.item).await {
+                        response.entries.extend(
+                            self.storage
+                                .get_all_entries_by_board(share.owner, share.item)
+                                .await?,
+                        );
+                        response.boards.push(board);
+                    }
+                }
+                SharedItemKind::Entry => {
+                    if let Ok(entry) = self.storage.get_entry(share.owner, share.item).await {
+                        response.entries.push(entry);
+                    }
+                }
+            }
+        }
+
+        //An entry shared directly and through its board is sent once.
+        let mut sent: HashSet<Option<uuid::Uuid>> = HashSet::new();
+        response.entries.retain(|entry| sent.insert(entry.uuid));
+        Ok(())
+    }
+
+    //Items changed since the cursor, as they are now, or deleted when the user no longer reaches them.
+    async fn delta_sync(
+        &self,
+        user_uuid: uuid::Uuid,
+        cursor: i64,
+        response: &mut SyncResponse,
+    ) -> EntryDomainResult<()> {
+        let changes = self
+            .storage
+            .get_changes(user_uuid, cursor, SYNC_PAGE_SIZE)
+            .await?;
+        response.has_more = changes.len() as i64 == SYNC_PAGE_SIZE;
+        response.cursor = changes.last().map(|change| change.seq).unwrap_or(cursor);
+
+        //An item changed several times is sent once.
+        let mut changed: Vec<(ChangedItem, String)> = Vec::new();
+        let mut seen: HashSet<(ChangedItem, String)> = HashSet::new();
+        for change in changes {
+            let key = (change.item, change.id);
+            if seen.insert(key.clone()) {
+                changed.push(key);
+            }
+        }
+
+        let mut labels: Option<Vec<Label>> = None;
+        for (item, id) in changed {
+            let uuid = uuid::Uuid::parse_str(&id).ok();
+            let found = match (item, uuid) {
+                (ChangedItem::Entry, Some(uuid)) => {
+                    match self.entry_access(user_uuid, uuid).await {
+                        Ok((entry, _)) => {
+                            response.entries.push(entry);
+                            true
+                        }
+                        Err(MemorizDomainError::NotFoundError) => false,
+                        Err(e) => return Err(e),
+                    }
+                }
+                (ChangedItem::Board, Some(uuid)) => {
+                    match self.board_access(user_uuid, uuid).await {
+                        Ok((board, _)) => {
+                            response.boards.push(board);
+                            true
+                        }
+                        Err(MemorizDomainError::NotFoundError) => false,
+                        Err(e) => return Err(e),
+                    }
+                }
+                (ChangedItem::Label, _) => {
+                    if labels.is_none() {
+                        labels = Some(self.storage.get_owner_labels(user_uuid).await?);
+                    }
+                    let labels = labels.as_mut().unwrap();
+                    match labels.iter().position(|label| label.id == id) {
+                        Some(position) => {
+                            response.labels.push(labels.swap_remove(position));
+                            true
+                        }
+                        None => false,
+                    }
+                }
+                _ => false,
+            };
+
+            if !found {
+                response.deleted.push(DeletedItem { item: item, id: id });
+            }
+        }
+
+        Ok(())
+    }
+
+    //Changed since the client saw it, by another client or user.
+    //Items the same sync already changed are not conflicts.
+    async fn is_sync_conflict(
+        &self,
+        user_uuid: uuid::Uuid,
+        id: &str,
+        base: Option<i64>,
+        touched: &HashSet<String>,
+    ) -> EntryDomainResult<bool> {
+        let base = match base {
+            Some(base) if !touched.contains(id) => base,
+            _ => return Ok(false),
+        };
+        Ok(
+            match self
+                .storage
+                .get_item_last_change_seq(user_uuid, id.to_string())
+                .await?
+            {
+                Some(seq) => seq > base,
+                None => false,
+            },
+        )
+    }
+
+    async fn apply_sync_mutation(
+        &self,
+        user_uuid: uuid::Uuid,
+        index: usize,
+        mutation: SyncMutation,
+        cursor: Option<i64>,
+        touched: &mut HashSet<String>,
+    ) -> EntryDomainResult<MutationResult> {
+        match mutation {
+            SyncMutation::PutEntry { mut entry, base } => {
+                let client_uuid = entry.uuid;
+                let existing = match client_uuid {
+                    Some(uuid) => match self.entry_access(user_uuid, uuid).await {
+                        Ok((existing, _)) => Some(existing),
+                        Err(MemorizDomainError::NotFoundError) => None,
+                        Err(e) => return Err(e),
+                    },
+                    None => None,
+                };
+
+                if let Some(id) = client_uuid.map(|uuid| uuid.to_string()) {
+                    if self
+                        .is_sync_conflict(user_uuid, &id, base.or(cursor), touched)
+                        .await?
+                    {
+                        let mut result =
+                            MutationResult::new(index, MutationStatus::Conflict, Some(id));
+                        result.entry = existing;
+                        return Ok(result);
+                    }
+                }
+
+                entry.owner = Some(user_uuid);
+                let entry = match existing {
+                    Some(_) => self.update_entry(entry, false).await?,
+                    //Created offline: the server gives the uuid.
+                    None => {
+                        entry.id = 0;
+                        entry.uuid = None;
+                        entry.created_on = None;
+                        entry.updated_on = None;
+                        self.create_entry(entry).await?
+                    }
+                };
+
+                let id = entry.uuid.map(|uuid| uuid.to_string());
+                touched.extend(id.clone());
+                let mut result = MutationResult::new(index, MutationStatus::Applied, id);
+                if existing.is_none() {
+                    result.client_id = client_uuid.map(|uuid| uuid.to_string());
+                }
+                Ok(result)
+            }
+            SyncMutation::DeleteEntry { uuid, base } => {
+                let id = uuid.to_string();
+                let existing = match self.entry_access(user_uuid, uuid).await {
+                    Ok(existing) => Some(existing),
+                    Err(MemorizDomainError::NotFoundError) => None,
+                    Err(e) => return Err(e),
+                };
+
+                if self
+                    .is_sync_conflict(user_uuid, &id, base.or(cursor), touched)
+                    .await?
+                {
+                    let mut result = MutationResult::new(index, MutationStatus::Conflict, Some(id));
+                    result.entry = existing.map(|(entry, _)| entry);
+                    return Ok(result);
+                }
+
+                //Already deleted is fine, only the owner deletes.
+                match existing {
+                    Some((_, Access::Owner)) => self.delete_entry(user_uuid, uuid).await?,
+                    Some(_) => return Err(MemorizDomainError::Forbidden),
+                    None => (),
+                }
+                touched.insert(id.to_owned());
+                Ok(MutationResult::new(
+                    index,
+                    MutationStatus::Applied,
+                    Some(id),
+                ))
+            }
+            SyncMutation::PutBoard { mut board, base } => {
+                let client_uuid = board.uuid;
+                let existing = match client_uuid {
+                    Some(uuid) => match self.board_access(user_uuid, uuid).await {
+                        Ok((existing, _)) => Some(existing),
+                        Err(MemorizDomainError::NotFoundError) => None,
+                        Err(e) => return Err(e),
+                    },
+                    None => None,
+                };
+
+                if let Some(id) = client_uuid.map(|uuid| uuid.to_string()) {
+                    if self
+                        .is_sync_conflict(user_uuid, &id, base.or(cursor), touched)
+                        .await?
+                    {
+                        let mut result =
+                            MutationResult::new(index, MutationStatus::Conflict, Some(id));
+                        result.board = existing;
+                        return Ok(result);
+                    }
+                }
+
+                board.owner = Some(user_uuid);
+                let board = match existing {
+                    Some(_) => self.update_board(board).await?,
+                    None => {
+                        board.uuid = None;
+                        self.create_board(board).await?
+                    }
+                };
+
+                let id = board.uuid.map(|uuid| uuid.to_string());
+                touched.extend(id.clone());
+                let mut result = MutationResult::new(index, MutationStatus::Applied, id);
+                if existing.is_none() {
+                    result.client_id = client_uuid.map(|uuid| uuid.to_string());
+                }
+                Ok(result)
+            }
+            SyncMutation::DeleteBoard { uuid, base } => {
+                let id = uuid.to_string();
+                let existing = match self.board_access(user_uuid, uuid).await {
+                    Ok(existing) => Some(existing),
+                    Err(MemorizDomainError::NotFoundError) => None,
+                    Err(e) => return Err(e),
+                };
+
+                if self
+                    .is_sync_conflict(user_uuid, &id, base.or(cursor), touched)
+                    .await?
+                {
+                    let mut result = MutationResult::new(index, MutationStatus::Conflict, Some(id));
+                    result.board = existing.map(|(board, _)| board);
+                    return Ok(result);
+                }
+
+                match existing {
+                    Some((_, Access::Owner)) => self.delete_board(user_uuid, uuid).await?,
+                    Some(_) => return Err(MemorizDomainError::Forbidden),
+                    None => (),
+                }
+                touched.insert(id.to_owned());
+                Ok(MutationResult::new(
+                    index,
+                    MutationStatus::Applied,
+                    Some(id),
+                ))
+            }
+        }
+    }
+
+    //Map an import batch on the user boards, labels and entries.
+    //Entries already imported from the same source are updated instead of duplicated.
+    async fn apply_import(
+        &self,
+        owner_uuid: uuid::Uuid,
+        batch: ImportBatch,
+    ) -> EntryDomainResult<ImportReport> {
+        let mut report = ImportReport::new(batch.source.to_owned());
+        report.warnings = batch.warnings;
+
+        let mut boards: HashMap<String, uuid::Uuid> = HashMap::new();
+        for board in self.storage.get_all_boards(owner_uuid).await? {
+            if let Some(uuid) = board.uuid {
+                boards.entry(title_key(&board.title)).or_insert(uuid);
+            }
+        }
+
+        let mut labels: HashMap<String, String> = HashMap::new();
+        for label in self.storage.get_owner_labels(owner_uuid).await? {
+            labels.entry(title_key(&label.name)).or_insert(label.id);
+        }
+
+        for imported in batch.entries {
+            let board_uuid = match &imported.board {
+                Some(title) => match boards.get(&title_key(title)) {
+                    Some(board_uuid) => Some(*board_uuid),
+                    None => {
+                        let board = Board::new(
+                            None,
+                            title.to_owned(),
+                            None,
+                            None,
+                            None,
+                            None,
+                            Some(owner_uuid),
+                        );
+                        let board = self.storage.create_board(board).await?;
+                        self.record_board_change(ChangeKind::Created, &board)
+                            .await?;
+                        report.boards_created += 1;
+                        boards.insert(title_key(title), board.uuid.unwrap());
+                        board.uuid
+                    }
+                },
+                None => None,
+            };
+
+            let existing = match imported.uuid {
+                Some(uuid) => self.storage.get_entry(owner_uuid, uuid).await.ok(),
+                None => None,
+            };
+            let existing = match existing {
+                Some(existing) => Some(existing),
+                None => {
+                    self.storage
+                        .get_entry_by_import_id(
+                            owner_uuid,
+                            batch.source.to_owned(),
+                            imported.source_id.to_owned(),
+                        )
+                        .await?
+                }
+            };
+
+            let data = with_import_marker(imported.data, &batch.source, &imported.source_id);
+            let mut entry = Entry::new(
+                0,
+                None,
+                imported.title,
+                imported.content,
+                Some(data),
+                imported.color,
+                imported.archived,
+                imported.created_on,
+                imported.updated_on,
+                Some(owner_uuid),
+                None,
+                board_uuid,
+                imported.due_on,
+                None,
+                None,
+                None,
+                None,
+            );
+
+            let entry = match existing {
+                Some(existing) => {
+                    entry.id = existing.id;
+                    entry.uuid = existing.uuid;
+                    entry.created_on = existing.created_on;
+                    entry.remind_at = existing.remind_at;
+                    entry.reminded_on = existing.reminded_on;
+                    entry.recurrence = existing.recurrence;
+                    report.updated += 1;
+                    self.update_entry(entry, false).await?
+                }
+                None => {
+                    report.created += 1;
+                    self.create_entry(entry).await?
+                }
+            };
+
+            let entry_uuid = match entry.uuid {
+                Some(entry_uuid) => entry_uuid,
+                None => continue,
+            };
+            let mut labelled = false;
+            for name in imported.labels {
+                let label_id = match labels.get(&title_key(&name)) {
+                    Some(label_id) => label_id.to_owned(),
+                    None => {
+                        let label_id = uuid::Uuid::new_v5(&owner_uuid, title_key(&name).as_bytes())
+                            .to_string();
+                        let label = Label::new(
+                            label_id.to_owned(),
+                            name.to_owned(),
+                            String::new(),
+                            owner_uuid,
+                        );
+                        self.storage.create_label(label).await?;
+                        self.record_change(
+                            &[owner_uuid],
+                            ChangeKind::Created,
+                            ChangedItem::Label,
+                            label_id.to_owned(),
+                            owner_uuid,
+                        )
+                        .await?;
+                        report.labels_created += 1;
+                        labels.insert(title_key(&name), label_id.to_owned());
+                        label_id
+                    }
+                };
+                self.storage
+                    .add_entry_label(owner_uuid, entry_uuid, label_id)
+                    .await?;
+                labelled = true;
+            }
+            //Indexed again now that the labels are known.
+            if labelled {
+                self.index_entry(owner_uuid, entry_uuid).await?;
+            }
         }
+
+        Ok(report)
     }
 }
 
@@ -27,17 +1012,16 @@ impl DomainTrait for MemorizDomain {
         &self,
         owner_uuid: uuid::Uuid,
         archived_filter: Option<bool>,
+        due_filter: Option<DueFilter>,
     ) -> EntryDomainResult<Vec<Entry>> {
-        let entries = self.storage.get_all_entries(owner_uuid).await?;
-        let filtered_entries = match archived_filter {
-            Some(filter) => entries
-                .into_iter()
-                .filter(|entry| entry.archived == filter)
-                .collect(),
-            None => entries,
-        };
-
-        Ok(filtered_entries)
+        let mut entries = self.storage.get_all_entries(owner_uuid).await?;
+        self.fill_attachments(owner_uuid, &mut entries).await?;
+        self.fill_previews(&mut entries).await?;
+        Ok(MemorizDomain::filter_entries(
+            entries,
+            archived_filter,
+            due_filter,
+        ))
     }
 
     async fn get_all_entries_by_board(
@@ -45,21 +1029,22 @@ impl DomainTrait for MemorizDomain {
         owner_uuid: uuid::Uuid,
         board_uuid: uuid::Uuid,
         archived_filter: Option<bool>,
+        due_filter: Option<DueFilter>,
     ) -> EntryDomainResult<Vec<Entry>> {
-        let entries = self
+        let (board, _) = self.board_access(owner_uuid, board_uuid).await?;
+        let board_owner = board.owner.unwrap_or(owner_uuid);
+        let mut entries = self
             .storage
-            .get_all_entries_by_board(owner_uuid, board_uuid)
+            .get_all_entries_by_board(board_owner, board_uuid)
             .await?;
+        self.fill_attachments(board_owner, &mut entries).await?;
+        self.fill_previews(&mut entries).await?;
 
-        let filtered_entries = match archived_filter {
-            Some(filter) => entries
-                .into_iter()
-                .filter(|entry| entry.archived == filter)
-                .collect(),
-            None => entries,
-        };
-
-        Ok(filtered_entries)
+        Ok(MemorizDomain::filter_entries(
+            entries,
+            archived_filter,
+            due_filter,
+        ))
     }
 
     async fn get_board(
@@ -68,81 +1053,1181 @@ impl DomainTrait for MemorizDomain {
         uuid: uuid::Uuid,
     ) -> EntryDomainResult<Board> {
         println!("BOARD {:?}", uuid);
-        Ok(self.storage.get_board(owner_uuid, uuid).await?)
+        let (board, _) = self.board_access(owner_uuid, uuid).await?;
+        Ok(board)
     }
 
     async fn get_all_boards(&self, owner_uuid: uuid::Uuid) -> EntryDomainResult<Vec<Board>> {
         Ok(self.storage.get_all_boards(owner_uuid).await?)
     }
 
-    async fn get_entry(
+    async fn create_saved_search(
+        &self,
+        mut saved_search: SavedSearch,
+    ) -> EntryDomainResult<SavedSearch> {
+        MemorizDomain::check_saved_search(&saved_search)?;
+        saved_search.uuid = Some(uuid::Uuid::new_v4());
+        Ok(self.storage.create_saved_search(saved_search).await?)
+    }
+
+    async fn update_saved_search(
+        &self,
+        saved_search: SavedSearch,
+    ) -> EntryDomainResult<SavedSearch> {
+        let (owner_uuid, uuid) = match (saved_search.owner, saved_search.uuid) {
+            (Some(owner_uuid), Some(uuid)) => (owner_uuid, uuid),
+            _ => return Err(MemorizDomainError::NotFoundError),
+        };
+        if self
+            .storage
+            .get_saved_search(owner_uuid, uuid)
+            .await?
+            .is_none()
+        {
+            return Err(MemorizDomainError::NotFoundError);
+        }
+        MemorizDomain::check_saved_search(&saved_search)?;
+        Ok(self.storage.update_saved_search(saved_search).await?)
+    }
+
+    async fn delete_saved_search(
         &self,
         owner_uuid: uuid::Uuid,
         uuid: uuid::Uuid,
-    ) -> EntryDomainResult<Entry> {
-        Ok(self.storage.get_entry(owner_uuid, uuid).await?)
+    ) -> EntryDomainResult<()> {
+        Ok(self.storage.delete_saved_search(owner_uuid, uuid).await?)
     }
 
-    async fn search(&self, owner_uuid: uuid::Uuid, query: String) -> EntryDomainResult<Vec<Entry>> {
-        let entries_id = self
-            .search_engine
-            .search_entries(owner_uuid, query)
-            .await
-            .unwrap();
+    async fn get_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<SavedSearch> {
+        match self.storage.get_saved_search(owner_uuid, uuid).await? {
+            Some(saved_search) => Ok(saved_search),
+            None => Err(MemorizDomainError::NotFoundError),
+        }
+    }
 
-        Ok(self
+    async fn get_all_saved_searches(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<SavedSearch>> {
+        Ok(self.storage.get_all_saved_searches(owner_uuid).await?)
+    }
+
+    async fn get_all_entries_by_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+        archived_filter: Option<bool>,
+        due_filter: Option<DueFilter>,
+    ) -> EntryDomainResult<Vec<Entry>> {
+        let saved_search = self.get_saved_search(owner_uuid, uuid).await?;
+        let query = parse_query(&saved_search.query)?;
+
+        let mut entries = self.storage.get_owner_entries(owner_uuid).await?;
+        self.fill_attachments(owner_uuid, &mut entries).await?;
+        let boards: HashMap<uuid::Uuid, String> = self
             .storage
-            .get_entries_by_ids(owner_uuid, entries_id)
-            .await?)
+            .get_all_boards(owner_uuid)
+            .await?
+            .into_iter()
+            .filter_map(|board| Some((board.uuid?, board.title)))
+            .collect();
+        let mut labels: HashMap<uuid::Uuid, Vec<Label>> = HashMap::new();
+        for (entry_uuid, label) in self.storage.get_all_entries_labels(owner_uuid).await? {
+            labels.entry(entry_uuid).or_default().push(label);
+        }
+
+        let mut matching: Vec<Entry> = Vec::new();
+        for mut entry in entries {
+            let entry_labels = entry
+                .uuid
+                .and_then(|uuid| labels.remove(&uuid))
+                .unwrap_or_default();
+            let board_title = entry
+                .board
+                .and_then(|board| boards.get(&board))
+                .map(|title| title.as_str());
+            if matches_query(&query, &entry, &entry_labels, board_title) {
+                entry.labels = Some(entry_labels);
+                matching.push(entry);
+            }
+        }
+        self.fill_previews(&mut matching).await?;
+
+        Ok(MemorizDomain::filter_entries(
+            matching,
+            archived_filter,
+            due_filter,
+        ))
     }
 
-    async fn create_entry(&self, entry: Entry) -> EntryDomainResult<Entry> {
-        Ok(self.storage.create_entry(entry).await?)
+    async fn create_template(&self, mut template: Template) -> EntryDomainResult<Template> {
+        let owner_uuid = template.owner.ok_or(MemorizDomainError::NotFoundError)?;
+        self.check_template(owner_uuid, &template).await?;
+        template.uuid = Some(uuid::Uuid::new_v4());
+        Ok(self.storage.create_template(template).await?)
     }
 
-    async fn update_entry(&self, entry: Entry) -> EntryDomainResult<Entry> {
-        Ok(self.storage.update_entry(entry).await?)
+    async fn update_template(&self, template: Template) -> EntryDomainResult<Template> {
+        let (owner_uuid, uuid) = match (template.owner, template.uuid) {
+            (Some(owner_uuid), Some(uuid)) => (owner_uuid, uuid),
+            _ => return Err(MemorizDomainError::NotFoundError),
+        };
+        if self.storage.get_template(owner_uuid, uuid).await?.is_none() {
+            return Err(MemorizDomainError::NotFoundError);
+        }
+        self.check_template(owner_uuid, &template).await?;
+        Ok(self.storage.update_template(template).await?)
     }
 
-    async fn delete_entry(
+    async fn delete_template(
         &self,
         owner_uuid: uuid::Uuid,
         uuid: uuid::Uuid,
     ) -> EntryDomainResult<()> {
-        Ok(self.storage.delete_entry(owner_uuid, uuid).await?)
+        Ok(self.storage.delete_template(owner_uuid, uuid).await?)
     }
 
-    async fn archive_entry(
+    async fn get_template(
         &self,
         owner_uuid: uuid::Uuid,
         uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Template> {
+        match self.storage.get_template(owner_uuid, uuid).await? {
+            Some(template) => Ok(template),
+            None => Err(MemorizDomainError::NotFoundError),
+        }
+    }
+
+    async fn get_all_templates(&self, owner_uuid: uuid::Uuid) -> EntryDomainResult<Vec<Template>> {
+        Ok(self.storage.get_all_templates(owner_uuid).await?)
+    }
+
+    async fn create_entry_from_template(
+        &self,
+        user_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+        request: TemplateEntryRequest,
     ) -> EntryDomainResult<Entry> {
-        let mut entry = self.get_entry(owner_uuid, uuid).await?;
-        entry.archived = true;
-        Ok(self.storage.update_entry(entry).await?)
+        let template = self.get_template(user_uuid, uuid).await?;
+        let board_uuid = request.board.or(template.board);
+        let board_title = match board_uuid {
+            Some(board_uuid) => self.board_access(user_uuid, board_uuid).await?.0.title,
+            None => String::new(),
+        };
+
+        let mut values: HashMap<String, String> = request
+            .values
+            .into_iter()
+            .map(|(name, value)| (name.trim().to_lowercase(), value))
+            .collect();
+        let date = request
+            .date
+            .unwrap_or_else(|| Utc::now().naive_utc().date());
+        values.insert("date".to_string(), date.format(DATE_FORMAT).to_string());
+        values.insert("board".to_string(), board_title);
+        values.insert(
+            "user".to_string(),
+            request.user.unwrap_or_else(|| user_uuid.to_string()),
+        );
+
+        let entry = Entry::new(
+            0,
+            None,
+            expand_placeholders(&template.title, &values),
+            template_content(&template, &values),
+            None,
+            template.color.to_owned(),
+            false,
+            None,
+            None,
+            Some(user_uuid),
+            None,
+            board_uuid,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        let mut entry = self.create_entry(entry).await?;
+
+        //Labels belong to the user: an entry created in the board of another user goes without.
+        let entry_uuid = match (entry.owner, entry.uuid) {
+            (Some(owner_uuid), Some(entry_uuid)) if owner_uuid == user_uuid => entry_uuid,
+            _ => return Ok(entry),
+        };
+        let mut labels: Vec<Label> = Vec::new();
+        for label in self.storage.get_owner_labels(user_uuid).await? {
+            if template.labels.contains(&label.id) {
+                self.storage
+                    .add_entry_label(user_uuid, entry_uuid, label.id.to_owned())
+                    .await?;
+                labels.push(label);
+            }
+        }
+        if !labels.is_empty() {
+            self.index_entry(user_uuid, entry_uuid).await?;
+        }
+        entry.labels = Some(labels);
+        Ok(entry)
     }
 
-    async fn undo_archive_entry(
+    async fn get_entry(
         &self,
         owner_uuid: uuid::Uuid,
         uuid: uuid::Uuid,
     ) -> EntryDomainResult<Entry> {
-        let mut entry = self.get_entry(owner_uuid, uuid).await?;
-        entry.archived = false;
-        Ok(self.storage.update_entry(entry).await?)
+        let (mut entry, _) = self.entry_access(owner_uuid, uuid).await?;
+        let attachments = self
+            .storage
+            .get_entry_attachments(entry.owner.unwrap_or(owner_uuid), uuid)
+            .await?;
+        entry.attachments = Some(attachments);
+        self.fill_previews(std::slice::from_mut(&mut entry)).await?;
+        Ok(entry)
     }
 
-    async fn create_board(&self, board: Board) -> EntryDomainResult<Board> {
-        Ok(self.storage.create_board(board).await?)
-    }
-    async fn update_board(&self, board: Board) -> EntryDomainResult<Board> {
-        Ok(self.storage.update_board(board).await?)
-    }
-    async fn delete_board(
+    async fn search(
         &self,
         owner_uuid: uuid::Uuid,
-        uuid: uuid::Uuid,
-    ) -> EntryDomainResult<()> {
-        Ok(self.storage.delete_board(owner_uuid, uuid).await?)
+        query: String,
+        offset: usize,
+        limit: usize,
+    ) -> EntryDomainResult<EntrySearchResults> {
+        let query = parse_query(&query)?;
+        let labels = self.storage.get_owner_labels(owner_uuid).await?;
+        let boards = self.storage.get_all_boards(owner_uuid).await?;
+        let request = match search_request(&query, &labels, &boards, offset, limit) {
+            Some(request) => request,
+            None => {
+                return Ok(EntrySearchResults {
+                    offset: offset,
+                    ..Default::default()
+                })
+            }
+        };
+
+        let response = self
+            .search_engine
+            .search_entries(owner_uuid, request)
+            .await?;
+
+        let entries_id = response.hits.iter().map(|hit| hit.uuid).collect();
+        let mut entries: HashMap<uuid::Uuid, Entry> = self
+            .storage
+            .get_entries_by_ids(owner_uuid, entries_id)
+            .await?
+            .into_iter()
+            .filter_map(|entry| Some((entry.uuid?, entry)))
+            .collect();
+
+        //Storage gives the entries in no particular order: the hits keep the relevance one.
+        //Entries deleted since they were indexed are left out.
+        let mut hits: Vec<EntrySearchHit> = Vec::new();
+        for hit in response.hits {
+            if let Some(mut entry) = entries.remove(&hit.uuid) {
+                entry.matched_in = Some(hit.sources);
+                hits.push(EntrySearchHit {
+                    entry: entry,
+                    score: hit.score,
+                    highlights: hit.highlights,
+                });
+            }
+        }
+
+        let label_names: HashMap<String, String> = labels
+            .into_iter()
+            .map(|label| (label.id, label.name))
+            .collect();
+        let board_titles: HashMap<String, String> = boards
+            .into_iter()
+            .filter_map(|board| Some((board.uuid?.to_string(), board.title)))
+            .collect();
+        let color_names: HashMap<String, String> = response
+            .facets
+            .colors
+            .keys()
+            .map(|color| (color.to_owned(), color.to_owned()))
+            .collect();
+
+        Ok(EntrySearchResults {
+            total: response.total,
+            offset: offset,
+            hits: hits,
+            facets: EntryFacets {
+                labels: facet_counts(response.facets.labels, &label_names),
+                boards: facet_counts(response.facets.boards, &board_titles),
+                colors: facet_counts(response.facets.colors, &color_names),
+            },
+        })
+    }
+
+    async fn get_graph(
+        &self,
+        owner_uuid: uuid::Uuid,
+        center_uuid: Option<uuid::Uuid>,
+        depth: usize,
+    ) -> EntryDomainResult<Graph> {
+        let entries = self.storage.get_owner_entries(owner_uuid).await?;
+        let boards = self.storage.get_all_boards(owner_uuid).await?;
+        let entries_labels = self.storage.get_all_entries_labels(owner_uuid).await?;
+
+        let graph = build_graph(&entries, &boards, &entries_labels);
+        Ok(match center_uuid {
+            Some(center_uuid) => neighbourhood(graph, &entry_node_id(&center_uuid), depth),
+            None => graph,
+        })
+    }
+
+    async fn import_keep(
+        &self,
+        owner_uuid: uuid::Uuid,
+        archive: Vec<u8>,
+    ) -> EntryDomainResult<ImportReport> {
+        let batch = parse_keep_archive(&archive)?;
+        self.apply_import(owner_uuid, batch).await
+    }
+
+    async fn import_markdown_vault(
+        &self,
+        owner_uuid: uuid::Uuid,
+        archive: Vec<u8>,
+    ) -> EntryDomainResult<ImportReport> {
+        let batch = parse_vault_archive(&archive)?;
+        self.apply_import(owner_uuid, batch).await
+    }
+
+    async fn import_trello(
+        &self,
+        owner_uuid: uuid::Uuid,
+        export: Vec<u8>,
+    ) -> EntryDomainResult<ImportReport> {
+        let batch = parse_trello_export(&export)?;
+        self.apply_import(owner_uuid, batch).await
+    }
+
+    async fn import_enex(
+        &self,
+        owner_uuid: uuid::Uuid,
+        file_name: String,
+        export: Vec<u8>,
+    ) -> EntryDomainResult<ImportReport> {
+        let batch = parse_enex_export(&file_name, &export)?;
+        self.apply_import(owner_uuid, batch).await
+    }
+
+    async fn export_markdown(&self, owner_uuid: uuid::Uuid) -> EntryDomainResult<MarkdownExport> {
+        let boards = self.storage.get_all_boards(owner_uuid).await?;
+        Ok(MarkdownExport::new(owner_uuid, &boards))
+    }
+
+    async fn next_markdown_export_chunk(
+        &self,
+        export: &mut MarkdownExport,
+    ) -> EntryDomainResult<Option<Vec<u8>>> {
+        if export.is_finished() {
+            return Ok(None);
+        }
+
+        //Entries are read one page at a time: only the zip directory stays in memory.
+        let entries = self
+            .storage
+            .get_owner_entries_page(export.owner(), export.last_entry(), EXPORT_PAGE_SIZE)
+            .await?;
+        if entries.is_empty() {
+            return export.finish().map(Some);
+        }
+
+        let entries_uuid: Vec<uuid::Uuid> = entries.iter().filter_map(|entry| entry.uuid).collect();
+        let entries_labels = self
+            .storage
+            .get_entries_labels(export.owner(), entries_uuid)
+            .await?;
+        export.add_entries(entries, entries_labels).map(Some)
+    }
+
+    async fn dispatch_due_reminders(&self, now: DateTime<Utc>) -> EntryDomainResult<usize> {
+        let entries = self.storage.get_entries_to_remind(now).await?;
+        let mut dispatched = 0;
+
+        for mut entry in entries {
+            if let Err(e) = self.notifier.notify_reminder(&entry).await {
+                //Keep the reminder: it will be retried on the next dispatch.
+                println!("REMINDER {:?} not dispatched: {}", entry.uuid, e);
+                continue;
+            }
+
+            //A reminder is only sent once, until a recurring entry rolls forward.
+            entry.reminded_on = Some(now);
+            let entry = self.storage.update_entry(entry).await?;
+            self.record_entry_change(ChangeKind::Updated, &entry)
+                .await?;
+            dispatched += 1;
+        }
+
+        Ok(dispatched)
+    }
+
+    async fn process_attachments(&self, limit: i64) -> EntryDomainResult<usize> {
+        let failed_before =
+            Utc::now() - Duration::minutes(ATTACHMENT_PROCESSING_RETRY_DELAY_MINUTES);
+        let attachments = self
+            .storage
+            .get_unprocessed_attachments(limit, ATTACHMENT_PROCESSING_MAX_ATTEMPTS, failed_before)
+            .await?;
+        let mut processed = 0;
+
+        for attachment in attachments {
+            let uuid = attachment.uuid;
+            let owner_uuid = attachment.owner;
+            match self.process_attachment(attachment).await {
+                Ok(_) => processed += 1,
+                //Left unprocessed: it is retried later, then given up.
+                Err(e) => {
+                    println!("ATTACHMENT {} not processed: {}", uuid, e);
+                    self.storage
+                        .record_attachment_failure(owner_uuid, uuid)
+                        .await?;
+                }
+            }
+        }
+
+        Ok(processed)
+    }
+
+    async fn process_link_previews(&self, limit: i64) -> EntryDomainResult<usize> {
+        let urls = self.storage.get_pending_link_previews(limit).await?;
+        let previews = fetch_link_previews(self.link_fetcher.as_ref(), urls).await;
+
+        for preview in previews.iter() {
+            self.storage.save_link_preview(preview).await?;
+        }
+        Ok(previews.len())
+    }
+
+    async fn renew_calendar_token(&self, owner_uuid: uuid::Uuid) -> EntryDomainResult<String> {
+        let token = uuid::Uuid::new_v4().to_simple().to_string();
+        self.storage
+            .set_calendar_token(owner_uuid, token.to_owned())
+            .await?;
+        Ok(token)
+    }
+
+    async fn get_calendar(
+        &self,
+        token: String,
+        board_filter: Option<uuid::Uuid>,
+        archived_filter: Option<bool>,
+        component: CalendarComponent,
+    ) -> EntryDomainResult<String> {
+        let owner_uuid = match self.storage.get_calendar_token_owner(token).await? {
+            Some(owner_uuid) => owner_uuid,
+            None => return Err(MemorizDomainError::NotFoundError),
+        };
+
+        let entries = self
+            .storage
+            .get_all_entries_with_due_date(owner_uuid)
+            .await?;
+        let entries: Vec<Entry> = MemorizDomain::filter_entries(entries, archived_filter, None)
+            .into_iter()
+            .filter(|entry| match board_filter {
+                Some(board_uuid) => entry.board == Some(board_uuid),
+                None => true,
+            })
+            .collect();
+
+        Ok(render_calendar(&entries, component, Utc::now()))
+    }
+
+    //Clients start from this document: a document made on their own would not merge.
+    async fn get_entry_document(
+        &self,
+        user_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<EntryDocument> {
+        let (entry, _) = self.entry_access(user_uuid, uuid).await?;
+        let owner_uuid = entry.owner.unwrap_or(user_uuid);
+
+        let mut document = self.load_content_document(owner_uuid, &entry).await?;
+        let bytes = document.save();
+        if self
+            .storage
+            .get_entry_document(owner_uuid, uuid)
+            .await?
+            .is_none()
+        {
+            self.storage
+                .save_entry_document(owner_uuid, uuid, bytes.to_owned())
+                .await?;
+        }
+
+        Ok(EntryDocument::new(
+            uuid,
+            document.heads(),
+            bytes,
+            document.content()?,
+        ))
+    }
+
+    async fn merge_entry_document(
+        &self,
+        user_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+        update: Vec<u8>,
+        heads: Vec<String>,
+    ) -> EntryDomainResult<EntryDocument> {
+        let (mut entry, access) = self.entry_access(user_uuid, uuid).await?;
+        if !access.can_edit() {
+            return Err(MemorizDomainError::Forbidden);
+        }
+        let owner_uuid = entry.owner.unwrap_or(user_uuid);
+
+        let mut document = self.load_content_document(owner_uuid, &entry).await?;
+        document.merge(&update)?;
+        self.storage
+            .save_entry_document(owner_uuid, uuid, document.save())
+            .await?;
+
+        //The plain content follows the document, for search and listings.
+        let content = document.content()?;
+        if entry.content.as_deref().unwrap_or_default() != content {
+            entry.content = Some(content.to_owned());
+            let entry = self.storage.update_entry(entry).await?;
+            self.refresh_entry_links(&entry).await?;
+            self.record_entry_change(ChangeKind::Updated, &entry)
+                .await?;
+        }
+
+        //Sent back: the changes after the heads the client knew, applying its own again does nothing.
+        Ok(EntryDocument::new(
+            uuid,
+            document.heads(),
+            document.changes_after(&heads),
+            content,
+        ))
+    }
+
+    //Attachments belong to the owner of the entry, whoever uploads them.
+    async fn add_attachment(
+        &self,
+        user_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        file_name: Option<String>,
+        content_type: String,
+        content: Vec<u8>,
+    ) -> EntryDomainResult<Attachment> {
+        let (entry, access) = self.entry_access(user_uuid, entry_uuid).await?;
+        if !access.can_edit() {
+            return Err(MemorizDomainError::Forbidden);
+        }
+        let content_type = check_attachment_content(&content_type, &content)?;
+
+        //Attachments are processed later, in the background: the upload stays fast.
+        let attachment = Attachment::new(
+            uuid::Uuid::new_v4(),
+            entry_uuid,
+            entry.owner.unwrap_or(user_uuid),
+            attachment_file_name(file_name),
+            content_type,
+            content.len() as i64,
+            Some(Utc::now()),
+            None,
+            None,
+            Some(content_hash(&content)),
+            Vec::new(),
+            None,
+        );
+        let blob_key = attachment.blob_key();
+        self.blob_store
+            .put(&blob_key, content, &attachment.content_type)
+            .await?;
+
+        //Without its description, the blob would never be reached.
+        match self.storage.create_attachment(attachment).await {
+            Ok(attachment) => Ok(attachment),
+            Err(e) => {
+                let _ = self.blob_store.delete(&blob_key).await;
+                Err(e.into())
+            }
+        }
+    }
+
+    async fn get_entry_attachments(
+        &self,
+        user_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<Attachment>> {
+        let (entry, _) = self.entry_access(user_uuid, entry_uuid).await?;
+        let owner_uuid = entry.owner.unwrap_or(user_uuid);
+        Ok(self
+            .storage
+            .get_entry_attachments(owner_uuid, entry_uuid)
+            .await?)
+    }
+
+    async fn get_attachment_content(
+        &self,
+        user_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<(Attachment, Vec<u8>)> {
+        let (entry, _) = self.entry_access(user_uuid, entry_uuid).await?;
+        let owner_uuid = entry.owner.unwrap_or(user_uuid);
+
+        let attachment = match self.storage.get_attachment(owner_uuid, uuid).await? {
+            Some(attachment) if attachment.entry == entry_uuid => attachment,
+            _ => return Err(MemorizDomainError::NotFoundError),
+        };
+        let content = self.blob_store.get(&attachment.blob_key()).await?;
+        Ok((attachment, content))
+    }
+
+    async fn get_attachment_thumbnail(
+        &self,
+        user_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+        size: i32,
+    ) -> EntryDomainResult<Vec<u8>> {
+        let (entry, _) = self.entry_access(user_uuid, entry_uuid).await?;
+        let owner_uuid = entry.owner.unwrap_or(user_uuid);
+
+        match self.storage.get_attachment(owner_uuid, uuid).await? {
+            Some(attachment)
+                if attachment.entry == entry_uuid
+                    && attachment.thumbnail_sizes().contains(&size) =>
+            {
+                Ok(self.blob_store.get(&attachment.thumbnail_key(size)).await?)
+            }
+            _ => Err(MemorizDomainError::NotFoundError),
+        }
+    }
+
+    async fn delete_attachment(
+        &self,
+        user_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()> {
+        let (entry, access) = self.entry_access(user_uuid, entry_uuid).await?;
+        if !access.can_edit() {
+            return Err(MemorizDomainError::Forbidden);
+        }
+        let owner_uuid = entry.owner.unwrap_or(user_uuid);
+
+        let attachment = match self.storage.get_attachment(owner_uuid, uuid).await? {
+            Some(attachment) if attachment.entry == entry_uuid => attachment,
+            _ => return Err(MemorizDomainError::NotFoundError),
+        };
+        self.storage.delete_attachment(owner_uuid, uuid).await?;
+        self.delete_attachment_blobs(&[attachment]).await;
+        self.index_entry(owner_uuid, entry_uuid).await
+    }
+
+    async fn get_entry_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<EntryLink>> {
+        Ok(self.storage.get_entry_links(owner_uuid, uuid).await?)
+    }
+
+    async fn get_entry_backlinks(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<Entry>> {
+        let entry = self.storage.get_entry(owner_uuid, uuid).await?;
+        let sources: Vec<uuid::Uuid> = self
+            .storage
+            .get_entry_backlinks(owner_uuid, uuid, entry.title)
+            .await?
+            .into_iter()
+            .map(|link| link.source)
+            .collect();
+
+        Ok(self.storage.get_entries_by_ids(owner_uuid, sources).await?)
+    }
+
+    async fn create_entry(&self, mut entry: Entry) -> EntryDomainResult<Entry> {
+        //An entry created in a shared board belongs to the board owner.
+        if let (Some(owner_uuid), Some(board_uuid)) = (entry.owner, entry.board) {
+            let (board, access) = self.board_access(owner_uuid, board_uuid).await?;
+            if !access.can_edit() {
+                return Err(MemorizDomainError::Forbidden);
+            }
+            entry.owner = board.owner;
+        }
+
+        entry.refresh_next_occurrence();
+        let entry = self.storage.create_entry(entry).await?;
+        self.refresh_entry_links(&entry).await?;
+        self.record_entry_change(ChangeKind::Created, &entry)
+            .await?;
+        Ok(entry)
+    }
+
+    async fn clip_entry(
+        &self,
+        owner_uuid: uuid::Uuid,
+        clip: ClipRequest,
+    ) -> EntryDomainResult<Entry> {
+        let article = clip_article(&clip.url, &clip.html)?;
+        let data = json!({
+            "source_url": clip.url,
+            "title": article.title,
+            "excerpt": article.excerpt,
+        });
+
+        let entry = Entry::new(
+            0,
+            None,
+            article.title,
+            Some(article.content),
+            Some(data),
+            None,
+            false,
+            None,
+            None,
+            Some(owner_uuid),
+            None,
+            clip.board,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        self.create_entry(entry).await
+    }
+
+    async fn update_entry(
+        &self,
+        mut entry: Entry,
+        rewrite_links: bool,
+    ) -> EntryDomainResult<Entry> {
+        let (existing, access) = match (entry.owner, entry.uuid) {
+            (Some(owner_uuid), Some(uuid)) => self.entry_access(owner_uuid, uuid).await?,
+            _ => return Err(MemorizDomainError::NotFoundError),
+        };
+        if !access.can_edit() {
+            return Err(MemorizDomainError::Forbidden);
+        }
+
+        //Saved under its owner: an editor of a shared entry cannot move it to another board.
+        entry.id = existing.id;
+        entry.owner = existing.owner;
+        if access != Access::Owner {
+            entry.board = existing.board;
+        }
+        //Clients do not send when the reminder went: kept until the reminder moves.
+        if entry.remind_at == existing.remind_at {
+            entry.reminded_on = existing.reminded_on;
+        }
+        //A moved due date anchors the rule on its new day.
+        if entry.due_on != existing.due_on {
+            if let Some(recurrence) = entry.recurrence.as_mut() {
+                recurrence.by_month_day = None;
+            }
+        }
+        entry.refresh_next_occurrence();
+
+        if entry.content != existing.content {
+            self.update_content_document(&existing, entry.content.as_deref().unwrap_or_default())
+                .await?;
+        }
+
+        let previous_title = match rewrite_links {
+            true => Some(existing.title),
+            false => None,
+        };
+
+        let entry = self.storage.update_entry(entry).await?;
+        self.refresh_entry_links(&entry).await?;
+        self.record_entry_change(ChangeKind::Updated, &entry)
+            .await?;
+
+        if let (Some(previous_title), Some(owner_uuid), Some(uuid)) =
+            (previous_title, entry.owner, entry.uuid)
+        {
+            if !same_title(&previous_title, &entry.title) {
+                self.rewrite_backlinks(owner_uuid, uuid, &previous_title, &entry.title)
+                    .await?;
+            }
+        }
+
+        Ok(entry)
+    }
+
+    async fn delete_entry(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()> {
+        //Recipients are read before the shares go away.
+        let board_uuid = match self.storage.get_entry(owner_uuid, uuid).await {
+            Ok(entry) => entry.board,
+            Err(_) => None,
+        };
+        let recipients = self.change_recipients(owner_uuid, uuid, board_uuid).await?;
+        let attachments = self.storage.get_entry_attachments(owner_uuid, uuid).await?;
+
+        self.storage.delete_entry(owner_uuid, uuid).await?;
+        self.storage.delete_entry_document(owner_uuid, uuid).await?;
+        self.storage
+            .delete_entry_attachments(owner_uuid, uuid)
+            .await?;
+        self.delete_attachment_blobs(&attachments).await;
+        self.storage
+            .delete_item_public_links(owner_uuid, uuid)
+            .await?;
+        self.storage.delete_item_shares(owner_uuid, uuid).await?;
+        if let Err(e) = self.search_engine.delete_entry(uuid).await {
+            println!("ENTRY {} not removed from the index: {}", uuid, e);
+        }
+
+        self.record_change(
+            &recipients,
+            ChangeKind::Deleted,
+            ChangedItem::Entry,
+            uuid.to_string(),
+            owner_uuid,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn archive_entry(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Entry> {
+        let (mut entry, access) = self.entry_access(owner_uuid, uuid).await?;
+        if !access.can_edit() {
+            return Err(MemorizDomainError::Forbidden);
+        }
+
+        //A recurring entry marked as done goes to its next occurrence.
+        entry.refresh_next_occurrence();
+        if !entry.roll_forward() {
+            entry.archived = true;
+        }
+        let entry = self.storage.update_entry(entry).await?;
+        self.record_entry_change(ChangeKind::Updated, &entry)
+            .await?;
+        Ok(entry)
+    }
+
+    async fn undo_archive_entry(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Entry> {
+        let (mut entry, access) = self.entry_access(owner_uuid, uuid).await?;
+        if !access.can_edit() {
+            return Err(MemorizDomainError::Forbidden);
+        }
+        entry.archived = false;
+        let entry = self.storage.update_entry(entry).await?;
+        self.record_entry_change(ChangeKind::Updated, &entry)
+            .await?;
+        Ok(entry)
+    }
+
+    async fn create_board(&self, board: Board) -> EntryDomainResult<Board> {
+        let board = self.storage.create_board(board).await?;
+        self.record_board_change(ChangeKind::Created, &board)
+            .await?;
+        Ok(board)
+    }
+    async fn update_board(&self, mut board: Board) -> EntryDomainResult<Board> {
+        let (existing, access) = match (board.owner, board.uuid) {
+            (Some(owner_uuid), Some(uuid)) => self.board_access(owner_uuid, uuid).await?,
+            _ => return Err(MemorizDomainError::NotFoundError),
+        };
+        if !access.can_edit() {
+            return Err(MemorizDomainError::Forbidden);
+        }
+
+        board.owner = existing.owner;
+        let board = self.storage.update_board(board).await?;
+        self.record_board_change(ChangeKind::Updated, &board)
+            .await?;
+        Ok(board)
+    }
+    async fn delete_board(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()> {
+        let recipients = self.change_recipients(owner_uuid, uuid, None).await?;
+
+        self.storage.delete_board(owner_uuid, uuid).await?;
+        self.storage
+            .delete_item_public_links(owner_uuid, uuid)
+            .await?;
+        self.storage.delete_item_shares(owner_uuid, uuid).await?;
+
+        self.record_change(
+            &recipients,
+            ChangeKind::Deleted,
+            ChangedItem::Board,
+            uuid.to_string(),
+            owner_uuid,
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn share_item(
+        &self,
+        owner_uuid: uuid::Uuid,
+        kind: SharedItemKind,
+        item_uuid: uuid::Uuid,
+        recipient_uuid: uuid::Uuid,
+        role: ShareRole,
+    ) -> EntryDomainResult<Share> {
+        if recipient_uuid == owner_uuid {
+            return Err(MemorizDomainError::Forbidden);
+        }
+
+        self.check_item_owner(owner_uuid, kind, item_uuid).await?;
+        let share = Share::new(item_uuid, kind, owner_uuid, recipient_uuid, role, None);
+        let share = self.storage.upsert_share(share).await?;
+
+        self.record_access_change(owner_uuid, kind, item_uuid, recipient_uuid)
+            .await?;
+        Ok(share)
+    }
+
+    async fn unshare_item(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+        recipient_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()> {
+        let share = self
+            .storage
+            .get_item_shares(owner_uuid, item_uuid)
+            .await?
+            .into_iter()
+            .find(|share| share.recipient == recipient_uuid);
+
+        self.storage
+            .delete_share(owner_uuid, item_uuid, recipient_uuid)
+            .await?;
+
+        if let Some(share) = share {
+            self.record_access_change(owner_uuid, share.kind, item_uuid, recipient_uuid)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn get_item_shares(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<Share>> {
+        Ok(self.storage.get_item_shares(owner_uuid, item_uuid).await?)
+    }
+
+    async fn get_shared_with_me(&self, user_uuid: uuid::Uuid) -> EntryDomainResult<SharedWithMe> {
+        let mut shared = SharedWithMe::default();
+
+        //Shares of deleted items are skipped.
+        for share in self.storage.get_recipient_shares(user_uuid).await? {
+            match share.kind {
+                SharedItemKind::Board => {
+                    if let Ok(board) = self.storage.get_board(share.owner, share.item).await {
+                        shared.boards.push(SharedBoard {
+                            board: board,
+                            role: share.role,
+                        });
+                    }
+                }
+                SharedItemKind::Entry => {
+                    if let Ok(entry) = self.storage.get_entry(share.owner, share.item).await {
+                        shared.entries.push(SharedEntry {
+                            entry: entry,
+                            role: share.role,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(shared)
+    }
+
+    async fn create_public_link(
+        &self,
+        owner_uuid: uuid::Uuid,
+        kind: SharedItemKind,
+        item_uuid: uuid::Uuid,
+        expires_on: Option<DateTime<Utc>>,
+        password: Option<String>,
+    ) -> EntryDomainResult<PublicLink> {
+        self.check_item_owner(owner_uuid, kind, item_uuid).await?;
+
+        let password_hash = match password.filter(|password| !password.is_empty()) {
+            Some(password) => Some(
+                tokio::task::spawn_blocking(move || bcrypt::hash(password, bcrypt::DEFAULT_COST))
+                    .await
+                    .map_err(|e| MemorizDomainError::Password(e.to_string()))?
+                    .map_err(|e| MemorizDomainError::Password(e.to_string()))?,
+            ),
+            None => None,
+        };
+
+        //Two random uuids: 244 bits nobody can guess.
+        let token = format!(
+            "{}{}",
+            uuid::Uuid::new_v4().to_simple(),
+            uuid::Uuid::new_v4().to_simple()
+        );
+        let link = PublicLink::new(
+            token,
+            item_uuid,
+            kind,
+            owner_uuid,
+            password_hash,
+            expires_on,
+            Some(Utc::now()),
+        );
+        Ok(self.storage.create_public_link(link).await?)
+    }
+
+    async fn get_item_public_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<PublicLink>> {
+        Ok(self
+            .storage
+            .get_item_public_links(owner_uuid, item_uuid)
+            .await?)
+    }
+
+    async fn revoke_public_link(
+        &self,
+        owner_uuid: uuid::Uuid,
+        token: String,
+    ) -> EntryDomainResult<()> {
+        Ok(self.storage.delete_public_link(owner_uuid, token).await?)
+    }
+
+    //The link is read on every request: a revoked link stops working at once.
+    async fn get_public_item(
+        &self,
+        token: String,
+        password: Option<String>,
+    ) -> EntryDomainResult<PublicItem> {
+        let link = match self.storage.get_public_link(token).await? {
+            Some(link) if !link.is_expired(Utc::now()) => link,
+            _ => return Err(MemorizDomainError::NotFoundError),
+        };
+
+        if let Some(password_hash) = link.password_hash.clone() {
+            let now = Utc::now();
+            if !self.password_throttle.reserve_attempt(&link.token, now) {
+                return Err(MemorizDomainError::TooManyAttempts);
+            }
+
+            //Bcrypt is slow on purpose: it runs on a blocking thread, not on the executor.
+            let granted = match password {
+                Some(password) => tokio::task::spawn_blocking(move || {
+                    bcrypt::verify(password, &password_hash).unwrap_or(false)
+                })
+                .await
+                .unwrap_or(false),
+                None => false,
+            };
+            //The reserved attempt stays a failure.
+            if !granted {
+                return Err(MemorizDomainError::Forbidden);
+            }
+            self.password_throttle.clear(&link.token);
+        }
+
+        match link.kind {
+            SharedItemKind::Entry => {
+                let entry = self
+                    .storage
+                    .get_entry(link.owner, link.item)
+                    .await
+                    .map_err(|_| MemorizDomainError::NotFoundError)?;
+                Ok(PublicItem::Entry {
+                    entry: MemorizDomain::public_entry(entry),
+                })
+            }
+            SharedItemKind::Board => {
+                let mut board = self
+                    .storage
+                    .get_board(link.owner, link.item)
+                    .await
+                    .map_err(|_| MemorizDomainError::NotFoundError)?;
+                board.owner = None;
+
+                //Archived entries stay private.
+                let entries = self
+                    .storage
+                    .get_all_entries_by_board(link.owner, link.item)
+                    .await?;
+                let entries = MemorizDomain::filter_entries(entries, Some(false), None)
+                    .into_iter()
+                    .map(MemorizDomain::public_entry)
+                    .collect();
+                Ok(PublicItem::Board {
+                    board: board,
+                    entries: entries,
+                })
+            }
+        }
+    }
+
+    async fn sync(
+        &self,
+        user_uuid: uuid::Uuid,
+        request: SyncRequest,
+    ) -> EntryDomainResult<SyncResponse> {
+        let mut response = SyncResponse::default();
+        let mut touched: HashSet<String> = HashSet::new();
+
+        //A cursor ahead of the log comes from another database, like before a restore
+        //in a new one: the client syncs everything again.
+        let cursor = match request.cursor {
+            Some(cursor) if cursor > self.storage.get_last_change_seq(user_uuid).await? => None,
+            cursor => cursor,
+        };
+
+        //A rejected mutation does not prevent applying the next ones.
+        for (index, mutation) in request.mutations.into_iter().enumerate() {
+            let id = mutation.item_id();
+            let result = match self
+                .apply_sync_mutation(user_uuid, index, mutation, cursor, &mut touched)
+                .await
+            {
+                Ok(result) => result,
+                Err(e) => {
+                    let mut result = MutationResult::new(index, MutationStatus::Rejected, id);
+                    result.message = Some(e.to_string());
+                    result
+                }
+            };
+            response.results.push(result);
+        }
+
+        //Applied mutations are part of the changes sent back.
+        match cursor {
+            Some(cursor) => self.delta_sync(user_uuid, cursor, &mut response).await?,
+            None => self.full_sync(user_uuid, &mut response).await?,
+        }
+        Ok(response)
     }
 }
@@ -0,0 +1,275 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use crate::core::dump::{
+    DumpHeader, DumpRecord, DumpReport, DumpedAttachment, DumpedDocument, DumpedPublicLink,
+    EntryLabel, DUMP_FORMAT, DUMP_FORMAT_VERSION,
+};
+use crate::core::link::EntryLink;
+use crate::storage::traits::StorageTrait;
+use std::collections::{HashMap, HashSet};
+use std::io::{BufRead, Write};
+
+//Lines of the change log read at once.
+const CHANGES_PAGE_SIZE: i64 = 1000;
+
+//Write the data of one owner, or of every owner, as JSON Lines.
+//Records are ordered so that a restore never references something not restored yet.
+pub async fn dump<W: Write>(
+    storage: &dyn StorageTrait,
+    owner_uuid: Option<uuid::Uuid>,
+    output: &mut W,
+) -> EntryDomainResult<DumpReport> {
+    let mut report = DumpReport::default();
+    write_record(output, &DumpRecord::Header(DumpHeader::new(owner_uuid)))?;
+
+    let owners = match owner_uuid {
+        Some(owner_uuid) => vec![owner_uuid],
+        None => storage.get_all_owners().await?,
+    };
+
+    for owner_uuid in owners {
+        report.owners += 1;
+
+        let mut items_uuid: Vec<uuid::Uuid> = Vec::new();
+        for board in storage.get_all_boards(owner_uuid).await? {
+            items_uuid.extend(board.uuid);
+            write_record(output, &DumpRecord::Board(board))?;
+            report.boards += 1;
+        }
+
+        for label in storage.get_owner_labels(owner_uuid).await? {
+            write_record(output, &DumpRecord::Label(label))?;
+            report.labels += 1;
+        }
+
+        //The same token keeps the subscribed calendars working after a restore.
+        if let Some(token) = storage.get_calendar_token(owner_uuid).await? {
+            write_record(output, &DumpRecord::CalendarToken(token))?;
+            report.calendar_tokens += 1;
+        }
+
+        //After the boards and labels they reference.
+        for template in storage.get_all_templates(owner_uuid).await? {
+            write_record(output, &DumpRecord::Template(template))?;
+            report.templates += 1;
+        }
+        //Queries reference labels and boards by name: they are restored as they are.
+        for saved_search in storage.get_all_saved_searches(owner_uuid).await? {
+            write_record(output, &DumpRecord::SavedSearch(saved_search))?;
+            report.saved_searches += 1;
+        }
+
+        let entries = storage.get_owner_entries(owner_uuid).await?;
+        let mut entries_uuid: Vec<uuid::Uuid> = Vec::new();
+        for entry in entries {
+            entries_uuid.extend(entry.uuid);
+            write_record(output, &DumpRecord::Entry(entry))?;
+            report.entries += 1;
+        }
+
+        for (entry_uuid, label) in storage.get_all_entries_labels(owner_uuid).await? {
+            let entry_label = EntryLabel::new(entry_uuid, label.id, owner_uuid);
+            write_record(output, &DumpRecord::EntryLabel(entry_label))?;
+            report.entry_labels += 1;
+        }
+
+        for entry_uuid in &entries_uuid {
+            for link in storage.get_entry_links(owner_uuid, *entry_uuid).await? {
+                write_record(output, &DumpRecord::EntryLink(link))?;
+                report.entry_links += 1;
+            }
+            if let Some(document) = storage.get_entry_document(owner_uuid, *entry_uuid).await? {
+                let document = DumpedDocument::new(*entry_uuid, owner_uuid, document);
+                write_record(output, &DumpRecord::Document(document))?;
+                report.documents += 1;
+            }
+        }
+
+        let mut texts: HashMap<uuid::Uuid, String> = HashMap::new();
+        for entry_uuid in &entries_uuid {
+            texts.extend(
+                storage
+                    .get_entry_attachment_texts(owner_uuid, *entry_uuid)
+                    .await?,
+            );
+        }
+        for attachment in storage.get_owner_attachments(owner_uuid).await? {
+            let text = texts.remove(&attachment.uuid);
+            let attachment = DumpedAttachment::new(attachment, text);
+            write_record(output, &DumpRecord::Attachment(attachment))?;
+            report.attachments += 1;
+        }
+
+        items_uuid.extend(entries_uuid);
+        for item_uuid in items_uuid {
+            for share in storage.get_item_shares(owner_uuid, item_uuid).await? {
+                write_record(output, &DumpRecord::Share(share))?;
+                report.shares += 1;
+            }
+            for link in storage.get_item_public_links(owner_uuid, item_uuid).await? {
+                let link = DumpedPublicLink::new(link);
+                write_record(output, &DumpRecord::PublicLink(link))?;
+                report.public_links += 1;
+            }
+        }
+
+        //The change log keeps its sequence: the sync cursors of the clients stay valid.
+        let mut since = 0;
+        loop {
+            let changes = storage
+                .get_changes(owner_uuid, since, CHANGES_PAGE_SIZE)
+                .await?;
+            let count = changes.len() as i64;
+            for change in changes {
+                since = change.seq;
+                write_record(output, &DumpRecord::Change(change))?;
+                report.changes += 1;
+            }
+            if count < CHANGES_PAGE_SIZE {
+                break;
+            }
+        }
+    }
+
+    output
+        .flush()
+        .map_err(|e| MemorizDomainError::Dump(e.to_string()))?;
+    Ok(report)
+}
+
+//Write the records of a dump in the storage, replacing the existing ones with the same uuid.
+pub async fn restore<R: BufRead>(
+    storage: &dyn StorageTrait,
+    input: R,
+) -> EntryDomainResult<DumpReport> {
+    let mut report = DumpReport::default();
+    let mut header_read = false;
+    let mut owners: HashSet<uuid::Uuid> = HashSet::new();
+    let mut links: HashMap<(uuid::Uuid, uuid::Uuid), Vec<EntryLink>> = HashMap::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| MemorizDomainError::Dump(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let record: DumpRecord = serde_json::from_str(&line)
+            .map_err(|e| MemorizDomainError::Dump(format!("line {}: {}", index + 1, e)))?;
+
+        match record {
+            DumpRecord::Header(header) => {
+                check_header(&header)?;
+                header_read = true;
+                continue;
+            }
+            _ if !header_read => {
+                return Err(MemorizDomainError::Dump(
+                    "Missing dump header on the first line".to_string(),
+                ))
+            }
+            DumpRecord::Board(board) => {
+                owners.extend(board.owner);
+                storage.restore_board(board).await?;
+                report.boards += 1;
+            }
+            DumpRecord::Label(label) => {
+                owners.insert(label.owner);
+                storage.restore_label(label).await?;
+                report.labels += 1;
+            }
+            DumpRecord::Entry(entry) => {
+                owners.extend(entry.owner);
+                storage.restore_entry(entry).await?;
+                report.entries += 1;
+            }
+            DumpRecord::EntryLabel(entry_label) => {
+                storage
+                    .add_entry_label(entry_label.owner, entry_label.entry, entry_label.label)
+                    .await?;
+                report.entry_labels += 1;
+            }
+            DumpRecord::EntryLink(link) => {
+                links
+                    .entry((link.owner, link.source))
+                    .or_default()
+                    .push(link);
+                report.entry_links += 1;
+            }
+            DumpRecord::Share(share) => {
+                storage.upsert_share(share).await?;
+                report.shares += 1;
+            }
+            DumpRecord::CalendarToken(token) => {
+                owners.insert(token.owner);
+                storage.restore_calendar_token(token).await?;
+                report.calendar_tokens += 1;
+            }
+            DumpRecord::PublicLink(link) => {
+                storage.restore_public_link(link.into_public_link()).await?;
+                report.public_links += 1;
+            }
+            DumpRecord::Change(change) => {
+                owners.insert(change.user);
+                storage.restore_change(change).await?;
+                report.changes += 1;
+            }
+            DumpRecord::Document(document) => {
+                storage
+                    .save_entry_document(document.owner, document.entry, document.document)
+                    .await?;
+                report.documents += 1;
+            }
+            DumpRecord::Attachment(dumped) => {
+                let attachment = dumped.attachment;
+                storage.restore_attachment(&attachment).await?;
+                if let Some(text) = dumped.text {
+                    storage.save_attachment_text(&attachment, text).await?;
+                }
+                report.attachments += 1;
+            }
+            DumpRecord::Template(template) => {
+                owners.extend(template.owner);
+                storage.restore_template(template).await?;
+                report.templates += 1;
+            }
+            DumpRecord::SavedSearch(saved_search) => {
+                owners.extend(saved_search.owner);
+                storage.restore_saved_search(saved_search).await?;
+                report.saved_searches += 1;
+            }
+        }
+    }
+
+    //Links of an entry replace its existing ones all at once.
+    for ((owner_uuid, source_uuid), entry_links) in links {
+        storage
+            .replace_entry_links(owner_uuid, source_uuid, entry_links)
+            .await?;
+    }
+    report.owners = owners.len();
+
+    Ok(report)
+}
+
+fn check_header(header: &DumpHeader) -> EntryDomainResult<()> {
+    if header.format != DUMP_FORMAT {
+        return Err(MemorizDomainError::Dump(format!(
+            "Unknown dump format: {}",
+            header.format
+        )));
+    }
+    if header.version > DUMP_FORMAT_VERSION {
+        return Err(MemorizDomainError::Dump(format!(
+            "Dump version {} is newer than the supported version {}",
+            header.version, DUMP_FORMAT_VERSION
+        )));
+    }
+    Ok(())
+}
+
+fn write_record<W: Write>(output: &mut W, record: &DumpRecord) -> EntryDomainResult<()> {
+    serde_json::to_writer(&mut *output, record)
+        .map_err(|e| MemorizDomainError::Dump(e.to_string()))?;
+    output
+        .write_all(b"\n")
+        .map_err(|e| MemorizDomainError::Dump(e.to_string()))
+}
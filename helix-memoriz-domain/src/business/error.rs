@@ -1,4 +1,5 @@
-use crate::storage::error::StorageError;
+use crate::blob::error::BlobError;
+use crate::storage::error::{SearchEngineError, StorageError};
 use std::result::Result;
 use thiserror::Error;
 
@@ -11,11 +12,43 @@ pub enum MemorizDomainError {
     StorageError,
     #[error("Not found error")]
     NotFoundError,
+    #[error("Forbidden")]
+    Forbidden,
+    #[error("Too many attempts")]
+    TooManyAttempts,
+    #[error("Password error: {0}")]
+    Password(String),
+    #[error("Document error: {0}")]
+    Document(String),
+    #[error("Import error: {0}")]
+    Import(String),
+    #[error("Export error: {0}")]
+    Export(String),
+    #[error("Dump error: {0}")]
+    Dump(String),
+    #[error("Attachment error: {0}")]
+    Attachment(String),
+    #[error("Clip error: {0}")]
+    Clip(String),
+    #[error("Template error: {0}")]
+    Template(String),
+    #[error("Query error: {0}")]
+    Query(String),
     #[error("Storage error: {source}")]
     Storage {
         #[from]
         source: StorageError,
     },
+    #[error("Search engine error: {source}")]
+    SearchEngine {
+        #[from]
+        source: SearchEngineError,
+    },
+    #[error("Blob error: {source}")]
+    Blob {
+        #[from]
+        source: BlobError,
+    },
 }
 
 //Define a generic error type to simplify return.
@@ -0,0 +1,193 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use crate::business::export::zip_stream::ZipStream;
+use crate::business::links::title_key;
+use crate::core::board::Board;
+use crate::core::entry::Entry;
+use crate::core::label::Label;
+use chrono::prelude::*;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+
+pub const FRONT_MATTER_DELIMITER: &str = "---";
+const UNTITLED: &str = "Untitled";
+
+//YAML header of an exported Markdown file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct FrontMatter {
+    pub uuid: Option<uuid::Uuid>,
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub archived: bool,
+    pub created_on: Option<DateTime<Utc>>,
+    pub updated_on: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due_on: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+//Markdown file of an entry: front matter, then the content as written by the user.
+pub fn entry_to_markdown(entry: &Entry, labels: &[String]) -> String {
+    let front_matter = FrontMatter {
+        uuid: entry.uuid,
+        title: Some(entry.title.clone()),
+        color: entry.color.clone(),
+        labels: labels.to_vec(),
+        archived: entry.archived,
+        created_on: entry.created_on,
+        updated_on: entry.updated_on,
+        due_on: entry.due_on,
+        data: entry.data.clone(),
+    };
+
+    //Front matter only holds plain values: its serialization cannot fail.
+    let yaml = serde_yaml::to_string(&front_matter).unwrap();
+    let yaml = yaml.trim_start_matches(FRONT_MATTER_DELIMITER).trim();
+
+    let mut markdown = format!(
+        "{}\n{}\n{}\n\n",
+        FRONT_MATTER_DELIMITER, yaml, FRONT_MATTER_DELIMITER
+    );
+    if let Some(content) = &entry.content {
+        markdown.push_str(content);
+        if !content.ends_with('\n') {
+            markdown.push('\n');
+        }
+    }
+    markdown
+}
+
+//Zip of the user data, built one page of entries at a time while it is being sent.
+//One folder per board, entries without board at the root.
+pub struct MarkdownExport {
+    owner: uuid::Uuid,
+    last_entry: Option<uuid::Uuid>,
+    folders: HashMap<uuid::Uuid, String>,
+    used_paths: HashSet<String>,
+    zip: Option<ZipStream>,
+}
+
+impl MarkdownExport {
+    pub fn new(owner: uuid::Uuid, boards: &[Board]) -> MarkdownExport {
+        let mut folders: HashMap<uuid::Uuid, String> = HashMap::new();
+        let mut used_folders: HashSet<String> = HashSet::new();
+        for board in boards {
+            if let Some(board_uuid) = board.uuid {
+                let folder = unique_name(&sanitize_file_name(&board.title), "", &used_folders);
+                used_folders.insert(title_key(&folder));
+                folders.insert(board_uuid, folder);
+            }
+        }
+
+        MarkdownExport {
+            owner: owner,
+            last_entry: None,
+            folders: folders,
+            used_paths: HashSet::new(),
+            zip: Some(ZipStream::new()),
+        }
+    }
+
+    pub fn owner(&self) -> uuid::Uuid {
+        self.owner
+    }
+
+    //Last entry added, the next page starts after it.
+    pub fn last_entry(&self) -> Option<uuid::Uuid> {
+        self.last_entry
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.zip.is_none()
+    }
+
+    //Bytes of the files of a page of entries.
+    pub fn add_entries(
+        &mut self,
+        entries: Vec<Entry>,
+        entries_labels: Vec<(uuid::Uuid, Label)>,
+    ) -> EntryDomainResult<Vec<u8>> {
+        let mut labels: HashMap<uuid::Uuid, Vec<String>> = HashMap::new();
+        for (entry_uuid, label) in entries_labels {
+            labels.entry(entry_uuid).or_default().push(label.name);
+        }
+
+        let mut chunk: Vec<u8> = Vec::new();
+        for entry in entries {
+            let entry_labels = entry
+                .uuid
+                .and_then(|uuid| labels.get(&uuid))
+                .cloned()
+                .unwrap_or_default();
+            let path = self.entry_path(&entry);
+            let markdown = entry_to_markdown(&entry, &entry_labels);
+            let modified_on = entry
+                .updated_on
+                .or(entry.created_on)
+                .unwrap_or_else(Utc::now);
+
+            let zip = self
+                .zip
+                .as_mut()
+                .ok_or_else(|| MemorizDomainError::Export("Export finished".to_string()))?;
+            chunk.extend(zip.add_file(&path, markdown.as_bytes(), modified_on)?);
+            self.last_entry = entry.uuid;
+        }
+        Ok(chunk)
+    }
+
+    //Bytes closing the archive, once every entry is added.
+    pub fn finish(&mut self) -> EntryDomainResult<Vec<u8>> {
+        match self.zip.take() {
+            Some(zip) => zip.finish(),
+            None => Err(MemorizDomainError::Export("Export finished".to_string())),
+        }
+    }
+
+    //Path of the entry file, made unique without case since most file systems ignore it.
+    fn entry_path(&mut self, entry: &Entry) -> String {
+        let folder = match entry.board.and_then(|board| self.folders.get(&board)) {
+            Some(folder) => format!("{}/", folder),
+            None => String::new(),
+        };
+
+        let base_name = format!("{}{}", folder, sanitize_file_name(&entry.title));
+        let path = unique_name(&base_name, ".md", &self.used_paths);
+        self.used_paths.insert(title_key(&path));
+        path
+    }
+}
+
+//Name usable as a file name on every platform.
+fn sanitize_file_name(title: &str) -> String {
+    let name: String = title
+        .chars()
+        .map(|character| match character {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            character if character.is_control() => ' ',
+            character => character,
+        })
+        .collect();
+
+    //Leading dots would hide the file, trailing ones are dropped by Windows.
+    let name = name.trim().trim_matches('.').trim();
+    match name.is_empty() {
+        true => UNTITLED.to_string(),
+        false => name.chars().take(120).collect(),
+    }
+}
+
+//"Name", then "Name (2)", "Name (3)"... until the name is not used yet.
+fn unique_name(base_name: &str, extension: &str, used_names: &HashSet<String>) -> String {
+    let mut name = format!("{}{}", base_name, extension);
+    let mut counter = 2;
+    while used_names.contains(&title_key(&name)) {
+        name = format!("{} ({}){}", base_name, counter, extension);
+        counter += 1;
+    }
+    name
+}
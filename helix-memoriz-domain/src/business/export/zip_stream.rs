@@ -0,0 +1,339 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use chrono::prelude::*;
+use crc32fast::Hasher;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+use std::convert::TryFrom;
+use std::io::Write;
+
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x0403_4b50;
+const CENTRAL_DIRECTORY_HEADER_SIGNATURE: u32 = 0x0201_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0606_4b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIGNATURE: u32 = 0x0706_4b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x0605_4b50;
+const VERSION: u16 = 20;
+const ZIP64_VERSION: u16 = 45;
+const ZIP64_EXTRA_FIELD_ID: u16 = 0x0001;
+const UTF8_NAMES_FLAG: u16 = 0x0800;
+const DEFLATE_METHOD: u16 = 8;
+//Values meaning the real one is in a ZIP64 record.
+const ZIP64_U16: u16 = u16::MAX;
+const ZIP64_U32: u32 = u32::MAX;
+
+//Zip archive written front to back, without seeking, so it can be sent while being built.
+//Each file is compressed in memory before being emitted: only the central directory is kept.
+//Past 65535 files or 4 GiB, sizes, offsets and counts go in ZIP64 records.
+pub struct ZipStream {
+    offset: u64,
+    files_count: u64,
+    central_directory: Vec<u8>,
+}
+
+impl ZipStream {
+    pub fn new() -> ZipStream {
+        ZipStream {
+            offset: 0,
+            files_count: 0,
+            central_directory: Vec::new(),
+        }
+    }
+
+    //Bytes of the local header and compressed content of a file.
+    pub fn add_file(
+        &mut self,
+        path: &str,
+        content: &[u8],
+        modified_on: DateTime<Utc>,
+    ) -> EntryDomainResult<Vec<u8>> {
+        let mut hasher = Hasher::new();
+        hasher.update(content);
+        let crc = hasher.finalize();
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        //Writing to a Vec cannot fail.
+        encoder.write_all(content).unwrap();
+        let compressed = encoder.finish().unwrap();
+
+        let (time, date) = to_dos_date_time(&modified_on);
+        let name = path.as_bytes();
+        let name_size = u16::try_from(name.len()).map_err(|_| too_large("File name"))?;
+        let compressed_size = compressed.len() as u64;
+        let size = content.len() as u64;
+
+        //The local header has both sizes in its ZIP64 field, or none of them.
+        let local_zip64 = size >= ZIP64_U32 as u64 || compressed_size >= ZIP64_U32 as u64;
+        let mut local_extra: Vec<u8> = Vec::new();
+        if local_zip64 {
+            put_u16(&mut local_extra, ZIP64_EXTRA_FIELD_ID);
+            put_u16(&mut local_extra, 16);
+            put_u64(&mut local_extra, size);
+            put_u64(&mut local_extra, compressed_size);
+        }
+
+        let mut chunk: Vec<u8> =
+            Vec::with_capacity(30 + name.len() + local_extra.len() + compressed.len());
+        put_u32(&mut chunk, LOCAL_FILE_HEADER_SIGNATURE);
+        put_u16(&mut chunk, version(local_zip64));
+        put_u16(&mut chunk, UTF8_NAMES_FLAG);
+        put_u16(&mut chunk, DEFLATE_METHOD);
+        put_u16(&mut chunk, time);
+        put_u16(&mut chunk, date);
+        put_u32(&mut chunk, crc);
+        put_u32(&mut chunk, zip64_u32(compressed_size, local_zip64));
+        put_u32(&mut chunk, zip64_u32(size, local_zip64));
+        put_u16(&mut chunk, name_size);
+        put_u16(&mut chunk, local_extra.len() as u16);
+        chunk.extend_from_slice(name);
+        chunk.extend_from_slice(&local_extra);
+        chunk.extend_from_slice(&compressed);
+
+        //The central directory only has the values too large for their field.
+        let mut extra: Vec<u8> = Vec::new();
+        for value in &[size, compressed_size, self.offset] {
+            if *value >= ZIP64_U32 as u64 {
+                put_u64(&mut extra, *value);
+            }
+        }
+        if !extra.is_empty() {
+            let data_size = extra.len() as u16;
+            let mut header: Vec<u8> = Vec::new();
+            put_u16(&mut header, ZIP64_EXTRA_FIELD_ID);
+            put_u16(&mut header, data_size);
+            extra.splice(0..0, header);
+        }
+        let directory_zip64 = !extra.is_empty();
+
+        let directory = &mut self.central_directory;
+        put_u32(directory, CENTRAL_DIRECTORY_HEADER_SIGNATURE);
+        put_u16(directory, version(directory_zip64));
+        put_u16(directory, version(directory_zip64));
+        put_u16(directory, UTF8_NAMES_FLAG);
+        put_u16(directory, DEFLATE_METHOD);
+        put_u16(directory, time);
+        put_u16(directory, date);
+        put_u32(directory, crc);
+        put_u32(directory, zip64_u32(compressed_size, false));
+        put_u32(directory, zip64_u32(size, false));
+        put_u16(directory, name_size);
+        put_u16(directory, extra.len() as u16);
+        //Comment, disk number, internal and external attributes.
+        put_u16(directory, 0);
+        put_u16(directory, 0);
+        put_u16(directory, 0);
+        put_u32(directory, 0);
+        put_u32(directory, zip64_u32(self.offset, false));
+        directory.extend_from_slice(name);
+        directory.extend_from_slice(&extra);
+
+        self.offset += chunk.len() as u64;
+        self.files_count += 1;
+        Ok(chunk)
+    }
+
+    //Bytes of the central directory closing the archive.
+    pub fn finish(self) -> EntryDomainResult<Vec<u8>> {
+        let mut chunk = self.central_directory;
+        let directory_size = chunk.len() as u64;
+        let directory_offset = self.offset;
+        let zip64 = self.files_count >= ZIP64_U16 as u64
+            || directory_size >= ZIP64_U32 as u64
+            || directory_offset >= ZIP64_U32 as u64;
+
+        if zip64 {
+            let record_offset = directory_offset + directory_size;
+            put_u32(&mut chunk, ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+            //Size of the record after this field.
+            put_u64(&mut chunk, 44);
+            put_u16(&mut chunk, ZIP64_VERSION);
+            put_u16(&mut chunk, ZIP64_VERSION);
+            put_u32(&mut chunk, 0);
+            put_u32(&mut chunk, 0);
+            put_u64(&mut chunk, self.files_count);
+            put_u64(&mut chunk, self.files_count);
+            put_u64(&mut chunk, directory_size);
+            put_u64(&mut chunk, directory_offset);
+
+            put_u32(&mut chunk, ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIGNATURE);
+            put_u32(&mut chunk, 0);
+            put_u64(&mut chunk, record_offset);
+            put_u32(&mut chunk, 1);
+        }
+
+        let files_count = match self.files_count >= ZIP64_U16 as u64 {
+            true => ZIP64_U16,
+            false => self.files_count as u16,
+        };
+        put_u32(&mut chunk, END_OF_CENTRAL_DIRECTORY_SIGNATURE);
+        put_u16(&mut chunk, 0);
+        put_u16(&mut chunk, 0);
+        put_u16(&mut chunk, files_count);
+        put_u16(&mut chunk, files_count);
+        put_u32(&mut chunk, zip64_u32(directory_size, false));
+        put_u32(&mut chunk, zip64_u32(directory_offset, false));
+        put_u16(&mut chunk, 0);
+        Ok(chunk)
+    }
+}
+
+impl Default for ZipStream {
+    fn default() -> Self {
+        ZipStream::new()
+    }
+}
+
+fn put_u16(buffer: &mut Vec<u8>, value: u16) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u32(buffer: &mut Vec<u8>, value: u32) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_u64(buffer: &mut Vec<u8>, value: u64) {
+    buffer.extend_from_slice(&value.to_le_bytes());
+}
+
+fn version(zip64: bool) -> u16 {
+    match zip64 {
+        true => ZIP64_VERSION,
+        false => VERSION,
+    }
+}
+
+//The value, or the marker of a value in a ZIP64 record.
+fn zip64_u32(value: u64, zip64: bool) -> u32 {
+    match zip64 || value >= ZIP64_U32 as u64 {
+        true => ZIP64_U32,
+        false => value as u32,
+    }
+}
+
+fn too_large(what: &str) -> MemorizDomainError {
+    MemorizDomainError::Export(format!("{} too large for a zip archive", what))
+}
+
+//MS-DOS time and date, which only represent the years 1980 to 2107.
+fn to_dos_date_time(date: &DateTime<Utc>) -> (u16, u16) {
+    if date.year() < 1980 {
+        return (0, (1 << 5) | 1);
+    }
+    if date.year() > 2107 {
+        return ((23 << 11) | (59 << 5) | 29, (127 << 9) | (12 << 5) | 31);
+    }
+
+    let time = (date.hour() << 11) | (date.minute() << 5) | (date.second() / 2);
+    let dos_date = (((date.year() - 1980) as u32) << 9) | (date.month() << 5) | date.day();
+    (time as u16, dos_date as u16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+    use zip::ZipArchive;
+
+    #[test]
+    fn writes_a_readable_archive() {
+        let mut zip = ZipStream::new();
+        let mut archive = zip
+            .add_file("Board/Entry.md", b"# Entry", Utc::now())
+            .unwrap();
+        archive.extend(zip.add_file("Other.md", b"", Utc::now()).unwrap());
+        archive.extend(zip.finish().unwrap());
+
+        let mut archive = ZipArchive::new(Cursor::new(archive)).unwrap();
+        assert_eq!(archive.len(), 2);
+        let mut content = String::new();
+        archive
+            .by_name("Board/Entry.md")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, "# Entry");
+    }
+
+    #[test]
+    fn counts_more_files_than_the_classic_format() {
+        let mut zip = ZipStream::new();
+        let mut archive: Vec<u8> = Vec::new();
+        for index in 0..=u16::MAX as u32 {
+            archive.extend(zip.add_file(&index.to_string(), b"", Utc::now()).unwrap());
+        }
+        archive.extend(zip.finish().unwrap());
+
+        let archive = ZipArchive::new(Cursor::new(archive)).unwrap();
+        assert_eq!(archive.len(), u16::MAX as usize + 1);
+    }
+
+    #[test]
+    fn writes_the_offsets_beyond_4_gib_in_zip64_records() {
+        let mut zip = ZipStream::new();
+        //As if 4 GiB of files were already sent.
+        zip.offset = ZIP64_U32 as u64 + 10;
+        zip.add_file("Late.md", b"# Late", Utc::now()).unwrap();
+
+        let directory = zip.central_directory.clone();
+        let name_end = 46 + "Late.md".len();
+        assert_eq!(&directory[42..46], &ZIP64_U32.to_le_bytes());
+        assert_eq!(
+            &directory[name_end..name_end + 2],
+            &ZIP64_EXTRA_FIELD_ID.to_le_bytes()
+        );
+        assert_eq!(
+            &directory[name_end + 4..name_end + 12],
+            &(ZIP64_U32 as u64 + 10).to_le_bytes()
+        );
+
+        let end = zip.finish().unwrap();
+        let record = &end[directory.len()..];
+        assert_eq!(
+            &record[..4],
+            &ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes()
+        );
+        assert_eq!(
+            &record[48..56],
+            &(ZIP64_U32 as u64 + 10 + 30 + 7 + 8).to_le_bytes()[..]
+        );
+        assert_eq!(
+            &record[56..60],
+            &ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIGNATURE.to_le_bytes()
+        );
+        assert_eq!(&end[end.len() - 6..end.len() - 2], &ZIP64_U32.to_le_bytes());
+    }
+
+    #[test]
+    fn writes_the_classic_format_below_the_limits() {
+        let mut zip = ZipStream::new();
+        zip.add_file("Entry.md", b"# Entry", Utc::now()).unwrap();
+        let end = zip.finish().unwrap();
+        assert!(!end
+            .windows(4)
+            .any(|bytes| bytes == ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes()));
+    }
+
+    #[test]
+    fn clamps_the_dates_before_1980() {
+        let (time, date) = to_dos_date_time(&Utc.ymd(1970, 1, 1).and_hms(12, 0, 0));
+        assert_eq!((time, date), (0, (1 << 5) | 1));
+
+        let (time, date) = to_dos_date_time(&Utc.ymd(1980, 1, 1).and_hms(0, 0, 2));
+        assert_eq!((time, date), (1, (1 << 5) | 1));
+    }
+
+    #[test]
+    fn clamps_the_dates_after_2107() {
+        let last = to_dos_date_time(&Utc.ymd(2107, 12, 31).and_hms(23, 59, 59));
+        assert_eq!(
+            last,
+            ((23 << 11) | (59 << 5) | 29, (127 << 9) | (12 << 5) | 31)
+        );
+        assert_eq!(
+            to_dos_date_time(&Utc.ymd(2108, 1, 1).and_hms(0, 0, 0)),
+            last
+        );
+        assert_eq!(
+            to_dos_date_time(&Utc.ymd(9999, 6, 15).and_hms(8, 0, 0)),
+            last
+        );
+    }
+}
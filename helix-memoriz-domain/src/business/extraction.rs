@@ -0,0 +1,42 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use std::panic;
+
+//Text kept per attachment: enough to find a document, not to index a library.
+const MAX_EXTRACTED_CHARS: usize = 200_000;
+
+pub fn is_extractable(content_type: &str) -> bool {
+    content_type == "application/pdf" || content_type.starts_with("text/")
+}
+
+//Text of a PDF or text attachment, to be searched. Whitespace is collapsed.
+pub fn extract_attachment_text(content_type: &str, content: &[u8]) -> EntryDomainResult<String> {
+    let text = match content_type {
+        "application/pdf" => extract_pdf_text(content)?,
+        _ if content_type.starts_with("text/") => String::from_utf8_lossy(content).to_string(),
+        _ => {
+            return Err(MemorizDomainError::Attachment(format!(
+                "No text to extract from {}",
+                content_type
+            )))
+        }
+    };
+
+    Ok(text
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+        .chars()
+        .take(MAX_EXTRACTED_CHARS)
+        .collect())
+}
+
+//The PDF parser panics on some malformed documents: it must not take the worker down.
+fn extract_pdf_text(content: &[u8]) -> EntryDomainResult<String> {
+    match panic::catch_unwind(|| pdf_extract::extract_text_from_mem(content)) {
+        Ok(Ok(text)) => Ok(text),
+        Ok(Err(e)) => Err(MemorizDomainError::Attachment(format!("{:?}", e))),
+        Err(_) => Err(MemorizDomainError::Attachment(
+            "Unreadable PDF document".to_string(),
+        )),
+    }
+}
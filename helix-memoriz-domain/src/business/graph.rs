@@ -0,0 +1,293 @@
+use crate::business::links::{parse_link_titles, title_key};
+use crate::core::board::Board;
+use crate::core::entry::Entry;
+use crate::core::graph::*;
+use crate::core::label::Label;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+const UUID_LENGTH: usize = 36;
+
+pub fn entry_node_id(uuid: &uuid::Uuid) -> String {
+    format!("entry:{}", uuid)
+}
+
+fn board_node_id(uuid: &uuid::Uuid) -> String {
+    format!("board:{}", uuid)
+}
+
+fn label_node_id(id: &str) -> String {
+    format!("label:{}", id)
+}
+
+//Build the graph of the entries, with their boards, labels and content references.
+//Labels come from Entry.labels and from the given (entry uuid, label) associations.
+pub fn build_graph(
+    entries: &[Entry],
+    boards: &[Board],
+    entries_labels: &[(uuid::Uuid, Label)],
+) -> Graph {
+    let mut nodes: Vec<GraphNode> = Vec::new();
+    let mut edges: Vec<GraphEdge> = Vec::new();
+
+    let mut board_uuids: HashSet<uuid::Uuid> = HashSet::new();
+    for board in boards {
+        if let Some(uuid) = board.uuid {
+            board_uuids.insert(uuid);
+            nodes.push(GraphNode::new(
+                board_node_id(&uuid),
+                GraphNodeKind::Board,
+                board.title.to_owned(),
+                board.color.clone(),
+            ));
+        }
+    }
+
+    let mut entry_uuids: HashSet<uuid::Uuid> = HashSet::new();
+    let mut uuids_by_title: HashMap<String, uuid::Uuid> = HashMap::new();
+    for entry in entries {
+        if let Some(uuid) = entry.uuid {
+            entry_uuids.insert(uuid);
+            uuids_by_title
+                .entry(title_key(&entry.title))
+                .or_insert(uuid);
+        }
+    }
+
+    for entry in entries {
+        let uuid = match entry.uuid {
+            Some(uuid) => uuid,
+            None => continue,
+        };
+        let node_id = entry_node_id(&uuid);
+        nodes.push(GraphNode::new(
+            node_id.to_owned(),
+            GraphNodeKind::Entry,
+            entry.title.to_owned(),
+            entry.color.clone(),
+        ));
+
+        if let Some(board_uuid) = entry.board.filter(|board| board_uuids.contains(board)) {
+            edges.push(GraphEdge::new(
+                node_id.to_owned(),
+                board_node_id(&board_uuid),
+                GraphEdgeKind::Board,
+            ));
+        }
+
+        for target in find_references(entry, &entry_uuids, &uuids_by_title) {
+            edges.push(GraphEdge::new(
+                node_id.to_owned(),
+                entry_node_id(&target),
+                GraphEdgeKind::Reference,
+            ));
+        }
+    }
+
+    let mut label_ids: HashSet<String> = HashSet::new();
+    let mut labelled: HashSet<(uuid::Uuid, String)> = HashSet::new();
+    let embedded_labels = entries.iter().flat_map(|entry| {
+        entry
+            .labels
+            .iter()
+            .flatten()
+            .filter_map(move |label| entry.uuid.map(|uuid| (uuid, label)))
+    });
+    let associated_labels = entries_labels.iter().map(|(uuid, label)| (*uuid, label));
+
+    for (uuid, label) in embedded_labels.chain(associated_labels) {
+        if !entry_uuids.contains(&uuid) || !labelled.insert((uuid, label.id.to_owned())) {
+            continue;
+        }
+        if label_ids.insert(label.id.to_owned()) {
+            nodes.push(GraphNode::new(
+                label_node_id(&label.id),
+                GraphNodeKind::Label,
+                label.name.to_owned(),
+                None,
+            ));
+        }
+        edges.push(GraphEdge::new(
+            entry_node_id(&uuid),
+            label_node_id(&label.id),
+            GraphEdgeKind::Label,
+        ));
+    }
+
+    Graph::new(nodes, edges)
+}
+
+//Keep the nodes at most "depth" edges away from the center, whatever the edge direction.
+pub fn neighbourhood(graph: Graph, center_id: &str, depth: usize) -> Graph {
+    let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+    for edge in &graph.edges {
+        adjacency
+            .entry(edge.source.as_str())
+            .or_default()
+            .push(edge.target.as_str());
+        adjacency
+            .entry(edge.target.as_str())
+            .or_default()
+            .push(edge.source.as_str());
+    }
+
+    let mut kept: HashSet<String> = HashSet::new();
+    let mut queue: VecDeque<(&str, usize)> = VecDeque::new();
+    if graph.nodes.iter().any(|node| node.id == center_id) {
+        kept.insert(center_id.to_string());
+        queue.push_back((center_id, 0));
+    }
+
+    while let Some((node_id, distance)) = queue.pop_front() {
+        if distance == depth {
+            continue;
+        }
+        for neighbour in adjacency.get(node_id).into_iter().flatten() {
+            if kept.insert(neighbour.to_string()) {
+                queue.push_back((neighbour, distance + 1));
+            }
+        }
+    }
+
+    let edges: Vec<GraphEdge> = graph
+        .edges
+        .iter()
+        .filter(|edge| kept.contains(&edge.source) && kept.contains(&edge.target))
+        .cloned()
+        .collect();
+    let nodes: Vec<GraphNode> = graph
+        .nodes
+        .into_iter()
+        .filter(|node| kept.contains(&node.id))
+        .collect();
+
+    Graph::new(nodes, edges)
+}
+
+//Entries referenced in the content, by UUID or by "[[Title]]" link.
+fn find_references(
+    entry: &Entry,
+    entry_uuids: &HashSet<uuid::Uuid>,
+    uuids_by_title: &HashMap<String, uuid::Uuid>,
+) -> Vec<uuid::Uuid> {
+    let content = match &entry.content {
+        Some(content) => content,
+        None => return Vec::new(),
+    };
+
+    let uuid_references = content
+        .split(|character: char| !(character.is_ascii_alphanumeric() || character == '-'))
+        .filter(|token| token.len() == UUID_LENGTH)
+        .filter_map(|token| uuid::Uuid::parse_str(token).ok())
+        .filter(|uuid| entry_uuids.contains(uuid));
+
+    let title_references = parse_link_titles(content)
+        .into_iter()
+        .filter_map(|title| uuids_by_title.get(&title_key(&title)).cloned());
+
+    let mut references: Vec<uuid::Uuid> = Vec::new();
+    for reference in uuid_references.chain(title_references) {
+        if Some(reference) != entry.uuid && !references.contains(&reference) {
+            references.push(reference);
+        }
+    }
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, content: &str) -> Entry {
+        Entry::new(
+            0,
+            Some(uuid::Uuid::new_v4()),
+            title.to_string(),
+            Some(content.to_string()),
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn node_titles(graph: &Graph) -> Vec<&str> {
+        let mut titles: Vec<&str> = graph.nodes.iter().map(|node| node.title.as_str()).collect();
+        titles.sort_unstable();
+        titles
+    }
+
+    #[test]
+    fn links_the_entries_by_title_and_uuid() {
+        let a = entry("A", "");
+        let b = entry("B", &format!("See [[a]] and {}", a.uuid.unwrap()));
+        let c = entry("C", "See [[B]], [[Missing]] and [[C]]");
+        let (a_id, b_id, c_id) = (
+            entry_node_id(&a.uuid.unwrap()),
+            entry_node_id(&b.uuid.unwrap()),
+            entry_node_id(&c.uuid.unwrap()),
+        );
+        let graph = build_graph(&[a, b, c], &[], &[]);
+
+        assert_eq!(
+            graph.edges,
+            vec![
+                GraphEdge::new(b_id.to_owned(), a_id, GraphEdgeKind::Reference),
+                GraphEdge::new(c_id, b_id, GraphEdgeKind::Reference),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_the_nodes_within_the_depth() {
+        //A chain A <- B <- C <- D <- E.
+        let a = entry("A", "");
+        let b = entry("B", "[[A]]");
+        let c = entry("C", "[[B]]");
+        let d = entry("D", "[[C]]");
+        let e = entry("E", "[[D]]");
+        let center = entry_node_id(&c.uuid.unwrap());
+        let entries = [a, b, c, d, e];
+
+        let graph = neighbourhood(build_graph(&entries, &[], &[]), &center, 0);
+        assert_eq!(node_titles(&graph), vec!["C"]);
+        assert!(graph.edges.is_empty());
+
+        let graph = neighbourhood(build_graph(&entries, &[], &[]), &center, 1);
+        assert_eq!(node_titles(&graph), vec!["B", "C", "D"]);
+        assert_eq!(graph.edges.len(), 2);
+
+        let graph = neighbourhood(build_graph(&entries, &[], &[]), &center, 2);
+        assert_eq!(node_titles(&graph), vec!["A", "B", "C", "D", "E"]);
+        assert_eq!(graph.edges.len(), 4);
+    }
+
+    #[test]
+    fn walks_the_cycles_once() {
+        //A cycle A -> B -> C -> A, and D out of it.
+        let a = entry("A", "[[B]]");
+        let b = entry("B", "[[C]]");
+        let c = entry("C", "[[A]]");
+        let d = entry("D", "");
+        let center = entry_node_id(&a.uuid.unwrap());
+
+        let graph = neighbourhood(build_graph(&[a, b, c, d], &[], &[]), &center, 10);
+        assert_eq!(node_titles(&graph), vec!["A", "B", "C"]);
+        assert_eq!(graph.edges.len(), 3);
+    }
+
+    #[test]
+    fn is_empty_around_an_unknown_node() {
+        let graph = neighbourhood(build_graph(&[entry("A", "")], &[], &[]), "entry:unknown", 2);
+        assert!(graph.nodes.is_empty());
+        assert!(graph.edges.is_empty());
+    }
+}
@@ -0,0 +1,98 @@
+pub mod enex;
+pub mod keep;
+pub mod trello;
+pub mod vault;
+
+use chrono::prelude::*;
+use serde_json::{json, Value};
+use std::io::{self, Read};
+
+//Key of Entry.data keeping the origin of an imported entry, for idempotent imports.
+pub const IMPORT_DATA_KEY: &str = "import";
+
+//Largest archive member read: a small zip can hold a file inflating to gigabytes.
+pub const MAX_ARCHIVE_MEMBER_SIZE: u64 = 32 * 1024 * 1024;
+
+//Entry read from an external archive, before being mapped on the user data.
+#[derive(Debug, Default)]
+pub struct ImportedEntry {
+    pub source_id: String,
+    pub uuid: Option<uuid::Uuid>,
+    pub title: String,
+    pub content: Option<String>,
+    pub data: Option<Value>,
+    pub color: Option<String>,
+    pub archived: bool,
+    pub board: Option<String>,
+    pub labels: Vec<String>,
+    pub created_on: Option<DateTime<Utc>>,
+    pub updated_on: Option<DateTime<Utc>>,
+    pub due_on: Option<DateTime<Utc>>,
+}
+
+//Everything read from an archive.
+#[derive(Debug)]
+pub struct ImportBatch {
+    pub source: String,
+    pub entries: Vec<ImportedEntry>,
+    pub warnings: Vec<String>,
+}
+
+impl ImportBatch {
+    pub fn new(source: &str) -> ImportBatch {
+        ImportBatch {
+            source: source.to_string(),
+            entries: Vec::new(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+//Add the import origin to the entry data.
+pub fn with_import_marker(data: Option<Value>, source: &str, source_id: &str) -> Value {
+    let marker = json!({ "source": source, "id": source_id });
+    match data {
+        Some(Value::Object(mut map)) => {
+            map.insert(IMPORT_DATA_KEY.to_string(), marker);
+            Value::Object(map)
+        }
+        Some(other) => json!({ IMPORT_DATA_KEY: marker, "value": other }),
+        None => json!({ IMPORT_DATA_KEY: marker }),
+    }
+}
+
+//Untitled notes are named after the first line of their content.
+pub fn title_or_first_line(title: &str, content: Option<&str>) -> String {
+    const MAX_TITLE_LENGTH: usize = 80;
+
+    if !title.trim().is_empty() {
+        return title.trim().to_string();
+    }
+
+    content
+        .and_then(|content| content.lines().find(|line| !line.trim().is_empty()))
+        .map(|line| line.trim().chars().take(MAX_TITLE_LENGTH).collect())
+        .unwrap_or_default()
+}
+
+//Read a whole archive member, refusing it when larger than MAX_ARCHIVE_MEMBER_SIZE.
+//The size declared by the archive is not trusted: reading stops past the limit.
+pub fn read_archive_member<R: Read>(member: R) -> io::Result<Vec<u8>> {
+    let mut content = Vec::new();
+    member
+        .take(MAX_ARCHIVE_MEMBER_SIZE + 1)
+        .read_to_end(&mut content)?;
+    if content.len() as u64 > MAX_ARCHIVE_MEMBER_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("larger than {} bytes, ignored", MAX_ARCHIVE_MEMBER_SIZE),
+        ));
+    }
+    Ok(content)
+}
+
+//Same as read_archive_member, for a text member.
+pub fn read_archive_text<R: Read>(member: R) -> io::Result<String> {
+    let content = read_archive_member(member)?;
+    String::from_utf8(content).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
@@ -0,0 +1,325 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use crate::business::import::{
+    read_archive_member, title_or_first_line, ImportBatch, ImportedEntry,
+};
+use chrono::prelude::*;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use serde_json::json;
+use std::io::Cursor;
+use std::path::Path;
+use zip::ZipArchive;
+
+pub const ENEX_SOURCE: &str = "evernote";
+
+//Exports do not name their notebook: it is named after the file.
+const DEFAULT_NOTEBOOK: &str = "Evernote";
+const ENEX_DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+const ZIP_SIGNATURE: &[u8] = b"PK\x03\x04";
+
+//Placeholders of the checkboxes, which the Markdown conversion would escape.
+const TODO_CHECKED: char = '\u{E000}';
+const TODO_UNCHECKED: char = '\u{E001}';
+
+#[derive(Debug, Default)]
+struct EnexNote {
+    title: String,
+    content: String,
+    created: Option<String>,
+    updated: Option<String>,
+    tags: Vec<String>,
+    source_url: Option<String>,
+    author: Option<String>,
+    reminder_time: Option<String>,
+    resources: usize,
+}
+
+//Read an Evernote export: one .enex notebook, or a zip of .enex notebooks.
+pub fn parse_enex_export(file_name: &str, export: &[u8]) -> EntryDomainResult<ImportBatch> {
+    let mut batch = ImportBatch::new(ENEX_SOURCE);
+
+    if !export.starts_with(ZIP_SIGNATURE) {
+        parse_notebook(&notebook_name(file_name), export, &mut batch)?;
+        return Ok(batch);
+    }
+
+    let mut zip = ZipArchive::new(Cursor::new(export))
+        .map_err(|e| MemorizDomainError::Import(e.to_string()))?;
+    for index in 0..zip.len() {
+        let mut file = zip
+            .by_index(index)
+            .map_err(|e| MemorizDomainError::Import(e.to_string()))?;
+        let name = file.name().to_string();
+        if file.is_dir() || !name.to_lowercase().ends_with(".enex") {
+            continue;
+        }
+
+        let notebook = match read_archive_member(&mut file) {
+            Ok(notebook) => notebook,
+            Err(e) => {
+                batch.warnings.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+        //A broken notebook does not prevent importing the other ones.
+        if let Err(e) = parse_notebook(&notebook_name(&name), &notebook, &mut batch) {
+            batch.warnings.push(format!("{}: {}", name, e));
+        }
+    }
+
+    Ok(batch)
+}
+
+fn notebook_name(file_name: &str) -> String {
+    Path::new(file_name)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().trim().to_string())
+        .filter(|stem| !stem.is_empty())
+        .unwrap_or_else(|| DEFAULT_NOTEBOOK.to_string())
+}
+
+fn parse_notebook(notebook: &str, xml: &[u8], batch: &mut ImportBatch) -> EntryDomainResult<()> {
+    let mut reader = Reader::from_reader(xml);
+    let mut buffer = Vec::new();
+    let mut note: Option<EnexNote> = None;
+    let mut text = String::new();
+
+    loop {
+        buffer.clear();
+        let event = reader.read_event(&mut buffer).map_err(|e| {
+            MemorizDomainError::Import(format!(
+                "Invalid ENEX at position {}: {}",
+                reader.buffer_position(),
+                e
+            ))
+        })?;
+
+        match event {
+            Event::Start(element) => {
+                text.clear();
+                match element.name() {
+                    b"note" => note = Some(EnexNote::default()),
+                    b"resource" => {
+                        if let Some(note) = note.as_mut() {
+                            note.resources += 1;
+                        }
+                    }
+                    _ => (),
+                }
+            }
+            //CDATA sections are given escaped, like text.
+            Event::Text(element) | Event::CData(element) => {
+                let value = element
+                    .unescape_and_decode(&reader)
+                    .map_err(|e| MemorizDomainError::Import(e.to_string()))?;
+                text.push_str(&value);
+            }
+            Event::End(element) => {
+                let value = std::mem::take(&mut text);
+                let current = match note.as_mut() {
+                    Some(current) => current,
+                    None => continue,
+                };
+                match element.name() {
+                    b"title" => current.title = value,
+                    b"content" => current.content = value,
+                    b"created" => current.created = Some(value),
+                    b"updated" => current.updated = Some(value),
+                    b"tag" => current.tags.push(value.trim().to_string()),
+                    b"source-url" => current.source_url = Some(value),
+                    b"author" => current.author = Some(value),
+                    b"reminder-time" => current.reminder_time = Some(value),
+                    b"note" => {
+                        let current = note.take().unwrap();
+                        batch
+                            .entries
+                            .push(map_note(current, notebook, &mut batch.warnings));
+                    }
+                    _ => (),
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+fn map_note(note: EnexNote, notebook: &str, warnings: &mut Vec<String>) -> ImportedEntry {
+    let content = enml_to_markdown(&note.content);
+    let title = title_or_first_line(&note.title, Some(&content));
+
+    if note.resources > 0 {
+        warnings.push(format!(
+            "{}: {} attachment(s) not imported",
+            title, note.resources
+        ));
+    }
+    if note.content.contains("<en-crypt") {
+        warnings.push(format!("{}: encrypted content not imported", title));
+    }
+
+    let mut data = json!({});
+    if let Some(source_url) = &note.source_url {
+        data["source_url"] = json!(source_url);
+    }
+    if let Some(author) = &note.author {
+        data["author"] = json!(author);
+    }
+
+    ImportedEntry {
+        //Notes have no identifier: re-importing a note is detected by its creation and title.
+        source_id: format!(
+            "{}/{}",
+            note.created.as_deref().unwrap_or_default(),
+            note.title.trim()
+        ),
+        title: title,
+        content: Some(content),
+        data: Some(data),
+        board: Some(notebook.to_string()),
+        labels: note
+            .tags
+            .into_iter()
+            .filter(|tag| !tag.is_empty())
+            .collect(),
+        created_on: note.created.as_deref().and_then(parse_enex_date),
+        updated_on: note.updated.as_deref().and_then(parse_enex_date),
+        due_on: note.reminder_time.as_deref().and_then(parse_enex_date),
+        ..Default::default()
+    }
+}
+
+fn parse_enex_date(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value.trim(), ENEX_DATE_FORMAT)
+        .ok()
+        .map(|date| DateTime::from_utc(date, Utc))
+}
+
+//Convert the ENML (XHTML with Evernote elements) of a note to Markdown.
+//Checkboxes become task list items, media and encrypted blocks are dropped.
+pub fn enml_to_markdown(enml: &str) -> String {
+    let mut html = String::new();
+    let mut position = 0;
+
+    while let Some(start) = enml[position..].find("<en-") {
+        let start = position + start;
+        let tag_end = match enml[start..].find('>') {
+            Some(end) => start + end + 1,
+            None => break,
+        };
+        html.push_str(&enml[position..start]);
+
+        let tag = &enml[start..tag_end];
+        position = tag_end;
+        if tag.starts_with("<en-todo") {
+            match tag.contains("checked=\"true\"") {
+                true => html.push(TODO_CHECKED),
+                false => html.push(TODO_UNCHECKED),
+            }
+        } else if tag.starts_with("<en-crypt") && !tag.ends_with("/>") {
+            //The encrypted text is the content of the element.
+            position = enml[tag_end..]
+                .find("</en-crypt>")
+                .map(|end| tag_end + end + "</en-crypt>".len())
+                .unwrap_or(enml.len());
+        } else if tag.starts_with("<en-note") {
+            html.push_str("<div>");
+        }
+    }
+    html.push_str(&enml[position..]);
+    let html = html.replace("</en-note>", "</div>");
+
+    let markdown = html2md::parse_html(&html);
+    let mut content = String::new();
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        let indentation = &line[..line.len() - trimmed.len()];
+        let line = match trimmed.chars().next() {
+            //A checkbox starting a line is a task item, elsewhere it stays a checkbox.
+            Some(TODO_CHECKED) | Some(TODO_UNCHECKED) => {
+                format!("{}- {}", indentation, trimmed)
+            }
+            _ => line.to_string(),
+        };
+        content.push_str(&line);
+        content.push('\n');
+    }
+
+    for (placeholder, checkbox) in &[(TODO_CHECKED, "[x]"), (TODO_UNCHECKED, "[ ]")] {
+        content = content
+            .replace(&format!("{} ", placeholder), &format!("{} ", checkbox))
+            .replace(*placeholder, &format!("{} ", checkbox));
+    }
+    content.trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOTE_ENML: &str = r#"<?xml version="1.0" encoding="UTF-8"?><!DOCTYPE en-note SYSTEM "http://xml.evernote.com/pub/enml2.dtd"><en-note><div><b>Trip</b> plan</div><div><en-todo checked="true"/>Tickets</div><div><en-todo checked="false"/>Hotel <en-todo/> maybe</div><div><en-media type="image/png" hash="abc"/></div><en-crypt cipher="AES">secret==</en-crypt><div>End</div></en-note>"#;
+
+    #[test]
+    fn converts_the_enml_to_markdown() {
+        assert_eq!(
+            enml_to_markdown(NOTE_ENML),
+            "**Trip** plan\n\n- [x] Tickets\n\n- [ ] Hotel [ ] maybe\n\nEnd"
+        );
+    }
+
+    #[test]
+    fn reads_the_notes_of_a_notebook() {
+        let export = format!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <en-export>
+              <note>
+                <title>Holidays</title>
+                <content><![CDATA[{}]]></content>
+                <created>20240105T083000Z</created>
+                <updated>20240106T090000Z</updated>
+                <tag>travel</tag>
+                <tag> </tag>
+                <note-attributes>
+                  <source-url>https://example.com/trip</source-url>
+                  <reminder-time>20240201T070000Z</reminder-time>
+                </note-attributes>
+                <resource><data encoding="base64">iVBORw0K</data></resource>
+              </note>
+            </en-export>"#,
+            NOTE_ENML
+        );
+        let batch = parse_enex_export("Travel.enex", export.as_bytes()).unwrap();
+
+        assert_eq!(batch.entries.len(), 1);
+        let entry = &batch.entries[0];
+        assert_eq!(entry.title, "Holidays");
+        assert_eq!(entry.source_id, "20240105T083000Z/Holidays");
+        assert_eq!(entry.board.as_deref(), Some("Travel"));
+        assert_eq!(entry.labels, vec!["travel"]);
+        assert!(entry.content.as_deref().unwrap().contains("- [x] Tickets"));
+        assert_eq!(
+            entry.created_on,
+            Some(Utc.ymd(2024, 1, 5).and_hms(8, 30, 0))
+        );
+        assert_eq!(entry.updated_on, Some(Utc.ymd(2024, 1, 6).and_hms(9, 0, 0)));
+        assert_eq!(entry.due_on, Some(Utc.ymd(2024, 2, 1).and_hms(7, 0, 0)));
+        assert_eq!(
+            entry.data,
+            Some(json!({ "source_url": "https://example.com/trip" }))
+        );
+        assert_eq!(
+            batch.warnings,
+            vec![
+                "Holidays: 1 attachment(s) not imported",
+                "Holidays: encrypted content not imported",
+            ]
+        );
+    }
+
+    #[test]
+    fn refuses_a_broken_notebook() {
+        assert!(parse_enex_export("Broken.enex", b"<en-export><note></en-export>").is_err());
+    }
+}
@@ -0,0 +1,220 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use crate::business::import::{read_archive_text, title_or_first_line, ImportBatch, ImportedEntry};
+use chrono::prelude::*;
+use serde_json::json;
+use std::io::Cursor;
+use std::path::Path;
+use zip::ZipArchive;
+
+pub const KEEP_SOURCE: &str = "google-keep";
+
+//Imported notes are gathered in a single board: Keep has no notebooks.
+pub const KEEP_BOARD_TITLE: &str = "Google Keep";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeepNote {
+    title: String,
+    text_content: Option<String>,
+    list_content: Option<Vec<KeepListItem>>,
+    color: Option<String>,
+    #[serde(default)]
+    is_trashed: bool,
+    #[serde(default)]
+    is_pinned: bool,
+    #[serde(default)]
+    is_archived: bool,
+    labels: Option<Vec<KeepLabel>>,
+    annotations: Option<Vec<KeepAnnotation>>,
+    attachments: Option<Vec<serde_json::Value>>,
+    created_timestamp_usec: Option<i64>,
+    user_edited_timestamp_usec: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeepListItem {
+    text: String,
+    #[serde(default)]
+    is_checked: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeepLabel {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct KeepAnnotation {
+    url: Option<String>,
+    title: Option<String>,
+}
+
+//Read the JSON notes of a Google Keep Takeout zip.
+pub fn parse_keep_archive(archive: &[u8]) -> EntryDomainResult<ImportBatch> {
+    let mut zip = ZipArchive::new(Cursor::new(archive))
+        .map_err(|e| MemorizDomainError::Import(e.to_string()))?;
+    let mut batch = ImportBatch::new(KEEP_SOURCE);
+
+    for index in 0..zip.len() {
+        let mut file = zip
+            .by_index(index)
+            .map_err(|e| MemorizDomainError::Import(e.to_string()))?;
+        if file.is_dir() || !file.name().to_lowercase().ends_with(".json") {
+            continue;
+        }
+
+        let name = file.name().to_string();
+        let json = match read_archive_text(&mut file) {
+            Ok(json) => json,
+            Err(e) => {
+                batch.warnings.push(format!("{}: {}", name, e));
+                continue;
+            }
+        };
+
+        //Takeout also contains JSON files which are not notes (Labels.json...).
+        let note: KeepNote = match serde_json::from_str(&json) {
+            Ok(note) => note,
+            Err(_) => {
+                batch
+                    .warnings
+                    .push(format!("{}: not a Keep note, ignored", name));
+                continue;
+            }
+        };
+
+        if note.is_trashed {
+            continue;
+        }
+
+        let source_id = Path::new(&name)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or(name.to_owned());
+        if !note.attachments.as_deref().unwrap_or_default().is_empty() {
+            batch
+                .warnings
+                .push(format!("{}: attachments are not imported", name));
+        }
+
+        batch.entries.push(map_note(note, source_id));
+    }
+
+    Ok(batch)
+}
+
+fn map_note(note: KeepNote, source_id: String) -> ImportedEntry {
+    let list_content = note.list_content.unwrap_or_default();
+    let title = title_or_first_line(
+        &note.title,
+        note.text_content
+            .as_deref()
+            .or_else(|| list_content.first().map(|item| item.text.as_str())),
+    );
+    let mut content = note.text_content.unwrap_or_default();
+
+    //Checklists become Markdown task lists.
+    for item in list_content {
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        let check = match item.is_checked {
+            true => "x",
+            false => " ",
+        };
+        content.push_str(&format!("- [{}] {}", check, item.text));
+    }
+
+    let links: Vec<serde_json::Value> = note
+        .annotations
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|KeepAnnotation { url, title }| {
+            url.map(|url| json!({ "url": url, "title": title }))
+        })
+        .collect();
+
+    let mut data = json!({ "pinned": note.is_pinned });
+    if !links.is_empty() {
+        data["links"] = json!(links);
+    }
+
+    ImportedEntry {
+        source_id: source_id,
+        title: title,
+        content: Some(content),
+        data: Some(data),
+        color: note
+            .color
+            .filter(|color| color != "DEFAULT")
+            .map(|color| color.to_lowercase()),
+        archived: note.is_archived,
+        board: Some(KEEP_BOARD_TITLE.to_string()),
+        labels: note
+            .labels
+            .unwrap_or_default()
+            .into_iter()
+            .map(|label| label.name)
+            .collect(),
+        created_on: note.created_timestamp_usec.and_then(from_timestamp_usec),
+        updated_on: note
+            .user_edited_timestamp_usec
+            .and_then(from_timestamp_usec),
+        ..Default::default()
+    }
+}
+
+//None for a timestamp out of the range of the dates: the entry is then dated by the import.
+fn from_timestamp_usec(timestamp: i64) -> Option<DateTime<Utc>> {
+    Utc.timestamp_opt(
+        timestamp.div_euclid(1_000_000),
+        (timestamp.rem_euclid(1_000_000) * 1_000) as u32,
+    )
+    .single()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note(json: &str) -> ImportedEntry {
+        map_note(serde_json::from_str(json).unwrap(), "note".to_string())
+    }
+
+    #[test]
+    fn maps_a_note_with_its_checklist() {
+        let entry = note(
+            r#"{"title": "", "textContent": "Groceries", "color": "RED", "isPinned": true,
+            "listContent": [{"text": "Milk", "isChecked": true}, {"text": "Eggs"}],
+            "labels": [{"name": "Home"}], "createdTimestampUsec": 1600000000123456}"#,
+        );
+
+        assert_eq!(entry.title, "Groceries");
+        assert_eq!(
+            entry.content.as_deref(),
+            Some("Groceries\n- [x] Milk\n- [ ] Eggs")
+        );
+        assert_eq!(entry.color.as_deref(), Some("red"));
+        assert_eq!(entry.labels, vec!["Home".to_string()]);
+        assert_eq!(
+            entry.created_on,
+            Some(Utc.timestamp(1_600_000_000, 123_456_000))
+        );
+    }
+
+    #[test]
+    fn ignores_the_timestamps_out_of_range() {
+        let entry = note(
+            r#"{"title": "Old", "createdTimestampUsec": -9223372036854775808,
+            "userEditedTimestampUsec": 9223372036854775807}"#,
+        );
+        assert_eq!(entry.created_on, None);
+        assert_eq!(entry.updated_on, None);
+
+        assert_eq!(
+            from_timestamp_usec(-1),
+            Some(Utc.timestamp(-1, 999_999_000))
+        );
+    }
+}
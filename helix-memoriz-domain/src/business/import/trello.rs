@@ -0,0 +1,347 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use crate::business::import::{title_or_first_line, ImportBatch, ImportedEntry};
+use chrono::prelude::*;
+use serde_json::json;
+use std::collections::HashMap;
+
+pub const TRELLO_SOURCE: &str = "trello";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloBoard {
+    name: String,
+    #[serde(default)]
+    lists: Vec<TrelloList>,
+    #[serde(default)]
+    cards: Vec<TrelloCard>,
+    #[serde(default)]
+    checklists: Vec<TrelloChecklist>,
+    #[serde(default)]
+    members: Vec<serde_json::Value>,
+    #[serde(default)]
+    custom_fields: Vec<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloList {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCard {
+    id: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    #[serde(default)]
+    closed: bool,
+    id_list: Option<String>,
+    #[serde(default)]
+    labels: Vec<TrelloLabel>,
+    due: Option<DateTime<Utc>>,
+    #[serde(default)]
+    due_complete: bool,
+    date_last_activity: Option<DateTime<Utc>>,
+    short_url: Option<String>,
+    cover: Option<TrelloCover>,
+    #[serde(default)]
+    attachments: Vec<serde_json::Value>,
+    #[serde(default)]
+    id_members: Vec<String>,
+    badges: Option<TrelloBadges>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloLabel {
+    #[serde(default)]
+    name: String,
+    color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloCover {
+    color: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloBadges {
+    #[serde(default)]
+    comments: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloChecklist {
+    id_card: String,
+    name: String,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    check_items: Vec<TrelloCheckItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TrelloCheckItem {
+    name: String,
+    state: String,
+    #[serde(default)]
+    pos: f64,
+}
+
+//Read the JSON export of a Trello board ("Menu > Print and export > Export as JSON").
+//The board becomes a Memoriz board, cards become entries. Lists have no equivalent:
+//the list of a card is kept in its data.
+pub fn parse_trello_export(export: &[u8]) -> EntryDomainResult<ImportBatch> {
+    let board: TrelloBoard = serde_json::from_slice(export)
+        .map_err(|e| MemorizDomainError::Import(format!("Not a Trello board export: {}", e)))?;
+    let mut batch = ImportBatch::new(TRELLO_SOURCE);
+
+    if !board.members.is_empty() {
+        batch.warnings.push(format!(
+            "{} member(s): card members are not imported",
+            board.members.len()
+        ));
+    }
+    if !board.custom_fields.is_empty() {
+        batch.warnings.push(format!(
+            "{} custom field(s): custom fields are not imported",
+            board.custom_fields.len()
+        ));
+    }
+
+    let lists: HashMap<&str, &TrelloList> = board
+        .lists
+        .iter()
+        .map(|list| (list.id.as_str(), list))
+        .collect();
+
+    let mut checklists: HashMap<&str, Vec<&TrelloChecklist>> = HashMap::new();
+    for checklist in &board.checklists {
+        checklists
+            .entry(checklist.id_card.as_str())
+            .or_default()
+            .push(checklist);
+    }
+
+    for card in &board.cards {
+        let list = card
+            .id_list
+            .as_deref()
+            .and_then(|id_list| lists.get(id_list).copied());
+        let card_checklists = checklists
+            .get(card.id.as_str())
+            .map(|card_checklists| card_checklists.as_slice())
+            .unwrap_or_default();
+
+        if !card.attachments.is_empty() {
+            batch.warnings.push(format!(
+                "{}: {} attachment(s) not imported",
+                card.name,
+                card.attachments.len()
+            ));
+        }
+        if let Some(badges) = &card.badges {
+            if badges.comments > 0 {
+                batch.warnings.push(format!(
+                    "{}: {} comment(s) not imported",
+                    card.name, badges.comments
+                ));
+            }
+        }
+
+        batch
+            .entries
+            .push(map_card(card, list, card_checklists, &board.name));
+    }
+
+    Ok(batch)
+}
+
+fn map_card(
+    card: &TrelloCard,
+    list: Option<&TrelloList>,
+    checklists: &[&TrelloChecklist],
+    board_name: &str,
+) -> ImportedEntry {
+    let mut content = card.desc.trim_end().to_string();
+
+    //Checklists become Markdown task lists, under their name.
+    let mut checklists = checklists.to_vec();
+    checklists.sort_by(|a, b| {
+        a.pos
+            .partial_cmp(&b.pos)
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    for checklist in checklists {
+        if !content.is_empty() {
+            content.push_str("\n\n");
+        }
+        content.push_str(&format!("### {}\n", checklist.name));
+
+        let mut items: Vec<&TrelloCheckItem> = checklist.check_items.iter().collect();
+        items.sort_by(|a, b| {
+            a.pos
+                .partial_cmp(&b.pos)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        for item in items {
+            let check = match item.state.as_str() {
+                "complete" => "x",
+                _ => " ",
+            };
+            content.push_str(&format!("\n- [{}] {}", check, item.name));
+        }
+    }
+
+    let mut data = json!({ "due_complete": card.due_complete });
+    if let Some(list) = list {
+        data["list"] = json!(list.name);
+    }
+    if let Some(short_url) = &card.short_url {
+        data["url"] = json!(short_url);
+    }
+    if !card.id_members.is_empty() {
+        data["members"] = json!(card.id_members);
+    }
+
+    //Unnamed Trello labels are only a color.
+    let mut labels: Vec<String> = Vec::new();
+    for label in &card.labels {
+        let name = match label.name.trim().is_empty() {
+            true => label.color.to_owned().unwrap_or_default(),
+            false => label.name.trim().to_string(),
+        };
+        if !name.is_empty() && !labels.contains(&name) {
+            labels.push(name);
+        }
+    }
+
+    ImportedEntry {
+        source_id: card.id.to_owned(),
+        title: title_or_first_line(&card.name, Some(&card.desc)),
+        content: Some(content),
+        data: Some(data),
+        color: card.cover.as_ref().and_then(|cover| cover.color.to_owned()),
+        archived: card.closed || list.map(|list| list.closed).unwrap_or(false),
+        board: Some(board_name.to_string()),
+        labels: labels,
+        created_on: created_on_from_id(&card.id),
+        updated_on: card.date_last_activity,
+        due_on: card.due,
+        ..Default::default()
+    }
+}
+
+//Trello ids start with the creation timestamp, in seconds, as 8 hexadecimal digits.
+fn created_on_from_id(id: &str) -> Option<DateTime<Utc>> {
+    let timestamp = i64::from_str_radix(id.get(..8)?, 16).ok()?;
+    Some(Utc.timestamp(timestamp, 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"{
+        "name": "Home",
+        "lists": [
+            {"id": "list-todo", "name": "To do"},
+            {"id": "list-old", "name": "Old", "closed": true}
+        ],
+        "cards": [
+            {
+                "id": "5f5e1000aaaaaaaaaaaaaaaa",
+                "name": "Paint the fence",
+                "desc": "White paint\n",
+                "idList": "list-todo",
+                "labels": [{"name": "Garden", "color": "green"}, {"name": "", "color": "red"}],
+                "due": "2024-05-01T10:00:00.000Z",
+                "dueComplete": true,
+                "shortUrl": "https://trello.com/c/abc",
+                "cover": {"color": "blue"},
+                "attachments": [{}],
+                "badges": {"comments": 2}
+            },
+            {"id": "5f5e1001bbbbbbbbbbbbbbbb", "name": "Old card", "idList": "list-old"}
+        ],
+        "checklists": [
+            {
+                "idCard": "5f5e1000aaaaaaaaaaaaaaaa",
+                "name": "Later",
+                "pos": 2,
+                "checkItems": [{"name": "Clean", "state": "incomplete"}]
+            },
+            {
+                "idCard": "5f5e1000aaaaaaaaaaaaaaaa",
+                "name": "Shopping",
+                "pos": 1,
+                "checkItems": [
+                    {"name": "Brush", "state": "incomplete", "pos": 2},
+                    {"name": "Paint", "state": "complete", "pos": 1}
+                ]
+            }
+        ],
+        "members": [{}]
+    }"#;
+
+    #[test]
+    fn maps_the_cards_with_their_list_and_checklists() {
+        let batch = parse_trello_export(EXPORT.as_bytes()).unwrap();
+        assert_eq!(batch.entries.len(), 2);
+
+        let card = &batch.entries[0];
+        assert_eq!(card.title, "Paint the fence");
+        assert_eq!(
+            card.content.as_deref(),
+            Some(
+                "White paint\n\n### Shopping\n\n- [x] Paint\n- [ ] Brush\n\n\
+                 ### Later\n\n- [ ] Clean"
+            )
+        );
+        assert_eq!(card.board.as_deref(), Some("Home"));
+        assert_eq!(card.labels, vec!["Garden", "red"]);
+        assert_eq!(card.color.as_deref(), Some("blue"));
+        assert!(!card.archived);
+        assert_eq!(card.due_on, Some(Utc.ymd(2024, 5, 1).and_hms(10, 0, 0)));
+        assert_eq!(card.created_on, Some(Utc.timestamp(0x5f5e_1000, 0)));
+        assert_eq!(
+            card.data,
+            Some(json!({
+                "due_complete": true,
+                "list": "To do",
+                "url": "https://trello.com/c/abc"
+            }))
+        );
+    }
+
+    #[test]
+    fn archives_the_cards_of_a_closed_list() {
+        let batch = parse_trello_export(EXPORT.as_bytes()).unwrap();
+        let card = &batch.entries[1];
+        assert!(card.archived);
+        assert_eq!(card.content.as_deref(), Some(""));
+        assert_eq!(card.data.as_ref().unwrap()["list"], json!("Old"));
+    }
+
+    #[test]
+    fn warns_about_what_is_not_imported() {
+        let batch = parse_trello_export(EXPORT.as_bytes()).unwrap();
+        assert_eq!(
+            batch.warnings,
+            vec![
+                "1 member(s): card members are not imported",
+                "Paint the fence: 1 attachment(s) not imported",
+                "Paint the fence: 2 comment(s) not imported",
+            ]
+        );
+    }
+
+    #[test]
+    fn refuses_another_json() {
+        assert!(parse_trello_export(b"[1, 2]").is_err());
+    }
+}
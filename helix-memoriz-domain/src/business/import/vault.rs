@@ -0,0 +1,308 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use crate::business::export::markdown::FRONT_MATTER_DELIMITER;
+use crate::business::import::{read_archive_text, title_or_first_line, ImportBatch, ImportedEntry};
+use chrono::prelude::*;
+use serde_json::{Map, Value};
+use std::io::Cursor;
+use zip::ZipArchive;
+
+pub const VAULT_SOURCE: &str = "markdown";
+
+const MARKDOWN_EXTENSIONS: [&str; 2] = [".md", ".markdown"];
+
+//Read the Markdown files of a zipped folder, such as an Obsidian vault or a Memoriz export.
+//Directories become boards, the YAML front matter gives the entry metadata.
+pub fn parse_vault_archive(archive: &[u8]) -> EntryDomainResult<ImportBatch> {
+    let mut zip = ZipArchive::new(Cursor::new(archive))
+        .map_err(|e| MemorizDomainError::Import(e.to_string()))?;
+    let mut batch = ImportBatch::new(VAULT_SOURCE);
+
+    for index in 0..zip.len() {
+        let mut file = zip
+            .by_index(index)
+            .map_err(|e| MemorizDomainError::Import(e.to_string()))?;
+        let path = file.name().replace('\\', "/");
+
+        //Hidden folders hold application settings (.obsidian, .trash, __MACOSX...).
+        if file.is_dir()
+            || path
+                .split('/')
+                .any(|part| part.starts_with('.') || part.starts_with("__"))
+        {
+            continue;
+        }
+        if !MARKDOWN_EXTENSIONS
+            .iter()
+            .any(|extension| path.to_lowercase().ends_with(extension))
+        {
+            batch
+                .warnings
+                .push(format!("{}: not a Markdown file, ignored", path));
+            continue;
+        }
+
+        let markdown = match read_archive_text(&mut file) {
+            Ok(markdown) => markdown,
+            Err(e) => {
+                batch.warnings.push(format!("{}: {}", path, e));
+                continue;
+            }
+        };
+
+        batch
+            .entries
+            .push(map_markdown_file(&path, &markdown, &mut batch.warnings));
+    }
+
+    Ok(batch)
+}
+
+fn map_markdown_file(path: &str, markdown: &str, warnings: &mut Vec<String>) -> ImportedEntry {
+    let (file_name, board) = match path.rfind('/') {
+        Some(position) => (&path[position + 1..], Some(path[..position].to_string())),
+        None => (path, None),
+    };
+    let file_stem = match file_name.rfind('.') {
+        Some(position) => &file_name[..position],
+        None => file_name,
+    };
+
+    let (front_matter, content) = split_front_matter(markdown);
+    let mut front_matter = match front_matter.map(serde_yaml::from_str::<Value>) {
+        Some(Ok(Value::Object(map))) => map,
+        Some(Ok(Value::Null)) | None => Map::new(),
+        Some(_) => {
+            warnings.push(format!("{}: invalid front matter, ignored", path));
+            Map::new()
+        }
+    };
+
+    //Links are kept verbatim, they are resolved again when the entry is saved.
+    let content = content.trim_start_matches(&['\r', '\n'][..]).trim_end();
+    let title = take_string(&mut front_matter, &["title"])
+        .unwrap_or_else(|| title_or_first_line(file_stem, Some(content)));
+
+    let uuid = match take_string(&mut front_matter, &["uuid"]) {
+        Some(uuid) => match uuid::Uuid::parse_str(&uuid) {
+            Ok(uuid) => Some(uuid),
+            Err(_) => {
+                warnings.push(format!("{}: invalid uuid {}, ignored", path, uuid));
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut entry = ImportedEntry {
+        source_id: path.to_string(),
+        uuid: uuid,
+        title: title,
+        content: Some(content.to_string()),
+        color: take_string(&mut front_matter, &["color"]),
+        archived: take(&mut front_matter, &["archived"])
+            .and_then(|archived| archived.as_bool())
+            .unwrap_or(false),
+        board: board,
+        labels: take_tags(&mut front_matter, &["labels", "tags"]),
+        created_on: take_date(&mut front_matter, &["created_on", "created"]),
+        updated_on: take_date(&mut front_matter, &["updated_on", "updated", "modified"]),
+        due_on: take_date(&mut front_matter, &["due_on", "due"]),
+        ..Default::default()
+    };
+
+    //Remaining properties (aliases, custom fields...) are kept in the entry data.
+    let mut data = match take(&mut front_matter, &["data"]) {
+        Some(Value::Object(data)) => data,
+        _ => Map::new(),
+    };
+    data.append(&mut front_matter);
+    if !data.is_empty() {
+        entry.data = Some(Value::Object(data));
+    }
+
+    entry
+}
+
+//Split "---\nyaml\n---\ncontent" into the YAML and the content.
+fn split_front_matter(markdown: &str) -> (Option<&str>, &str) {
+    let markdown = markdown.trim_start_matches('\u{feff}');
+    let first_line_end = markdown.find('\n').unwrap_or(markdown.len());
+    if markdown[..first_line_end].trim_end() != FRONT_MATTER_DELIMITER {
+        return (None, markdown);
+    }
+
+    let yaml_start = (first_line_end + 1).min(markdown.len());
+    let mut line_start = yaml_start;
+    while line_start < markdown.len() {
+        let line_end = markdown[line_start..]
+            .find('\n')
+            .map(|end| line_start + end)
+            .unwrap_or(markdown.len());
+        let line = markdown[line_start..line_end].trim_end();
+        if line == FRONT_MATTER_DELIMITER || line == "..." {
+            let content_start = (line_end + 1).min(markdown.len());
+            return (
+                Some(&markdown[yaml_start..line_start]),
+                &markdown[content_start..],
+            );
+        }
+        line_start = line_end + 1;
+    }
+
+    //Unclosed front matter: the file is only content.
+    (None, markdown)
+}
+
+//Remove the first of the given keys found in the front matter.
+fn take(front_matter: &mut Map<String, Value>, keys: &[&str]) -> Option<Value> {
+    let mut found: Option<Value> = None;
+    for key in keys {
+        if let Some(value) = front_matter.remove(*key) {
+            found = found.or(Some(value));
+        }
+    }
+    found
+}
+
+fn take_string(front_matter: &mut Map<String, Value>, keys: &[&str]) -> Option<String> {
+    match take(front_matter, keys)? {
+        Value::String(value) if !value.trim().is_empty() => Some(value.trim().to_string()),
+        Value::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+//Tags are either a list or a string separated by commas or spaces, with an optional "#".
+fn take_tags(front_matter: &mut Map<String, Value>, keys: &[&str]) -> Vec<String> {
+    let values: Vec<String> = match take(front_matter, keys) {
+        Some(Value::Array(values)) => values
+            .into_iter()
+            .filter_map(|value| match value {
+                Value::String(value) => Some(value),
+                Value::Number(value) => Some(value.to_string()),
+                _ => None,
+            })
+            .collect(),
+        Some(Value::String(value)) => value
+            .split(|character: char| character == ',' || character.is_whitespace())
+            .map(|tag| tag.to_string())
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let mut tags: Vec<String> = Vec::new();
+    for value in values {
+        let tag = value.trim().trim_start_matches('#').to_string();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags
+}
+
+//RFC 3339 date times, or plain dates taken at midnight UTC.
+fn take_date(front_matter: &mut Map<String, Value>, keys: &[&str]) -> Option<DateTime<Utc>> {
+    let value = take_string(front_matter, keys)?;
+    if let Ok(date) = DateTime::parse_from_rfc3339(&value) {
+        return Some(date.with_timezone(&Utc));
+    }
+    if let Ok(date) = NaiveDateTime::parse_from_str(&value, "%Y-%m-%d %H:%M") {
+        return Some(DateTime::from_utc(date, Utc));
+    }
+    NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+        .ok()
+        .map(|date| DateTime::from_utc(date.and_hms(0, 0, 0), Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::business::export::zip_stream::ZipStream;
+
+    fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipStream::new();
+        let mut archive: Vec<u8> = Vec::new();
+        for (path, content) in files {
+            archive.extend(zip.add_file(path, content.as_bytes(), Utc::now()).unwrap());
+        }
+        archive.extend(zip.finish().unwrap());
+        archive
+    }
+
+    #[test]
+    fn splits_the_front_matter() {
+        assert_eq!(
+            split_front_matter("---\ntitle: Note\n---\nBody\n"),
+            (Some("title: Note\n"), "Body\n")
+        );
+        assert_eq!(
+            split_front_matter("\u{feff}---\r\ntitle: Note\r\n...\r\nBody"),
+            (Some("title: Note\r\n"), "Body")
+        );
+        assert_eq!(
+            split_front_matter("---\ntitle: Note\nBody"),
+            (None, "---\ntitle: Note\nBody")
+        );
+        assert_eq!(split_front_matter("Body\n---\n"), (None, "Body\n---\n"));
+    }
+
+    #[test]
+    fn maps_the_front_matter_and_tags() {
+        let mut warnings: Vec<String> = Vec::new();
+        let entry = map_markdown_file(
+            "Projects/Garden.md",
+            "---\ntitle: Vegetable garden\ntags: [garden, \"#home\", garden]\n\
+             due: 2024-04-01\naliases: [Potager]\n---\n\nSee [[Seeds]].\n",
+            &mut warnings,
+        );
+
+        assert!(warnings.is_empty());
+        assert_eq!(entry.title, "Vegetable garden");
+        assert_eq!(entry.content.as_deref(), Some("See [[Seeds]]."));
+        assert_eq!(entry.board.as_deref(), Some("Projects"));
+        assert_eq!(entry.labels, vec!["garden", "home"]);
+        assert_eq!(entry.due_on, Some(Utc.ymd(2024, 4, 1).and_hms(0, 0, 0)));
+        assert_eq!(
+            entry.data,
+            Some(serde_json::json!({ "aliases": ["Potager"] }))
+        );
+    }
+
+    #[test]
+    fn splits_the_tags_of_a_string() {
+        let mut warnings: Vec<String> = Vec::new();
+        let entry = map_markdown_file(
+            "Note.md",
+            "---\ntags: \"#one, two three\"\nuuid: nope\n---\nBody",
+            &mut warnings,
+        );
+
+        assert_eq!(entry.title, "Note");
+        assert_eq!(entry.labels, vec!["one", "two", "three"]);
+        assert_eq!(entry.uuid, None);
+        assert_eq!(warnings, vec!["Note.md: invalid uuid nope, ignored"]);
+    }
+
+    #[test]
+    fn reads_the_markdown_files_of_a_vault() {
+        let batch = parse_vault_archive(&archive(&[
+            (".obsidian/app.json", "{}"),
+            ("Inbox.md", "# First line\nBody"),
+            ("Work/Plan.markdown", "---\ntitle: Plan\n---\nSteps"),
+            ("Work/logo.png", "png"),
+        ]))
+        .unwrap();
+
+        let titles: Vec<&str> = batch
+            .entries
+            .iter()
+            .map(|entry| entry.title.as_str())
+            .collect();
+        assert_eq!(titles, vec!["Inbox", "Plan"]);
+        assert_eq!(batch.entries[1].board.as_deref(), Some("Work"));
+        assert_eq!(
+            batch.warnings,
+            vec!["Work/logo.png: not a Markdown file, ignored"]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+const LINK_START: &str = "[[";
+const LINK_END: &str = "]]";
+const CODE_MARK: char = '`';
+
+//Titles referenced by "[[Title]]", "[[Title|Alias]]" or "[[Title#Heading]]" links.
+//Titles are compared without case, each one is only returned once.
+pub fn parse_link_titles(content: &str) -> Vec<String> {
+    let mut titles: Vec<String> = Vec::new();
+
+    for (start, end) in find_links(content) {
+        let (title, _) = split_link(&content[start..end]);
+        let title = title.trim();
+        if !title.is_empty() && !titles.iter().any(|known| same_title(known, title)) {
+            titles.push(title.to_string());
+        }
+    }
+
+    titles
+}
+
+//Point the links targeting the old title to the new one, keeping aliases and headings.
+pub fn rewrite_links(content: &str, old_title: &str, new_title: &str) -> String {
+    let mut rewritten = String::new();
+    let mut last_position = 0;
+
+    for (start, end) in find_links(content) {
+        let (title, suffix) = split_link(&content[start..end]);
+        if same_title(title, old_title) {
+            rewritten.push_str(&content[last_position..start]);
+            rewritten.push_str(new_title);
+            rewritten.push_str(suffix);
+            last_position = end;
+        }
+    }
+
+    rewritten.push_str(&content[last_position..]);
+    rewritten
+}
+
+pub fn same_title(title: &str, other_title: &str) -> bool {
+    title_key(title) == title_key(other_title)
+}
+
+//Normalized form of a title used to match links.
+pub fn title_key(title: &str) -> String {
+    title.trim().to_lowercase()
+}
+
+//Byte ranges of the text between "[[" and "]]", on a single line, out of code.
+fn find_links(content: &str) -> Vec<(usize, usize)> {
+    let code_ranges = find_code(content);
+    let mut links: Vec<(usize, usize)> = Vec::new();
+    let mut position = 0;
+
+    while let Some(start) = content[position..].find(LINK_START) {
+        let link_start = position + start;
+        if let Some((_, code_end)) = code_ranges
+            .iter()
+            .find(|(code_start, code_end)| *code_start <= link_start && link_start < *code_end)
+        {
+            position = *code_end;
+            continue;
+        }
+
+        let inner_start = link_start + LINK_START.len();
+        let inner_end = match content[inner_start..].find(LINK_END) {
+            Some(end) => inner_start + end,
+            None => break,
+        };
+
+        let inner = &content[inner_start..inner_end];
+        if inner.contains('\n') || inner.contains(LINK_START) {
+            position = inner_start;
+        } else {
+            links.push((inner_start, inner_end));
+            position = inner_end + LINK_END.len();
+        }
+    }
+
+    links
+}
+
+//Byte ranges of the code spans and fenced blocks,
+//from a run of backticks to the next run of the same length.
+fn find_code(content: &str) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    let mut position = 0;
+
+    while let Some(start) = content[position..].find(CODE_MARK) {
+        let code_start = position + start;
+        let mark_length = backtick_run(&content[code_start..]);
+        let mut search = code_start + mark_length;
+        position = search;
+
+        while let Some(next) = content[search..].find(CODE_MARK) {
+            let close_start = search + next;
+            let close_length = backtick_run(&content[close_start..]);
+            if close_length == mark_length {
+                position = close_start + close_length;
+                ranges.push((code_start, position));
+                break;
+            }
+            search = close_start + close_length;
+        }
+    }
+
+    ranges
+}
+
+fn backtick_run(text: &str) -> usize {
+    text.len() - text.trim_start_matches(CODE_MARK).len()
+}
+
+//Split "Title#Heading|Alias" into ("Title", "#Heading|Alias").
+fn split_link(inner: &str) -> (&str, &str) {
+    let title_end = inner.find(&['|', '#'][..]).unwrap_or(inner.len());
+    (&inner[..title_end], &inner[title_end..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_titles_with_aliases_and_headings() {
+        assert_eq!(
+            parse_link_titles("See [[Rust]], [[rust|the language]] and [[Cargo#Features]]."),
+            vec!["Rust", "Cargo"]
+        );
+    }
+
+    #[test]
+    fn ignores_the_empty_and_broken_links() {
+        assert!(parse_link_titles("[[]] [[ |alias]] [[Split\nline]] [[Open").is_empty());
+        assert_eq!(parse_link_titles("[[Outer [[Inner]]"), vec!["Inner"]);
+    }
+
+    #[test]
+    fn ignores_the_links_in_code() {
+        let content = "Write `[[Title]]` or ``a ` [[Tick]]``.\n```\n[[Fenced]]\n```\n[[Real]]";
+        assert_eq!(parse_link_titles(content), vec!["Real"]);
+        assert_eq!(parse_link_titles("An open ` then [[Real]]"), vec!["Real"]);
+    }
+
+    #[test]
+    fn rewrites_the_links_keeping_aliases_and_headings() {
+        assert_eq!(
+            rewrite_links("[[old]], [[Old|alias]], [[OLD#Part]]", "Old", "New"),
+            "[[New]], [[New|alias]], [[New#Part]]"
+        );
+    }
+
+    #[test]
+    fn rewrites_only_the_whole_titles() {
+        let content = "[[Old note]], [[Older]], [[Gold]], Old and `[[Old]]`, then [[Old]]";
+        assert_eq!(
+            rewrite_links(content, "Old", "New"),
+            "[[Old note]], [[Older]], [[Gold]], Old and `[[Old]]`, then [[New]]"
+        );
+    }
+}
@@ -0,0 +1,144 @@
+use crate::core::entry::Entry;
+use crate::core::share::PublicItem;
+use ammonia::Builder;
+use pulldown_cmark::{html, Options, Parser};
+use std::borrow::Cow;
+
+//Render Markdown as HTML, sanitized against script injection.
+//Task lists ("- [ ]" and "- [x]") become disabled checkboxes.
+pub fn render_html(markdown: &str) -> String {
+    let mut options = Options::empty();
+    options.insert(Options::ENABLE_TASKLISTS);
+    options.insert(Options::ENABLE_TABLES);
+    options.insert(Options::ENABLE_STRIKETHROUGH);
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, Parser::new_ext(markdown, options));
+
+    sanitize_html(&unsafe_html)
+}
+
+pub fn sanitize_html(unsafe_html: &str) -> String {
+    Builder::default()
+        .add_tags(&["input"])
+        .add_tag_attributes("input", &["type", "checked", "disabled"])
+        .attribute_filter(|element, attribute, value| match (element, attribute) {
+            ("input", "type") if value != "checkbox" => None,
+            _ => Some(Cow::from(value)),
+        })
+        .clean(unsafe_html)
+        .to_string()
+}
+
+//Fill the rendered HTML of the entry content.
+pub fn render_entry_html(entry: &mut Entry) {
+    entry.content_html = entry.content.as_ref().map(|content| render_html(content));
+}
+
+//Standalone page of an item served by a public link.
+pub fn render_public_html(item: &PublicItem) -> String {
+    let (title, body) = match item {
+        PublicItem::Entry { entry } => (&entry.title, render_public_entry(entry, "h1")),
+        PublicItem::Board { board, entries } => {
+            let mut body = format!("<h1>{}</h1>\n", ammonia::clean_text(&board.title));
+            for entry in entries {
+                body.push_str(&render_public_entry(entry, "h2"));
+            }
+            (&board.title, body)
+        }
+    };
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+        ammonia::clean_text(title),
+        body
+    )
+}
+
+fn render_public_entry(entry: &Entry, heading: &str) -> String {
+    format!(
+        "<article>\n<{heading}>{}</{heading}>\n{}</article>\n",
+        ammonia::clean_text(&entry.title),
+        entry
+            .content
+            .as_deref()
+            .map(render_html)
+            .unwrap_or_default(),
+        heading = heading
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(title: &str, content: &str) -> Entry {
+        Entry::new(
+            0,
+            None,
+            title.to_string(),
+            Some(content.to_string()),
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn strips_the_scripts_and_frames() {
+        let html = sanitize_html(
+            "<p>Hi<script>alert(1)</script></p><iframe src=\"https://example.com\"></iframe>",
+        );
+        assert_eq!(html, "<p>Hi</p>");
+    }
+
+    #[test]
+    fn strips_the_event_handlers_and_javascript_links() {
+        let html = sanitize_html(
+            "<img src=\"cat.png\" onerror=\"alert(1)\"><a href=\"javascript:alert(1)\">link</a>",
+        );
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("src=\"cat.png\""));
+        assert!(html.contains(">link</a>"));
+    }
+
+    #[test]
+    fn keeps_the_task_list_checkboxes() {
+        let html = render_html("- [ ] todo\n- [x] done\n");
+        assert!(html.contains("<input disabled=\"\" type=\"checkbox\">\ntodo"));
+        assert!(html.contains("<input disabled=\"\" type=\"checkbox\" checked=\"\">\ndone"));
+    }
+
+    #[test]
+    fn strips_the_other_input_attributes() {
+        let html = sanitize_html(
+            "<input type=\"text\" name=\"password\" value=\"secret\" onfocus=\"alert(1)\">",
+        );
+        assert_eq!(html, "<input>");
+    }
+
+    #[test]
+    fn escapes_the_titles_of_a_public_page() {
+        let item = PublicItem::Entry {
+            entry: entry(
+                "<script>alert(1)</script>",
+                "Hello <script>alert(1)</script>",
+            ),
+        };
+        let page = render_public_html(&item);
+        assert!(!page.contains("<script>"));
+        assert!(page.contains("<title>&lt;script&gt;alert(1)&lt;&#47;script&gt;</title>"));
+        assert!(page.contains("<p>Hello </p>"));
+    }
+}
@@ -0,0 +1,225 @@
+use crate::business::clip::{first_text, meta_content, truncate};
+use crate::core::preview::LinkPreview;
+use crate::fetcher::traits::LinkFetcherTrait;
+use ammonia::Url;
+use chrono::prelude::*;
+use scraper::Html;
+
+//Previews of an entry: the first URLs of its content.
+pub const MAX_PREVIEWS_PER_ENTRY: usize = 10;
+
+const URL_STARTS: &[&str] = &["https://", "http://"];
+const TITLE_LENGTH: usize = 200;
+const DESCRIPTION_LENGTH: usize = 300;
+
+//Web URLs of a content, in their normalized form, each one only once.
+//Punctuation ending a sentence, or closing a Markdown link, is not part of the URL.
+pub fn find_urls(content: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+
+    for word in content.split(|c: char| c.is_whitespace() || "<>\"'`".contains(c)) {
+        let start = match URL_STARTS
+            .iter()
+            .filter_map(|url_start| word.find(url_start))
+            .min()
+        {
+            Some(start) => start,
+            None => continue,
+        };
+        //"word(https://" is not a link, "(https://" and "[link](https://" are.
+        if let Some(previous) = word[..start].chars().last() {
+            if previous.is_alphanumeric() {
+                continue;
+            }
+        }
+
+        let url = trim_url(&word[start..]);
+        if let Ok(url) = Url::parse(url) {
+            let url = url.to_string();
+            if url.len() <= 2048 && !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+        if urls.len() == MAX_PREVIEWS_PER_ENTRY {
+            break;
+        }
+    }
+
+    urls
+}
+
+fn trim_url(url: &str) -> &str {
+    let mut url = url;
+    loop {
+        let trimmed = url.trim_end_matches(|c| ".,;:!?*_".contains(c));
+        let trimmed = match trimmed.chars().last() {
+            Some(')') if trimmed.matches('(').count() < trimmed.matches(')').count() => {
+                &trimmed[..trimmed.len() - 1]
+            }
+            Some(']') if trimmed.matches('[').count() < trimmed.matches(']').count() => {
+                &trimmed[..trimmed.len() - 1]
+            }
+            _ => trimmed,
+        };
+        if trimmed == url {
+            return url;
+        }
+        url = trimmed;
+    }
+}
+
+//Preview of a fetched page, from its OpenGraph metadata, or its title and description.
+pub fn parse_link_preview(url: &str, html: &str, fetched_on: DateTime<Utc>) -> LinkPreview {
+    let document = Html::parse_document(html);
+
+    let title = meta_content(&document, "og:title")
+        .or_else(|| meta_content(&document, "twitter:title"))
+        .or_else(|| first_text(&document, "title"))
+        .map(|title| truncate(&title, TITLE_LENGTH));
+    let description = meta_content(&document, "og:description")
+        .or_else(|| meta_content(&document, "twitter:description"))
+        .or_else(|| meta_content(&document, "description"))
+        .map(|description| truncate(&description, DESCRIPTION_LENGTH));
+    let site_name =
+        meta_content(&document, "og:site_name").map(|site_name| truncate(&site_name, TITLE_LENGTH));
+
+    //Images given by a relative URL are resolved against the page, only web ones are kept.
+    let image = meta_content(&document, "og:image")
+        .or_else(|| meta_content(&document, "twitter:image"))
+        .and_then(|image| Url::parse(url).ok()?.join(&image).ok())
+        .filter(|image| image.scheme() == "https" || image.scheme() == "http")
+        .map(|image| image.to_string());
+
+    LinkPreview::new(
+        url.to_string(),
+        title,
+        description,
+        image,
+        site_name,
+        Some(fetched_on),
+    )
+}
+
+//Previews of the pages, fetched one after the other. A page that cannot be fetched
+//gets a preview without metadata: it is not fetched again.
+pub async fn fetch_link_previews(
+    fetcher: &dyn LinkFetcherTrait,
+    urls: Vec<String>,
+) -> Vec<LinkPreview> {
+    let mut previews = Vec::new();
+    for url in urls {
+        let preview = match fetcher.fetch_page(&url).await {
+            Ok(html) => parse_link_preview(&url, &html, Utc::now()),
+            Err(e) => {
+                println!("LINK PREVIEW {} not fetched: {}", url, e);
+                LinkPreview::new(url, None, None, None, None, Some(Utc::now()))
+            }
+        };
+        previews.push(preview);
+    }
+    previews
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::static_link_fetcher::StaticLinkFetcher;
+    use std::collections::HashMap;
+
+    const PAGE: &str = r#"<html><head>
+        <title>Page title</title>
+        <meta property="og:title" content="Open Graph title">
+        <meta name="description" content="Page description">
+        <meta property="og:image" content="/images/cover.png">
+        <meta property="og:site_name" content="Example">
+        </head><body></body></html>"#;
+
+    #[test]
+    fn finds_each_url_once() {
+        let urls = find_urls(
+            "See https://example.com/a, then [link](https://example.com/b) \
+             and again https://example.com/a.",
+        );
+        assert_eq!(urls, vec!["https://example.com/a", "https://example.com/b"]);
+    }
+
+    #[test]
+    fn ignores_urls_glued_to_a_word() {
+        assert!(find_urls("nothttps://example.com").is_empty());
+        assert!(find_urls("ftp://example.com").is_empty());
+    }
+
+    #[test]
+    fn keeps_the_urls_of_the_first_previews() {
+        let content: Vec<String> = (0..15)
+            .map(|index| format!("https://example.com/{}", index))
+            .collect();
+        assert_eq!(find_urls(&content.join(" ")).len(), MAX_PREVIEWS_PER_ENTRY);
+    }
+
+    #[test]
+    fn trims_the_punctuation_ending_a_url() {
+        assert_eq!(
+            trim_url("https://example.com/page."),
+            "https://example.com/page"
+        );
+        assert_eq!(
+            trim_url("https://example.com/page)."),
+            "https://example.com/page"
+        );
+        assert_eq!(
+            trim_url("https://en.wikipedia.org/wiki/Rust_(language)"),
+            "https://en.wikipedia.org/wiki/Rust_(language)"
+        );
+        assert_eq!(trim_url("https://example.com/**"), "https://example.com/");
+    }
+
+    #[test]
+    fn parses_the_open_graph_metadata() {
+        let preview = parse_link_preview("https://example.com/page", PAGE, Utc::now());
+
+        assert_eq!(preview.title.as_deref(), Some("Open Graph title"));
+        assert_eq!(preview.description.as_deref(), Some("Page description"));
+        assert_eq!(preview.site_name.as_deref(), Some("Example"));
+        assert_eq!(
+            preview.image.as_deref(),
+            Some("https://example.com/images/cover.png")
+        );
+    }
+
+    #[test]
+    fn falls_back_on_the_page_title() {
+        let preview = parse_link_preview(
+            "https://example.com/page",
+            "<html><head><title> Only a title </title></head></html>",
+            Utc::now(),
+        );
+
+        assert_eq!(preview.title.as_deref(), Some("Only a title"));
+        assert_eq!(preview.description, None);
+        assert_eq!(preview.image, None);
+    }
+
+    #[tokio::test]
+    async fn fetches_the_previews_of_the_known_pages() {
+        let mut pages = HashMap::new();
+        pages.insert("https://example.com/page".to_string(), PAGE.to_string());
+        let fetcher = StaticLinkFetcher::new(pages);
+
+        let previews = fetch_link_previews(
+            &fetcher,
+            vec![
+                "https://example.com/page".to_string(),
+                "https://example.com/missing".to_string(),
+            ],
+        )
+        .await;
+
+        assert_eq!(previews.len(), 2);
+        assert_eq!(previews[0].title.as_deref(), Some("Open Graph title"));
+        //A missing page is saved without metadata, so it is not fetched again.
+        assert_eq!(previews[1].url, "https://example.com/missing");
+        assert!(previews[1].is_empty());
+        assert!(previews[1].fetched_on.is_some());
+    }
+}
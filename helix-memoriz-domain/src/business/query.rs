@@ -0,0 +1,291 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use crate::business::links::title_key;
+use crate::core::entry::Entry;
+use crate::core::label::Label;
+use crate::core::search::{DateRange, EntryQuery};
+use chrono::prelude::*;
+
+const QUERY_DATE_FORMAT: &str = "%Y-%m-%d";
+const RANGE_SEPARATOR: &str = "..";
+
+//Only these names make a filter of "name:value", other words with a colon are text.
+const FILTERS: &[&str] = &[
+    "label", "board", "color", "archived", "created", "updated", "due", "has",
+];
+
+//Parse a query made of words, "quoted phrases" and filters:
+//  label:work board:"Side projects" color:red archived:false has:attachment
+//  created:2021-01-01..2021-01-31 updated:>=2021-03-01 due:<2021-04-01 due:2021-04-01
+pub fn parse_query(query: &str) -> EntryDomainResult<EntryQuery> {
+    let mut parsed = EntryQuery::default();
+
+    for (filter, value) in tokenize(query)? {
+        let filter = match filter {
+            Some(filter) => filter,
+            None => {
+                if !value.trim().is_empty() {
+                    parsed.terms.push(value.trim().to_lowercase());
+                }
+                continue;
+            }
+        };
+        if value.trim().is_empty() {
+            return Err(query_error(format!("Missing value of \"{}:\"", filter)));
+        }
+
+        let value = value.trim().to_string();
+        match filter.as_str() {
+            "label" => parsed.labels.push(title_key(&value)),
+            "board" => parsed.boards.push(title_key(&value)),
+            "color" => parsed.colors.push(title_key(&value)),
+            "archived" => parsed.archived = Some(parse_bool(&filter, &value)?),
+            "created" => parsed.created = Some(parse_date_range(&value)?),
+            "updated" => parsed.updated = Some(parse_date_range(&value)?),
+            "due" => parsed.due = Some(parse_date_range(&value)?),
+            _ => match value.to_lowercase().as_str() {
+                "attachment" | "attachments" => parsed.has_attachment = Some(true),
+                _ => return Err(query_error(format!("Unknown \"has:{}\"", value))),
+            },
+        }
+    }
+
+    Ok(parsed)
+}
+
+//Filter name, when a known one, and value of each part of the query.
+//Quotes group words, a filter value included: label:"two words".
+fn tokenize(query: &str) -> EntryDomainResult<Vec<(Option<String>, String)>> {
+    let mut tokens: Vec<(Option<String>, String)> = Vec::new();
+    let mut chars = query.chars().peekable();
+
+    loop {
+        while chars.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+            chars.next();
+        }
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut filter: Option<String> = None;
+        let mut value = String::new();
+        let mut in_quotes = false;
+        let mut quoted = false;
+        while let Some(&c) = chars.peek() {
+            if c.is_whitespace() && !in_quotes {
+                break;
+            }
+            chars.next();
+            match c {
+                '"' => {
+                    in_quotes = !in_quotes;
+                    quoted = true;
+                }
+                ':' if filter.is_none()
+                    && !quoted
+                    && FILTERS.contains(&value.to_lowercase().as_str()) =>
+                {
+                    filter = Some(value.to_lowercase());
+                    value.clear();
+                }
+                _ => value.push(c),
+            }
+        }
+
+        if in_quotes {
+            return Err(query_error("Unterminated quote".to_string()));
+        }
+        tokens.push((filter, value));
+    }
+
+    Ok(tokens)
+}
+
+fn parse_bool(filter: &str, value: &str) -> EntryDomainResult<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" => Ok(true),
+        "false" | "no" => Ok(false),
+        _ => Err(query_error(format!("Invalid \"{}:{}\"", filter, value))),
+    }
+}
+
+//"2021-01-01..2021-01-31", open with "2021-01-01.." or "..2021-01-31",
+//">=", ">", "<=" or "<" a date, or a single day.
+fn parse_date_range(value: &str) -> EntryDomainResult<DateRange> {
+    if let Some(separator) = value.find(RANGE_SEPARATOR) {
+        let from = &value[..separator];
+        let to = &value[separator + RANGE_SEPARATOR.len()..];
+        let range = DateRange::new(parse_optional_date(from)?, parse_optional_date(to)?);
+        if range.from.is_none() && range.to.is_none() {
+            return Err(query_error(format!("Invalid date range \"{}\"", value)));
+        }
+        return Ok(range);
+    }
+
+    //The day after the last date, or before the first one, does not exist.
+    let out_of_range = || query_error(format!("Date out of range \"{}\"", value));
+    Ok(if let Some(date) = value.strip_prefix(">=") {
+        DateRange::new(Some(parse_date(date)?), None)
+    } else if let Some(date) = value.strip_prefix('>') {
+        let from = parse_date(date)?.succ_opt().ok_or_else(out_of_range)?;
+        DateRange::new(Some(from), None)
+    } else if let Some(date) = value.strip_prefix("<=") {
+        DateRange::new(None, Some(parse_date(date)?))
+    } else if let Some(date) = value.strip_prefix('<') {
+        let to = parse_date(date)?.pred_opt().ok_or_else(out_of_range)?;
+        DateRange::new(None, Some(to))
+    } else {
+        let date = parse_date(value)?;
+        DateRange::new(Some(date), Some(date))
+    })
+}
+
+fn parse_optional_date(value: &str) -> EntryDomainResult<Option<NaiveDate>> {
+    match value.trim().is_empty() {
+        true => Ok(None),
+        false => Ok(Some(parse_date(value)?)),
+    }
+}
+
+fn parse_date(value: &str) -> EntryDomainResult<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), QUERY_DATE_FORMAT)
+        .map_err(|_| query_error(format!("Invalid date \"{}\", expected YYYY-MM-DD", value)))
+}
+
+fn query_error(message: String) -> MemorizDomainError {
+    MemorizDomainError::Query(message)
+}
+
+//Labels and boards are given by name, without case, or by identifier.
+pub fn matches_query(
+    query: &EntryQuery,
+    entry: &Entry,
+    labels: &[Label],
+    board_title: Option<&str>,
+) -> bool {
+    let text = format!(
+        "{}\n{}",
+        entry.title,
+        entry.content.as_deref().unwrap_or_default()
+    )
+    .to_lowercase();
+    let has_attachment = entry
+        .attachments
+        .as_ref()
+        .map(|attachments| !attachments.is_empty())
+        .unwrap_or(false);
+    let board = entry.board.map(|board| board.to_string());
+    let board_title = board_title.map(title_key);
+
+    query.terms.iter().all(|term| text.contains(term))
+        && query.labels.iter().all(|wanted| {
+            labels
+                .iter()
+                .any(|label| title_key(&label.id) == *wanted || title_key(&label.name) == *wanted)
+        })
+        && (query.boards.is_empty()
+            || query.boards.iter().any(|wanted| {
+                board.as_ref() == Some(wanted) || board_title.as_ref() == Some(wanted)
+            }))
+        && (query.colors.is_empty()
+            || query
+                .colors
+                .iter()
+                .any(|wanted| entry.color.as_deref().map(title_key).as_ref() == Some(wanted)))
+        && query
+            .archived
+            .map(|archived| entry.archived == archived)
+            .unwrap_or(true)
+        && query
+            .created
+            .map(|range| range.contains(entry.created_on))
+            .unwrap_or(true)
+        && query
+            .updated
+            .map(|range| range.contains(entry.updated_on))
+            .unwrap_or(true)
+        && query
+            .due
+            .map(|range| range.contains(entry.due_on))
+            .unwrap_or(true)
+        && query
+            .has_attachment
+            .map(|wanted| has_attachment == wanted)
+            .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        Some(NaiveDate::from_ymd(year, month, day))
+    }
+
+    #[test]
+    fn parses_terms_phrases_and_filters() {
+        let query = parse_query(
+            r#"Release "Side Projects" label:Work board:"Side projects" has:attachment"#,
+        )
+        .unwrap();
+
+        assert_eq!(query.terms, vec!["release", "side projects"]);
+        assert_eq!(query.labels, vec![title_key("Work")]);
+        assert_eq!(query.boards, vec![title_key("Side projects")]);
+        assert_eq!(query.has_attachment, Some(true));
+    }
+
+    #[test]
+    fn keeps_unknown_filters_as_text() {
+        let query = parse_query("note:later https://example.com").unwrap();
+
+        assert_eq!(query.terms, vec!["note:later", "https://example.com"]);
+        assert!(query.labels.is_empty());
+    }
+
+    #[test]
+    fn parses_the_date_ranges() {
+        let query = parse_query(
+            "created:2021-01-01..2021-01-31 updated:>2021-03-01 due:<2021-04-01 archived:no",
+        )
+        .unwrap();
+
+        assert_eq!(
+            query.created,
+            Some(DateRange::new(date(2021, 1, 1), date(2021, 1, 31)))
+        );
+        assert_eq!(query.updated, Some(DateRange::new(date(2021, 3, 2), None)));
+        assert_eq!(query.due, Some(DateRange::new(None, date(2021, 3, 31))));
+        assert_eq!(query.archived, Some(false));
+
+        let query = parse_query("due:2021-04-01 created:..2021-02-01").unwrap();
+        assert_eq!(
+            query.due,
+            Some(DateRange::new(date(2021, 4, 1), date(2021, 4, 1)))
+        );
+        assert_eq!(query.created, Some(DateRange::new(None, date(2021, 2, 1))));
+    }
+
+    #[test]
+    fn refuses_dates_out_of_range() {
+        let last = chrono::naive::MAX_DATE
+            .format(QUERY_DATE_FORMAT)
+            .to_string();
+        let first = chrono::naive::MIN_DATE
+            .format(QUERY_DATE_FORMAT)
+            .to_string();
+
+        assert!(parse_query(&format!("due:>={}", last)).is_ok());
+        assert!(parse_query(&format!("due:>{}", last)).is_err());
+        assert!(parse_query(&format!("due:<{}", first)).is_err());
+    }
+
+    #[test]
+    fn refuses_invalid_queries() {
+        assert!(parse_query("label:").is_err());
+        assert!(parse_query("\"unterminated").is_err());
+        assert!(parse_query("archived:maybe").is_err());
+        assert!(parse_query("has:colors").is_err());
+        assert!(parse_query("due:2021-13-01").is_err());
+        assert!(parse_query("due:..").is_err());
+    }
+}
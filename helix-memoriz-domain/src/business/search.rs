@@ -0,0 +1,165 @@
+use crate::business::error::EntryDomainResult;
+use crate::business::links::title_key;
+use crate::core::board::Board;
+use crate::core::entry::Entry;
+use crate::core::label::Label;
+use crate::core::search::{EntryQuery, FacetCount, SearchRequest};
+use crate::storage::traits::{SearchEngineTrait, StorageTrait};
+use std::collections::HashMap;
+
+pub const DEFAULT_SEARCH_LIMIT: usize = 20;
+pub const MAX_SEARCH_LIMIT: usize = 100;
+
+//Entries read at once while reindexing.
+const REINDEX_PAGE_SIZE: i64 = 200;
+
+//Entry as sent to the search engine, with its labels, attachments and their text.
+pub async fn indexed_entry(
+    storage: &dyn StorageTrait,
+    owner_uuid: uuid::Uuid,
+    uuid: uuid::Uuid,
+) -> EntryDomainResult<Entry> {
+    let entry = storage.get_entry(owner_uuid, uuid).await?;
+    with_indexed_details(storage, owner_uuid, entry).await
+}
+
+async fn with_indexed_details(
+    storage: &dyn StorageTrait,
+    owner_uuid: uuid::Uuid,
+    mut entry: Entry,
+) -> EntryDomainResult<Entry> {
+    let uuid = match entry.uuid {
+        Some(uuid) => uuid,
+        None => return Ok(entry),
+    };
+
+    entry.labels = Some(
+        storage
+            .get_entries_labels(owner_uuid, vec![uuid])
+            .await?
+            .into_iter()
+            .map(|(_, label)| label)
+            .collect(),
+    );
+    let mut texts: HashMap<uuid::Uuid, String> = storage
+        .get_entry_attachment_texts(owner_uuid, uuid)
+        .await?
+        .into_iter()
+        .collect();
+
+    let mut attachments = storage.get_entry_attachments(owner_uuid, uuid).await?;
+    for attachment in attachments.iter_mut() {
+        attachment.text = texts.remove(&attachment.uuid);
+    }
+    entry.attachments = Some(attachments);
+    Ok(entry)
+}
+
+//Send again the entries of one owner, or of every owner, to the search engine:
+//entries indexed before a change of the indexed fields are found again.
+pub async fn reindex(
+    storage: &dyn StorageTrait,
+    search_engine: &dyn SearchEngineTrait,
+    owner_uuid: Option<uuid::Uuid>,
+) -> EntryDomainResult<usize> {
+    let owners = match owner_uuid {
+        Some(owner_uuid) => vec![owner_uuid],
+        None => storage.get_all_owners().await?,
+    };
+
+    let mut indexed = 0;
+    for owner_uuid in owners {
+        let mut last_entry: Option<uuid::Uuid> = None;
+        loop {
+            let entries = storage
+                .get_owner_entries_page(owner_uuid, last_entry, REINDEX_PAGE_SIZE)
+                .await?;
+            let count = entries.len() as i64;
+            for entry in entries {
+                last_entry = entry.uuid;
+                let entry = with_indexed_details(storage, owner_uuid, entry).await?;
+                search_engine.index_entry(&entry).await?;
+                indexed += 1;
+            }
+            if count < REINDEX_PAGE_SIZE {
+                break;
+            }
+        }
+    }
+    Ok(indexed)
+}
+
+//Search engine request of a parsed query, its labels and boards given by name turned
+//into identifiers. None when a label or all the boards are unknown: nothing can match.
+pub fn search_request(
+    query: &EntryQuery,
+    labels: &[Label],
+    boards: &[Board],
+    offset: usize,
+    limit: usize,
+) -> Option<SearchRequest> {
+    let mut label_ids: Vec<String> = Vec::new();
+    for wanted in query.labels.iter() {
+        let label = labels
+            .iter()
+            .find(|label| title_key(&label.id) == *wanted || title_key(&label.name) == *wanted)?;
+        if !label_ids.contains(&label.id) {
+            label_ids.push(label.id.to_owned());
+        }
+    }
+
+    //A board is given by uuid or title, and titles are not unique.
+    let board_uuids: Vec<uuid::Uuid> = boards
+        .iter()
+        .filter(|board| {
+            query.boards.iter().any(|wanted| {
+                board.uuid.map(|uuid| uuid.to_string()).as_ref() == Some(wanted)
+                    || title_key(&board.title) == *wanted
+            })
+        })
+        .filter_map(|board| board.uuid)
+        .collect();
+    if !query.boards.is_empty() && board_uuids.is_empty() {
+        return None;
+    }
+
+    Some(SearchRequest {
+        text: query
+            .terms
+            .iter()
+            .map(|term| match term.contains(char::is_whitespace) {
+                true => format!("\"{}\"", term),
+                false => term.to_owned(),
+            })
+            .collect::<Vec<String>>()
+            .join(" "),
+        boards: board_uuids,
+        labels: label_ids,
+        colors: query.colors.clone(),
+        archived: query.archived,
+        created: query.created,
+        updated: query.updated,
+        due: query.due,
+        has_attachment: query.has_attachment,
+        offset: offset,
+        limit: limit.min(MAX_SEARCH_LIMIT),
+    })
+}
+
+//Counts of a facet, the most frequent first. Values without a name, like the
+//labels deleted since the entries were indexed, are left out.
+pub fn facet_counts(
+    counts: HashMap<String, usize>,
+    names: &HashMap<String, String>,
+) -> Vec<FacetCount> {
+    let mut facets: Vec<FacetCount> = counts
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .filter_map(|(value, count)| {
+            let name = names.get(&value)?.to_owned();
+            Some(FacetCount::new(value, name, count))
+        })
+        .collect();
+    facets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    facets
+}
@@ -0,0 +1,152 @@
+use crate::core::template::Template;
+use std::collections::HashMap;
+
+const PLACEHOLDER_START: &str = "{{";
+const PLACEHOLDER_END: &str = "}}";
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+//Replace the "{{name}}" placeholders by their value, names are compared without case.
+//Unknown placeholders are kept as they are: the braces may be part of the text.
+pub fn expand_placeholders(text: &str, values: &HashMap<String, String>) -> String {
+    let mut expanded = String::new();
+    let mut position = 0;
+
+    while let Some(start) = text[position..].find(PLACEHOLDER_START) {
+        let start = position + start;
+        let name_start = start + PLACEHOLDER_START.len();
+        let end = match text[name_start..].find(PLACEHOLDER_END) {
+            Some(end) => name_start + end,
+            None => break,
+        };
+
+        //"{{ {{date}}": only the last opening braces start the placeholder.
+        if let Some(inner_start) = text[name_start..end].rfind(PLACEHOLDER_START) {
+            let inner_start = name_start + inner_start;
+            expanded.push_str(&text[position..inner_start]);
+            position = inner_start;
+            continue;
+        }
+
+        let name = text[name_start..end].trim().to_lowercase();
+        match values.get(&name) {
+            Some(value) => {
+                expanded.push_str(&text[position..start]);
+                expanded.push_str(value);
+            }
+            None => expanded.push_str(&text[position..end + PLACEHOLDER_END.len()]),
+        }
+        position = end + PLACEHOLDER_END.len();
+    }
+
+    expanded.push_str(&text[position..]);
+    expanded
+}
+
+//Content of an entry created from the template: its content, then its checklist
+//as a Markdown task list.
+pub fn template_content(template: &Template, values: &HashMap<String, String>) -> Option<String> {
+    let mut content = template
+        .content
+        .as_deref()
+        .map(|content| expand_placeholders(content, values))
+        .unwrap_or_default();
+
+    let items: Vec<String> = template
+        .checklist
+        .iter()
+        .map(|item| item.trim())
+        .filter(|item| !item.is_empty())
+        .map(|item| format!("- [ ] {}", expand_placeholders(item, values)))
+        .collect();
+    if !items.is_empty() {
+        if !content.is_empty() {
+            content = format!("{}\n\n", content.trim_end());
+        }
+        content.push_str(&items.join("\n"));
+    }
+
+    match content.is_empty() {
+        true => None,
+        false => Some(content),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values() -> HashMap<String, String> {
+        let mut values: HashMap<String, String> = HashMap::new();
+        values.insert("date".to_string(), "2024-03-01".to_string());
+        values.insert("user".to_string(), "Ada".to_string());
+        values
+    }
+
+    #[test]
+    fn expands_the_known_placeholders() {
+        assert_eq!(
+            expand_placeholders("Meeting {{date}} with {{ User }}, {{DATE}}", &values()),
+            "Meeting 2024-03-01 with Ada, 2024-03-01"
+        );
+    }
+
+    #[test]
+    fn keeps_the_unknown_placeholders() {
+        assert_eq!(
+            expand_placeholders("{{unknown}} and {{ }} by {{user}}", &values()),
+            "{{unknown}} and {{ }} by Ada"
+        );
+        assert_eq!(
+            expand_placeholders("fn main() { {{date}} }} {{open", &values()),
+            "fn main() { 2024-03-01 }} {{open"
+        );
+        assert_eq!(
+            expand_placeholders("{{ {{date}} }}", &values()),
+            "{{ 2024-03-01 }}"
+        );
+    }
+
+    #[test]
+    fn appends_the_checklist() {
+        let template = Template::new(
+            None,
+            "Weekly".to_string(),
+            "Week of {{date}}".to_string(),
+            Some("Notes of {{user}}\n\n".to_string()),
+            None,
+            Vec::new(),
+            None,
+            vec![
+                "Review {{date}}".to_string(),
+                "  ".to_string(),
+                "Plan".to_string(),
+            ],
+            None,
+            None,
+            None,
+        );
+        assert_eq!(
+            template_content(&template, &values()).as_deref(),
+            Some("Notes of Ada\n\n- [ ] Review 2024-03-01\n- [ ] Plan")
+        );
+    }
+
+    #[test]
+    fn has_no_content_without_content_nor_checklist() {
+        let template = Template::new(
+            None,
+            "Empty".to_string(),
+            "Empty".to_string(),
+            None,
+            None,
+            Vec::new(),
+            None,
+            vec![" ".to_string()],
+            None,
+            None,
+            None,
+        );
+        assert_eq!(template_content(&template, &values()), None);
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+//Wrong passwords given for each key, a public link token, to slow down guessing.
+//Kept in memory: a restart forgets them, which only gives a few more attempts.
+pub struct PasswordThrottle {
+    max_failures: usize,
+    window: Duration,
+    failures: Mutex<HashMap<String, Vec<DateTime<Utc>>>>,
+}
+
+impl PasswordThrottle {
+    pub fn new(max_failures: usize, window: Duration) -> PasswordThrottle {
+        PasswordThrottle {
+            max_failures: max_failures,
+            window: window,
+            failures: Mutex::new(HashMap::new()),
+        }
+    }
+
+    //True when the key had too many failures during the last window.
+    pub fn is_blocked(&self, key: &str, now: DateTime<Utc>) -> bool {
+        let failures = self.failures.lock().unwrap();
+        let since = now - self.window;
+        failures
+            .get(key)
+            .map(|dates| dates.iter().filter(|date| **date > since).count())
+            .unwrap_or(0)
+            >= self.max_failures
+    }
+
+    //Count the attempt as a failure before the password is checked, under the same lock
+    //as the limit: concurrent guesses can not all pass before the first one fails.
+    //Return false when the key is blocked, clear() forgets the attempt when it succeeds.
+    pub fn reserve_attempt(&self, key: &str, now: DateTime<Utc>) -> bool {
+        let mut failures = self.failures.lock().unwrap();
+        let since = now - self.window;
+
+        //Older failures are forgotten, so the map does not grow with the tokens tried.
+        failures.retain(|_, dates| {
+            dates.retain(|date| *date > since);
+            !dates.is_empty()
+        });
+        let dates = failures.entry(key.to_string()).or_default();
+        if dates.len() >= self.max_failures {
+            return false;
+        }
+        dates.push(now);
+        true
+    }
+
+    pub fn clear(&self, key: &str) {
+        self.failures.lock().unwrap().remove(key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn blocks_after_too_many_failures() {
+        let throttle = PasswordThrottle::new(2, Duration::minutes(10));
+        let now = Utc::now();
+
+        assert!(throttle.reserve_attempt("token", now));
+        assert!(!throttle.is_blocked("token", now));
+        assert!(throttle.reserve_attempt("token", now));
+        assert!(throttle.is_blocked("token", now));
+        assert!(!throttle.reserve_attempt("token", now));
+        assert!(!throttle.is_blocked("other", now));
+    }
+
+    #[test]
+    fn blocks_concurrent_wrong_passwords() {
+        let throttle = Arc::new(PasswordThrottle::new(3, Duration::minutes(10)));
+        let now = Utc::now();
+
+        //Every guess is still being checked: none of them has failed yet.
+        let guesses: Vec<thread::JoinHandle<bool>> = (0..20)
+            .map(|_| {
+                let throttle = throttle.clone();
+                thread::spawn(move || throttle.reserve_attempt("token", now))
+            })
+            .collect();
+        let granted = guesses
+            .into_iter()
+            .map(|guess| guess.join().unwrap())
+            .filter(|granted| *granted)
+            .count();
+
+        assert_eq!(granted, 3);
+        assert!(throttle.is_blocked("token", now));
+        assert!(!throttle.reserve_attempt("token", now + Duration::minutes(5)));
+    }
+
+    #[test]
+    fn forgets_the_failures_of_past_windows() {
+        let throttle = PasswordThrottle::new(1, Duration::minutes(10));
+        let now = Utc::now();
+
+        assert!(throttle.reserve_attempt("token", now));
+        assert!(throttle.is_blocked("token", now + Duration::minutes(9)));
+        assert!(!throttle.is_blocked("token", now + Duration::minutes(11)));
+    }
+
+    #[test]
+    fn clear_forgets_the_failures() {
+        let throttle = PasswordThrottle::new(1, Duration::minutes(10));
+        let now = Utc::now();
+
+        assert!(throttle.reserve_attempt("token", now));
+        throttle.clear("token");
+        assert!(!throttle.is_blocked("token", now));
+    }
+}
@@ -0,0 +1,109 @@
+use crate::business::error::{EntryDomainResult, MemorizDomainError};
+use exif::{In, Reader, Tag};
+use image::imageops::FilterType;
+use image::io::Reader as ImageReader;
+use image::{DynamicImage, GenericImageView, ImageOutputFormat, Rgba, RgbaImage};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+
+//Longest side of the generated thumbnails, in pixels.
+pub const THUMBNAIL_SIZES: &[u32] = &[128, 512, 1024];
+pub const THUMBNAIL_CONTENT_TYPE: &str = "image/jpeg";
+
+//Larger images are described but not decoded: it would take too much memory.
+const MAX_DECODED_PIXELS: u64 = 40_000_000;
+const THUMBNAIL_QUALITY: u8 = 80;
+
+pub struct ImageDescription {
+    pub width: u32,
+    pub height: u32,
+    //Longest side and JPEG content of each thumbnail.
+    pub thumbnails: Vec<(u32, Vec<u8>)>,
+}
+
+pub fn content_hash(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+//Size of the image as displayed, after its EXIF orientation, and its thumbnails.
+//An image smaller than a thumbnail size keeps its own size.
+pub fn describe_image(content: &[u8]) -> EntryDomainResult<ImageDescription> {
+    let (width, height) = ImageReader::new(Cursor::new(content))
+        .with_guessed_format()
+        .map_err(image_error)?
+        .into_dimensions()
+        .map_err(image_error)?;
+    let orientation = exif_orientation(content);
+    let (width, height) = match orientation {
+        5..=8 => (height, width),
+        _ => (width, height),
+    };
+
+    if width as u64 * height as u64 > MAX_DECODED_PIXELS {
+        return Ok(ImageDescription {
+            width: width,
+            height: height,
+            thumbnails: Vec::new(),
+        });
+    }
+
+    let image = image::load_from_memory(content).map_err(image_error)?;
+    let image = apply_orientation(image, orientation);
+
+    let mut thumbnails = Vec::new();
+    for size in THUMBNAIL_SIZES {
+        let thumbnail = match width.max(height) > *size {
+            true => image.resize(*size, *size, FilterType::Triangle),
+            false => image.clone(),
+        };
+        thumbnails.push((*size, encode_thumbnail(thumbnail)?));
+    }
+
+    Ok(ImageDescription {
+        width: width,
+        height: height,
+        thumbnails: thumbnails,
+    })
+}
+
+//Orientation tag of the image, 1 (as stored) when it has none.
+fn exif_orientation(content: &[u8]) -> u32 {
+    Reader::new()
+        .read_from_container(&mut Cursor::new(content))
+        .ok()
+        .and_then(|exif| {
+            exif.get_field(Tag::Orientation, In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        })
+        .unwrap_or(1)
+}
+
+fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+//JPEG has no transparency: transparent parts become white.
+fn encode_thumbnail(thumbnail: DynamicImage) -> EntryDomainResult<Vec<u8>> {
+    let (width, height) = thumbnail.dimensions();
+    let mut flattened = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+    image::imageops::overlay(&mut flattened, &thumbnail.to_rgba8(), 0, 0);
+
+    let mut content = Vec::new();
+    DynamicImage::ImageRgb8(DynamicImage::ImageRgba8(flattened).to_rgb8())
+        .write_to(&mut content, ImageOutputFormat::Jpeg(THUMBNAIL_QUALITY))
+        .map_err(image_error)?;
+    Ok(content)
+}
+
+fn image_error(error: impl ToString) -> MemorizDomainError {
+    MemorizDomainError::Attachment(error.to_string())
+}
@@ -1,13 +1,24 @@
+use crate::business::calendar::CalendarComponent;
 use crate::business::error::EntryDomainResult;
-use crate::core::{board::*, entry::*};
+use crate::business::export::markdown::MarkdownExport;
+use crate::core::{
+    attachment::*, board::*, clip::*, document::*, entry::*, graph::*, import::*, link::*,
+    search::*, share::*, sync::*, template::*,
+};
 use async_trait::async_trait;
+use chrono::prelude::*;
 
 #[async_trait]
 pub trait DomainTrait: Send + Sync {
     //ENTRY
     //-----------------------------------------------
     async fn create_entry(&self, entry: Entry) -> EntryDomainResult<Entry>;
-    async fn update_entry(&self, entry: Entry) -> EntryDomainResult<Entry>;
+    async fn clip_entry(
+        &self,
+        owner_uuid: uuid::Uuid,
+        clip: ClipRequest,
+    ) -> EntryDomainResult<Entry>;
+    async fn update_entry(&self, entry: Entry, rewrite_links: bool) -> EntryDomainResult<Entry>;
     async fn delete_entry(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid)
         -> EntryDomainResult<()>;
     async fn archive_entry(
@@ -28,15 +39,146 @@ pub trait DomainTrait: Send + Sync {
         &self,
         owner_uuid: uuid::Uuid,
         archived_filter: Option<bool>,
+        due_filter: Option<DueFilter>,
     ) -> EntryDomainResult<Vec<Entry>>;
     async fn get_all_entries_by_board(
         &self,
         owner_uuid: uuid::Uuid,
         board_uuid: uuid::Uuid,
         archived_filter: Option<bool>,
+        due_filter: Option<DueFilter>,
     ) -> EntryDomainResult<Vec<Entry>>;
 
-    async fn search(&self, owner_uuid: uuid::Uuid, query: String) -> EntryDomainResult<Vec<Entry>>;
+    //Query with the syntax of the saved searches, a page of "limit" hits from "offset".
+    async fn search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        query: String,
+        offset: usize,
+        limit: usize,
+    ) -> EntryDomainResult<EntrySearchResults>;
+
+    async fn get_entry_document(
+        &self,
+        user_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<EntryDocument>;
+    async fn merge_entry_document(
+        &self,
+        user_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+        update: Vec<u8>,
+        heads: Vec<String>,
+    ) -> EntryDomainResult<EntryDocument>;
+    async fn add_attachment(
+        &self,
+        user_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        file_name: Option<String>,
+        content_type: String,
+        content: Vec<u8>,
+    ) -> EntryDomainResult<Attachment>;
+    async fn get_entry_attachments(
+        &self,
+        user_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<Attachment>>;
+    async fn get_attachment_content(
+        &self,
+        user_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<(Attachment, Vec<u8>)>;
+    async fn get_attachment_thumbnail(
+        &self,
+        user_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+        size: i32,
+    ) -> EntryDomainResult<Vec<u8>>;
+    async fn delete_attachment(
+        &self,
+        user_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()>;
+    async fn get_entry_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<EntryLink>>;
+    async fn get_entry_backlinks(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<Entry>>;
+
+    // GRAPH
+    //-----------------------------------------------
+    async fn get_graph(
+        &self,
+        owner_uuid: uuid::Uuid,
+        center_uuid: Option<uuid::Uuid>,
+        depth: usize,
+    ) -> EntryDomainResult<Graph>;
+
+    // IMPORT
+    //-----------------------------------------------
+    async fn import_keep(
+        &self,
+        owner_uuid: uuid::Uuid,
+        archive: Vec<u8>,
+    ) -> EntryDomainResult<ImportReport>;
+    async fn import_markdown_vault(
+        &self,
+        owner_uuid: uuid::Uuid,
+        archive: Vec<u8>,
+    ) -> EntryDomainResult<ImportReport>;
+    async fn import_trello(
+        &self,
+        owner_uuid: uuid::Uuid,
+        export: Vec<u8>,
+    ) -> EntryDomainResult<ImportReport>;
+    async fn import_enex(
+        &self,
+        owner_uuid: uuid::Uuid,
+        file_name: String,
+        export: Vec<u8>,
+    ) -> EntryDomainResult<ImportReport>;
+
+    // EXPORT
+    //-----------------------------------------------
+    async fn export_markdown(&self, owner_uuid: uuid::Uuid) -> EntryDomainResult<MarkdownExport>;
+    //Next bytes of the archive, None once it is complete.
+    async fn next_markdown_export_chunk(
+        &self,
+        export: &mut MarkdownExport,
+    ) -> EntryDomainResult<Option<Vec<u8>>>;
+
+    // REMINDER
+    //-----------------------------------------------
+    async fn dispatch_due_reminders(&self, now: DateTime<Utc>) -> EntryDomainResult<usize>;
+
+    // ATTACHMENT PROCESSING
+    //-----------------------------------------------
+    //Size and thumbnails of at most "limit" new image attachments.
+    async fn process_attachments(&self, limit: i64) -> EntryDomainResult<usize>;
+
+    // LINK PREVIEWS
+    //-----------------------------------------------
+    //Fetch the previews of at most "limit" URLs waiting for theirs.
+    async fn process_link_previews(&self, limit: i64) -> EntryDomainResult<usize>;
+
+    // CALENDAR
+    //-----------------------------------------------
+    async fn renew_calendar_token(&self, owner_uuid: uuid::Uuid) -> EntryDomainResult<String>;
+    async fn get_calendar(
+        &self,
+        token: String,
+        board_filter: Option<uuid::Uuid>,
+        archived_filter: Option<bool>,
+        component: CalendarComponent,
+    ) -> EntryDomainResult<String>;
 
     // BOARD
     //-----------------------------------------------
@@ -48,6 +190,116 @@ pub trait DomainTrait: Send + Sync {
         -> EntryDomainResult<Board>;
     async fn get_all_boards(&self, owner_uuid: uuid::Uuid) -> EntryDomainResult<Vec<Board>>;
 
+    // SAVED SEARCH
+    //-----------------------------------------------
+    async fn create_saved_search(
+        &self,
+        saved_search: SavedSearch,
+    ) -> EntryDomainResult<SavedSearch>;
+    async fn update_saved_search(
+        &self,
+        saved_search: SavedSearch,
+    ) -> EntryDomainResult<SavedSearch>;
+    async fn delete_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()>;
+    async fn get_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<SavedSearch>;
+    async fn get_all_saved_searches(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<SavedSearch>>;
+    //Entries of the owner matching the query, evaluated at each call.
+    async fn get_all_entries_by_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+        archived_filter: Option<bool>,
+        due_filter: Option<DueFilter>,
+    ) -> EntryDomainResult<Vec<Entry>>;
+
+    // TEMPLATE
+    //-----------------------------------------------
+    async fn create_template(&self, template: Template) -> EntryDomainResult<Template>;
+    async fn update_template(&self, template: Template) -> EntryDomainResult<Template>;
+    async fn delete_template(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()>;
+    async fn get_template(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Template>;
+    async fn get_all_templates(&self, owner_uuid: uuid::Uuid) -> EntryDomainResult<Vec<Template>>;
+    //Entry expanded from a template of the user, with its labels.
+    async fn create_entry_from_template(
+        &self,
+        user_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+        request: TemplateEntryRequest,
+    ) -> EntryDomainResult<Entry>;
+
+    // SHARE
+    //-----------------------------------------------
+    async fn share_item(
+        &self,
+        owner_uuid: uuid::Uuid,
+        kind: SharedItemKind,
+        item_uuid: uuid::Uuid,
+        recipient_uuid: uuid::Uuid,
+        role: ShareRole,
+    ) -> EntryDomainResult<Share>;
+    async fn unshare_item(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+        recipient_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<()>;
+    async fn get_item_shares(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<Share>>;
+    async fn get_shared_with_me(&self, user_uuid: uuid::Uuid) -> EntryDomainResult<SharedWithMe>;
+    async fn create_public_link(
+        &self,
+        owner_uuid: uuid::Uuid,
+        kind: SharedItemKind,
+        item_uuid: uuid::Uuid,
+        expires_on: Option<DateTime<Utc>>,
+        password: Option<String>,
+    ) -> EntryDomainResult<PublicLink>;
+    async fn get_item_public_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> EntryDomainResult<Vec<PublicLink>>;
+    async fn revoke_public_link(
+        &self,
+        owner_uuid: uuid::Uuid,
+        token: String,
+    ) -> EntryDomainResult<()>;
+    async fn get_public_item(
+        &self,
+        token: String,
+        password: Option<String>,
+    ) -> EntryDomainResult<PublicItem>;
+
+    // SYNC
+    //-----------------------------------------------
+    async fn sync(
+        &self,
+        user_uuid: uuid::Uuid,
+        request: SyncRequest,
+    ) -> EntryDomainResult<SyncResponse>;
+
     /*
     // LABEL
     //-----------------------------------------------
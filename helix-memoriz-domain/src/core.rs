@@ -1,3 +1,17 @@
+pub mod attachment;
 pub mod board;
+pub mod clip;
+pub mod document;
+pub mod dump;
 pub mod entry;
+pub mod event;
+pub mod graph;
+pub mod import;
 pub mod label;
+pub mod link;
+pub mod preview;
+pub mod recurrence;
+pub mod search;
+pub mod share;
+pub mod sync;
+pub mod template;
@@ -0,0 +1,96 @@
+use chrono::prelude::*;
+use uuid;
+
+//File attached to an entry. The content is in the blob store, under "blob_key()".
+//Images get their size and thumbnails once processed, in the background.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Attachment {
+    pub uuid: uuid::Uuid,
+    pub entry: uuid::Uuid,
+    pub owner: uuid::Uuid,
+    pub file_name: String,
+    pub content_type: String,
+    pub size: i64,
+    pub created_on: Option<DateTime<Utc>>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub content_hash: Option<String>,
+    pub thumbnails: Vec<Thumbnail>,
+    pub processed_on: Option<DateTime<Utc>>,
+    //Extracted text of a document, only read to index its entry.
+    #[serde(skip)]
+    pub text: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Thumbnail {
+    pub size: i32,
+    pub url: String,
+}
+
+impl Attachment {
+    pub fn new(
+        uuid: uuid::Uuid,
+        entry: uuid::Uuid,
+        owner: uuid::Uuid,
+        file_name: String,
+        content_type: String,
+        size: i64,
+        created_on: Option<DateTime<Utc>>,
+        width: Option<i32>,
+        height: Option<i32>,
+        content_hash: Option<String>,
+        thumbnail_sizes: Vec<i32>,
+        processed_on: Option<DateTime<Utc>>,
+    ) -> Attachment {
+        Attachment {
+            uuid: uuid,
+            entry: entry,
+            owner: owner,
+            file_name: file_name,
+            content_type: content_type,
+            size: size,
+            created_on: created_on,
+            width: width,
+            height: height,
+            content_hash: content_hash,
+            thumbnails: thumbnail_sizes
+                .into_iter()
+                .map(|size| Thumbnail::new(entry, uuid, size))
+                .collect(),
+            processed_on: processed_on,
+            text: None,
+        }
+    }
+
+    pub fn blob_key(&self) -> String {
+        format!("{}/{}", self.owner, self.uuid)
+    }
+
+    pub fn thumbnail_key(&self, size: i32) -> String {
+        format!("{}/{}-{}", self.owner, self.uuid, size)
+    }
+
+    pub fn thumbnail_sizes(&self) -> Vec<i32> {
+        self.thumbnails
+            .iter()
+            .map(|thumbnail| thumbnail.size)
+            .collect()
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.content_type.starts_with("image/")
+    }
+}
+
+impl Thumbnail {
+    pub fn new(entry: uuid::Uuid, attachment: uuid::Uuid, size: i32) -> Thumbnail {
+        Thumbnail {
+            size: size,
+            url: format!(
+                "/api/entries/{}/attachments/{}/thumbnails/{}",
+                entry, attachment, size
+            ),
+        }
+    }
+}
@@ -0,0 +1,9 @@
+use uuid;
+
+//Page sent by the browser extension: its URL and its HTML as rendered in the browser.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ClipRequest {
+    pub url: String,
+    pub html: String,
+    pub board: Option<uuid::Uuid>,
+}
@@ -0,0 +1,26 @@
+use uuid;
+
+//Automerge document of an entry content, or changes of it, with its current heads.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntryDocument {
+    pub uuid: uuid::Uuid,
+    pub heads: Vec<String>,
+    pub document: Vec<u8>,
+    pub content: String,
+}
+
+impl EntryDocument {
+    pub fn new(
+        uuid: uuid::Uuid,
+        heads: Vec<String>,
+        document: Vec<u8>,
+        content: String,
+    ) -> EntryDocument {
+        EntryDocument {
+            uuid: uuid,
+            heads: heads,
+            document: document,
+            content: content,
+        }
+    }
+}
@@ -0,0 +1,308 @@
+use crate::core::{
+    attachment::Attachment,
+    board::Board,
+    entry::Entry,
+    label::Label,
+    link::EntryLink,
+    search::SavedSearch,
+    share::{PublicLink, Share},
+    sync::ChangeLogEntry,
+    template::Template,
+};
+use chrono::prelude::*;
+use uuid;
+
+pub const DUMP_FORMAT: &str = "memoriz-dump";
+//Increased on every change of the records, older dumps stay readable.
+pub const DUMP_FORMAT_VERSION: u32 = 4;
+
+//First line of a dump.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DumpHeader {
+    pub format: String,
+    pub version: u32,
+    pub created_on: DateTime<Utc>,
+    //None when every owner is dumped.
+    pub owner: Option<uuid::Uuid>,
+}
+
+impl DumpHeader {
+    pub fn new(owner: Option<uuid::Uuid>) -> DumpHeader {
+        DumpHeader {
+            format: DUMP_FORMAT.to_string(),
+            version: DUMP_FORMAT_VERSION,
+            created_on: Utc::now(),
+            owner: owner,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntryLabel {
+    pub entry: uuid::Uuid,
+    pub label: String,
+    pub owner: uuid::Uuid,
+}
+
+impl EntryLabel {
+    pub fn new(entry: uuid::Uuid, label: String, owner: uuid::Uuid) -> EntryLabel {
+        EntryLabel {
+            entry: entry,
+            label: label,
+            owner: owner,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CalendarToken {
+    pub owner: uuid::Uuid,
+    pub token: String,
+    pub created_on: DateTime<Utc>,
+}
+
+impl CalendarToken {
+    pub fn new(owner: uuid::Uuid, token: String, created_on: DateTime<Utc>) -> CalendarToken {
+        CalendarToken {
+            owner: owner,
+            token: token,
+            created_on: created_on,
+        }
+    }
+}
+
+//Public link with its password hash, which PublicLink never serializes.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DumpedPublicLink {
+    #[serde(flatten)]
+    pub link: PublicLink,
+    pub password_hash: Option<String>,
+}
+
+impl DumpedPublicLink {
+    pub fn new(link: PublicLink) -> DumpedPublicLink {
+        DumpedPublicLink {
+            password_hash: link.password_hash.clone(),
+            link: link,
+        }
+    }
+
+    pub fn into_public_link(self) -> PublicLink {
+        let mut link = self.link;
+        link.password_hash = self.password_hash;
+        link
+    }
+}
+
+//Automerge document of an entry content, in base64.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DumpedDocument {
+    pub entry: uuid::Uuid,
+    pub owner: uuid::Uuid,
+    #[serde(with = "base64_bytes")]
+    pub document: Vec<u8>,
+}
+
+impl DumpedDocument {
+    pub fn new(entry: uuid::Uuid, owner: uuid::Uuid, document: Vec<u8>) -> DumpedDocument {
+        DumpedDocument {
+            entry: entry,
+            owner: owner,
+            document: document,
+        }
+    }
+}
+
+//Attachment with its extracted text, which Attachment never serializes. The files
+//stay in the blob store, backed up on its own: blob_keys lists the file and thumbnails.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DumpedAttachment {
+    #[serde(flatten)]
+    pub attachment: Attachment,
+    pub text: Option<String>,
+    #[serde(default)]
+    pub blob_keys: Vec<String>,
+}
+
+impl DumpedAttachment {
+    pub fn new(attachment: Attachment, text: Option<String>) -> DumpedAttachment {
+        let mut blob_keys = vec![attachment.blob_key()];
+        for size in attachment.thumbnail_sizes() {
+            blob_keys.push(attachment.thumbnail_key(size));
+        }
+
+        DumpedAttachment {
+            attachment: attachment,
+            text: text,
+            blob_keys: blob_keys,
+        }
+    }
+}
+
+mod base64_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        base64::decode(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+//One line of a dump, in JSON Lines: {"type":"entry", ...}.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DumpRecord {
+    Header(DumpHeader),
+    Board(Board),
+    Label(Label),
+    Entry(Entry),
+    EntryLabel(EntryLabel),
+    EntryLink(EntryLink),
+    //Since version 2.
+    Share(Share),
+    //Since version 3.
+    CalendarToken(CalendarToken),
+    //Since version 3.
+    PublicLink(DumpedPublicLink),
+    //Since version 3.
+    Change(ChangeLogEntry),
+    //Since version 3.
+    Document(DumpedDocument),
+    //Since version 3.
+    Attachment(DumpedAttachment),
+    //Since version 4.
+    Template(Template),
+    //Since version 4.
+    SavedSearch(SavedSearch),
+}
+
+//Number of records dumped or restored.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DumpReport {
+    pub owners: usize,
+    pub boards: usize,
+    pub labels: usize,
+    pub entries: usize,
+    pub entry_labels: usize,
+    pub entry_links: usize,
+    #[serde(default)]
+    pub shares: usize,
+    #[serde(default)]
+    pub calendar_tokens: usize,
+    #[serde(default)]
+    pub public_links: usize,
+    #[serde(default)]
+    pub changes: usize,
+    #[serde(default)]
+    pub documents: usize,
+    #[serde(default)]
+    pub attachments: usize,
+    #[serde(default)]
+    pub templates: usize,
+    #[serde(default)]
+    pub saved_searches: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::share::SharedItemKind;
+
+    #[test]
+    fn public_link_record_keeps_the_password_hash() {
+        let link = PublicLink::new(
+            "token".to_string(),
+            uuid::Uuid::new_v4(),
+            SharedItemKind::Entry,
+            uuid::Uuid::new_v4(),
+            Some("$2b$12$hash".to_string()),
+            None,
+            Some(Utc::now()),
+        );
+        let line =
+            serde_json::to_string(&DumpRecord::PublicLink(DumpedPublicLink::new(link))).unwrap();
+
+        match serde_json::from_str(&line).unwrap() {
+            DumpRecord::PublicLink(record) => {
+                let link = record.into_public_link();
+                assert_eq!(link.token, "token");
+                assert_eq!(link.password_hash.as_deref(), Some("$2b$12$hash"));
+            }
+            record => panic!("unexpected record {:?}", record),
+        }
+    }
+
+    #[test]
+    fn document_record_keeps_the_bytes() {
+        let document = DumpedDocument::new(
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+            vec![0, 133, 255],
+        );
+        let line = serde_json::to_string(&DumpRecord::Document(document)).unwrap();
+
+        match serde_json::from_str(&line).unwrap() {
+            DumpRecord::Document(record) => assert_eq!(record.document, vec![0, 133, 255]),
+            record => panic!("unexpected record {:?}", record),
+        }
+    }
+
+    #[test]
+    fn attachment_record_keeps_the_text_and_thumbnails() {
+        let attachment = Attachment::new(
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+            uuid::Uuid::new_v4(),
+            "photo.jpg".to_string(),
+            "image/jpeg".to_string(),
+            2048,
+            Some(Utc::now()),
+            Some(800),
+            Some(600),
+            None,
+            vec![256],
+            Some(Utc::now()),
+        );
+        let record = DumpedAttachment::new(attachment, Some("Text".to_string()));
+        assert_eq!(record.blob_keys.len(), 2);
+        let line = serde_json::to_string(&DumpRecord::Attachment(record)).unwrap();
+
+        match serde_json::from_str(&line).unwrap() {
+            DumpRecord::Attachment(record) => {
+                assert_eq!(record.text.as_deref(), Some("Text"));
+                assert_eq!(record.attachment.thumbnail_sizes(), vec![256]);
+            }
+            record => panic!("unexpected record {:?}", record),
+        }
+    }
+
+    #[test]
+    fn template_record_keeps_the_placeholders() {
+        let template = Template::new(
+            Some(uuid::Uuid::new_v4()),
+            "Meeting".to_string(),
+            "Meeting of {{date}}".to_string(),
+            None,
+            None,
+            vec!["work".to_string()],
+            None,
+            vec!["Notes by {{user}}".to_string()],
+            Some(Utc::now()),
+            None,
+            Some(uuid::Uuid::new_v4()),
+        );
+        let line = serde_json::to_string(&DumpRecord::Template(template)).unwrap();
+
+        match serde_json::from_str(&line).unwrap() {
+            DumpRecord::Template(record) => {
+                assert_eq!(record.title, "Meeting of {{date}}");
+                assert_eq!(record.checklist, vec!["Notes by {{user}}"]);
+            }
+            record => panic!("unexpected record {:?}", record),
+        }
+    }
+}
@@ -1,5 +1,10 @@
+use crate::core::attachment::Attachment;
 use crate::core::label::Label;
+use crate::core::preview::LinkPreview;
+use crate::core::recurrence::Recurrence;
+use crate::core::search::MatchSource;
 use chrono::prelude::*;
+use chrono::Duration;
 use serde_json;
 use uuid;
 
@@ -17,6 +22,25 @@ pub struct Entry {
     pub owner: Option<uuid::Uuid>,
     pub labels: Option<Vec<Label>>,
     pub board: Option<uuid::Uuid>,
+    pub due_on: Option<DateTime<Utc>>,
+    pub remind_at: Option<DateTime<Utc>>,
+    //Set when the reminder is sent: the reminder date stays for the next occurrences.
+    #[serde(default)]
+    pub reminded_on: Option<DateTime<Utc>>,
+    pub recurrence: Option<Recurrence>,
+    pub next_occurrence: Option<DateTime<Utc>>,
+    //Rendered on demand from the content, never stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_html: Option<String>,
+    //Filled on reads from the attachments, never stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attachments: Option<Vec<Attachment>>,
+    //Filled on reads from the cached previews of the URLs of the content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previews: Option<Vec<LinkPreview>>,
+    //Filled on searches: where the query matched.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matched_in: Option<Vec<MatchSource>>,
 }
 
 impl Entry {
@@ -33,6 +57,11 @@ impl Entry {
         owner: Option<uuid::Uuid>,
         labels: Option<Vec<Label>>,
         board: Option<uuid::Uuid>,
+        due_on: Option<DateTime<Utc>>,
+        remind_at: Option<DateTime<Utc>>,
+        reminded_on: Option<DateTime<Utc>>,
+        recurrence: Option<Recurrence>,
+        next_occurrence: Option<DateTime<Utc>>,
     ) -> Entry {
         Entry {
             id: id,
@@ -47,6 +76,195 @@ impl Entry {
             owner: owner,
             labels: labels,
             board: board,
+            due_on: due_on,
+            remind_at: remind_at,
+            reminded_on: reminded_on,
+            recurrence: recurrence,
+            next_occurrence: next_occurrence,
+            content_html: None,
+            attachments: None,
+            previews: None,
+            matched_in: None,
         }
     }
+
+    pub fn refresh_next_occurrence(&mut self) {
+        if let (Some(recurrence), Some(due_on)) = (self.recurrence.as_mut(), self.due_on) {
+            recurrence.anchor(due_on);
+        }
+        self.next_occurrence = match (&self.recurrence, self.due_on) {
+            (Some(recurrence), Some(due_on)) => recurrence.next_occurrence(due_on),
+            _ => None,
+        };
+    }
+
+    //Move a recurring entry to its next occurrence, the reminder keeping the same advance
+    //and waiting to be sent again.
+    //Return false when the entry is not recurring.
+    pub fn roll_forward(&mut self) -> bool {
+        let (due_on, next_occurrence) = match (self.due_on, self.next_occurrence) {
+            (Some(due_on), Some(next_occurrence)) if self.recurrence.is_some() => {
+                (due_on, next_occurrence)
+            }
+            _ => return false,
+        };
+
+        self.remind_at = self
+            .remind_at
+            .map(|remind_at| next_occurrence - (due_on - remind_at));
+        self.reminded_on = None;
+        self.due_on = Some(next_occurrence);
+        self.refresh_next_occurrence();
+        true
+    }
+}
+
+//Filter applied on the due date of the entries.
+#[derive(Debug, Clone, Copy)]
+pub enum DueFilter {
+    Overdue,
+    Upcoming(Duration),
+}
+
+impl DueFilter {
+    pub fn matches(&self, entry: &Entry, now: DateTime<Utc>) -> bool {
+        match (self, entry.due_on) {
+            (_, None) => false,
+            (DueFilter::Overdue, Some(due_on)) => due_on < now,
+            //A delay beyond the last date takes every later due date.
+            (DueFilter::Upcoming(delay), Some(due_on)) => {
+                due_on >= now
+                    && now
+                        .checked_add_signed(*delay)
+                        .map(|until| due_on <= until)
+                        .unwrap_or(true)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn recurring_entry(
+        recurrence: Option<&str>,
+        due_on: DateTime<Utc>,
+        remind_at: Option<DateTime<Utc>>,
+    ) -> Entry {
+        let mut entry = Entry::new(
+            0,
+            None,
+            "Rent".to_string(),
+            None,
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+            None,
+            None,
+            Some(due_on),
+            remind_at,
+            None,
+            recurrence.map(|value| Recurrence::from_str(value).unwrap()),
+            None,
+        );
+        entry.refresh_next_occurrence();
+        entry
+    }
+
+    #[test]
+    fn rolls_forward_with_the_same_reminder_advance() {
+        let due_on = Utc.ymd(2024, 1, 31).and_hms(18, 0, 0);
+        let mut entry = recurring_entry(
+            Some("FREQ=MONTHLY"),
+            due_on,
+            Some(due_on - Duration::hours(2)),
+        );
+
+        assert!(entry.roll_forward());
+        assert_eq!(entry.due_on, Some(Utc.ymd(2024, 2, 29).and_hms(18, 0, 0)));
+        assert_eq!(
+            entry.remind_at,
+            Some(Utc.ymd(2024, 2, 29).and_hms(16, 0, 0))
+        );
+        assert_eq!(
+            entry.next_occurrence,
+            Some(Utc.ymd(2024, 3, 31).and_hms(18, 0, 0))
+        );
+
+        assert!(entry.roll_forward());
+        assert_eq!(entry.due_on, Some(Utc.ymd(2024, 3, 31).and_hms(18, 0, 0)));
+        assert_eq!(
+            entry.remind_at,
+            Some(Utc.ymd(2024, 3, 31).and_hms(16, 0, 0))
+        );
+    }
+
+    #[test]
+    fn rolls_the_reminder_over_the_previous_day() {
+        let due_on = Utc.ymd(2024, 2, 29).and_hms(8, 0, 0);
+        let mut entry = recurring_entry(
+            Some("FREQ=YEARLY"),
+            due_on,
+            Some(due_on - Duration::hours(12)),
+        );
+
+        assert!(entry.roll_forward());
+        assert_eq!(entry.due_on, Some(Utc.ymd(2025, 2, 28).and_hms(8, 0, 0)));
+        assert_eq!(
+            entry.remind_at,
+            Some(Utc.ymd(2025, 2, 27).and_hms(20, 0, 0))
+        );
+        for _ in 0..3 {
+            assert!(entry.roll_forward());
+        }
+        assert_eq!(entry.due_on, Some(Utc.ymd(2028, 2, 29).and_hms(8, 0, 0)));
+    }
+
+    #[test]
+    fn keeps_an_entry_without_reminder_or_recurrence() {
+        let due_on = Utc.ymd(2024, 3, 1).and_hms(8, 0, 0);
+
+        let mut without_reminder = recurring_entry(Some("FREQ=DAILY;INTERVAL=2"), due_on, None);
+        assert!(without_reminder.roll_forward());
+        assert_eq!(without_reminder.due_on, Some(due_on + Duration::days(2)));
+        assert_eq!(without_reminder.remind_at, None);
+
+        let mut not_recurring = recurring_entry(None, due_on, Some(due_on));
+        assert!(!not_recurring.roll_forward());
+        assert_eq!(not_recurring.due_on, Some(due_on));
+        assert_eq!(not_recurring.next_occurrence, None);
+    }
+
+    #[test]
+    fn upcoming_filter_survives_a_delay_beyond_the_last_date() {
+        let now = Utc.ymd(2024, 3, 1).and_hms(8, 0, 0);
+        let entry = recurring_entry(None, now + Duration::days(400), None);
+
+        assert!(DueFilter::Upcoming(Duration::max_value()).matches(&entry, now));
+        assert!(!DueFilter::Upcoming(Duration::days(30)).matches(&entry, now));
+        assert!(!DueFilter::Overdue.matches(&entry, now));
+    }
+
+    #[test]
+    fn sends_the_reminder_again_on_the_next_occurrence() {
+        let due_on = Utc.ymd(2024, 3, 4).and_hms(9, 0, 0);
+        let mut entry = recurring_entry(
+            Some("FREQ=WEEKLY"),
+            due_on,
+            Some(due_on - Duration::minutes(30)),
+        );
+        entry.reminded_on = Some(due_on - Duration::minutes(30));
+
+        assert!(entry.roll_forward());
+        assert_eq!(
+            entry.remind_at,
+            Some(Utc.ymd(2024, 3, 11).and_hms(8, 30, 0))
+        );
+        assert_eq!(entry.reminded_on, None);
+    }
 }
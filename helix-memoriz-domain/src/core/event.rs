@@ -0,0 +1,96 @@
+use chrono::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+use uuid;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangedItem {
+    Entry,
+    Board,
+    Label,
+}
+
+//Change made through the domain, pushed to the connected clients.
+//Clients fetch the item again: the event only tells what changed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub kind: ChangeKind,
+    pub item: ChangedItem,
+    //Uuid of an entry or a board, id of a label.
+    pub id: String,
+    pub owner: uuid::Uuid,
+    pub occurred_on: DateTime<Utc>,
+}
+
+impl ChangeEvent {
+    pub fn new(
+        kind: ChangeKind,
+        item: ChangedItem,
+        id: String,
+        owner: uuid::Uuid,
+        occurred_on: DateTime<Utc>,
+    ) -> ChangeEvent {
+        ChangeEvent {
+            kind: kind,
+            item: item,
+            id: id,
+            owner: owner,
+            occurred_on: occurred_on,
+        }
+    }
+}
+
+impl fmt::Display for ChangeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChangeKind::Created => write!(f, "created"),
+            ChangeKind::Updated => write!(f, "updated"),
+            ChangeKind::Deleted => write!(f, "deleted"),
+        }
+    }
+}
+
+impl FromStr for ChangeKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "created" => Ok(ChangeKind::Created),
+            "updated" => Ok(ChangeKind::Updated),
+            "deleted" => Ok(ChangeKind::Deleted),
+            _ => Err(format!("Unknown change kind: {}", value)),
+        }
+    }
+}
+
+impl fmt::Display for ChangedItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ChangedItem::Entry => write!(f, "entry"),
+            ChangedItem::Board => write!(f, "board"),
+            ChangedItem::Label => write!(f, "label"),
+        }
+    }
+}
+
+impl FromStr for ChangedItem {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "entry" => Ok(ChangedItem::Entry),
+            "board" => Ok(ChangedItem::Board),
+            "label" => Ok(ChangedItem::Label),
+            _ => Err(format!("Unknown changed item: {}", value)),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphNodeKind {
+    Entry,
+    Board,
+    Label,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphEdgeKind {
+    Board,
+    Label,
+    Reference,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub kind: GraphNodeKind,
+    pub title: String,
+    pub color: Option<String>,
+}
+
+impl GraphNode {
+    pub fn new(id: String, kind: GraphNodeKind, title: String, color: Option<String>) -> GraphNode {
+        GraphNode {
+            id: id,
+            kind: kind,
+            title: title,
+            color: color,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub kind: GraphEdgeKind,
+}
+
+impl GraphEdge {
+    pub fn new(source: String, target: String, kind: GraphEdgeKind) -> GraphEdge {
+        GraphEdge {
+            source: source,
+            target: target,
+            kind: kind,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl Graph {
+    pub fn new(nodes: Vec<GraphNode>, edges: Vec<GraphEdge>) -> Graph {
+        Graph {
+            nodes: nodes,
+            edges: edges,
+        }
+    }
+}
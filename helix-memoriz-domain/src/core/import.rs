@@ -0,0 +1,20 @@
+//Summary of an import, returned to the user.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub source: String,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub boards_created: usize,
+    pub labels_created: usize,
+    pub warnings: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn new(source: String) -> ImportReport {
+        ImportReport {
+            source: source,
+            ..Default::default()
+        }
+    }
+}
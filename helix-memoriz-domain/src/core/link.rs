@@ -0,0 +1,26 @@
+use uuid;
+
+//Wiki-style "[[Title]]" reference from an entry to another one.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntryLink {
+    pub source: uuid::Uuid,
+    pub target_title: String,
+    pub target: Option<uuid::Uuid>,
+    pub owner: uuid::Uuid,
+}
+
+impl EntryLink {
+    pub fn new(
+        source: uuid::Uuid,
+        target_title: String,
+        target: Option<uuid::Uuid>,
+        owner: uuid::Uuid,
+    ) -> EntryLink {
+        EntryLink {
+            source: source,
+            target_title: target_title,
+            target: target,
+            owner: owner,
+        }
+    }
+}
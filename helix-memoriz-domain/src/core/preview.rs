@@ -0,0 +1,39 @@
+use chrono::prelude::*;
+
+//OpenGraph metadata of a URL found in the content of the entries, cached for
+//all users. A URL waiting to be fetched has no "fetched_on", a URL which could
+//not be fetched has no metadata: it is not fetched again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkPreview {
+    pub url: String,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub image: Option<String>,
+    pub site_name: Option<String>,
+    pub fetched_on: Option<DateTime<Utc>>,
+}
+
+impl LinkPreview {
+    pub fn new(
+        url: String,
+        title: Option<String>,
+        description: Option<String>,
+        image: Option<String>,
+        site_name: Option<String>,
+        fetched_on: Option<DateTime<Utc>>,
+    ) -> LinkPreview {
+        LinkPreview {
+            url: url,
+            title: title,
+            description: description,
+            image: image,
+            site_name: site_name,
+            fetched_on: fetched_on,
+        }
+    }
+
+    //Nothing to show: not fetched yet, or without metadata.
+    pub fn is_empty(&self) -> bool {
+        self.title.is_none() && self.description.is_none() && self.image.is_none()
+    }
+}
@@ -0,0 +1,384 @@
+use chrono::prelude::*;
+use chrono::Duration;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+//Far enough for any schedule, and the dates it gives stay in the range of chrono.
+pub const MAX_INTERVAL: u32 = 1000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+//RRULE-style recurrence, serialized as "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE".
+//Monthly and yearly rules keep their day in BYMONTHDAY, the day of the first due date:
+//a rule on the 31st ends the shorter months on their last day and comes back to the 31st.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Recurrence {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub by_weekday: Vec<Weekday>,
+    pub by_month_day: Option<u32>,
+}
+
+impl Recurrence {
+    pub fn new(
+        frequency: Frequency,
+        interval: u32,
+        by_weekday: Vec<Weekday>,
+        by_month_day: Option<u32>,
+    ) -> Recurrence {
+        Recurrence {
+            frequency: frequency,
+            interval: interval.clamp(1, MAX_INTERVAL),
+            by_weekday: by_weekday,
+            by_month_day: by_month_day,
+        }
+    }
+
+    //Day the monthly and yearly occurrences come back to, taken from the first due date.
+    pub fn anchor(&mut self, due_on: DateTime<Utc>) {
+        match self.frequency {
+            Frequency::Monthly | Frequency::Yearly if self.by_month_day.is_none() => {
+                self.by_month_day = Some(due_on.day())
+            }
+            _ => {}
+        }
+    }
+
+    //Compute the first occurrence strictly after the given one,
+    //None when it is beyond the dates chrono can hold.
+    pub fn next_occurrence(&self, from: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let interval = self.interval.clamp(1, MAX_INTERVAL);
+        match self.frequency {
+            Frequency::Daily => from.checked_add_signed(Duration::days(interval as i64)),
+            Frequency::Weekly => self.next_weekly_occurrence(from, interval),
+            Frequency::Monthly => add_months(from, interval, self.by_month_day),
+            Frequency::Yearly => add_months(from, interval * 12, self.by_month_day),
+        }
+    }
+
+    fn next_weekly_occurrence(&self, from: DateTime<Utc>, interval: u32) -> Option<DateTime<Utc>> {
+        if self.by_weekday.is_empty() {
+            return from.checked_add_signed(Duration::weeks(interval as i64));
+        }
+
+        //Remaining days of the current week first...
+        let days_from_monday = from.weekday().num_days_from_monday() as i64;
+        for offset in 1..(7 - days_from_monday) {
+            let candidate = from.checked_add_signed(Duration::days(offset))?;
+            if self.by_weekday.contains(&candidate.weekday()) {
+                return Some(candidate);
+            }
+        }
+
+        //...then the first matching day of the next week in the interval.
+        let next_week_start = from
+            .checked_sub_signed(Duration::days(days_from_monday))?
+            .checked_add_signed(Duration::weeks(interval as i64))?;
+        (0..7)
+            .filter_map(|offset| next_week_start.checked_add_signed(Duration::days(offset)))
+            .find(|candidate| self.by_weekday.contains(&candidate.weekday()))
+    }
+}
+
+fn add_months(date: DateTime<Utc>, months: u32, day: Option<u32>) -> Option<DateTime<Utc>> {
+    let total_months = date.year() * 12 + date.month0() as i32 + months as i32;
+    let year = total_months / 12;
+    let month = (total_months % 12) as u32 + 1;
+
+    //The 31st becomes the last day of shorter months.
+    let day = day
+        .unwrap_or_else(|| date.day())
+        .min(days_in_month(year, month)?);
+    Utc.ymd_opt(year, month, day)
+        .single()?
+        .and_time(date.time())
+}
+
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let (next_year, next_month) = match month {
+        12 => (year + 1, 1),
+        _ => (year, month + 1),
+    };
+    Some(
+        NaiveDate::from_ymd_opt(next_year, next_month, 1)?
+            .pred_opt()?
+            .day(),
+    )
+}
+
+fn weekday_to_rrule(weekday: &Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+fn weekday_from_rrule(value: &str) -> Result<Weekday, String> {
+    match value {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(format!("Unknown weekday: {}", value)),
+    }
+}
+
+impl FromStr for Recurrence {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let rule = value.trim().trim_start_matches("RRULE:");
+        let mut frequency: Option<Frequency> = None;
+        let mut interval: u32 = 1;
+        let mut by_weekday: Vec<Weekday> = Vec::new();
+        let mut by_month_day: Option<u32> = None;
+
+        for part in rule.split(';').filter(|part| !part.is_empty()) {
+            let mut key_value = part.splitn(2, '=');
+            let key = key_value.next().unwrap_or("").to_uppercase();
+            let value = key_value.next().unwrap_or("").to_uppercase();
+
+            match key.as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => return Err(format!("Unsupported frequency: {}", value)),
+                    })
+                }
+                "INTERVAL" => {
+                    interval = value
+                        .parse()
+                        .map_err(|_| format!("Invalid interval: {}", value))?;
+                    if interval > MAX_INTERVAL {
+                        return Err(format!("Interval above {}: {}", MAX_INTERVAL, value));
+                    }
+                }
+                "BYDAY" => {
+                    for day in value.split(',') {
+                        by_weekday.push(weekday_from_rrule(day)?);
+                    }
+                }
+                "BYMONTHDAY" => {
+                    by_month_day = match value.parse() {
+                        Ok(day) if (1..=31).contains(&day) => Some(day),
+                        _ => return Err(format!("Invalid month day: {}", value)),
+                    }
+                }
+                _ => return Err(format!("Unsupported rule part: {}", key)),
+            }
+        }
+
+        match frequency {
+            Some(frequency) => Ok(Recurrence::new(
+                frequency,
+                interval,
+                by_weekday,
+                by_month_day,
+            )),
+            None => Err("Missing FREQ".to_string()),
+        }
+    }
+}
+
+impl fmt::Display for Recurrence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={};INTERVAL={}", frequency, self.interval)?;
+
+        if !self.by_weekday.is_empty() {
+            let days: Vec<&str> = self.by_weekday.iter().map(weekday_to_rrule).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if let Some(day) = self.by_month_day {
+            write!(f, ";BYMONTHDAY={}", day)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Recurrence {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Recurrence::from_str(&value)
+    }
+}
+
+impl From<Recurrence> for String {
+    fn from(recurrence: Recurrence) -> Self {
+        recurrence.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32) -> DateTime<Utc> {
+        Utc.ymd(year, month, day).and_hms(9, 30, 0)
+    }
+
+    fn rule(value: &str) -> Recurrence {
+        Recurrence::from_str(value).unwrap()
+    }
+
+    #[test]
+    fn ends_the_month_on_its_last_day() {
+        let monthly = rule("FREQ=MONTHLY");
+
+        assert_eq!(
+            monthly.next_occurrence(at(2023, 1, 31)),
+            Some(at(2023, 2, 28))
+        );
+        assert_eq!(
+            monthly.next_occurrence(at(2024, 1, 31)),
+            Some(at(2024, 2, 29))
+        );
+        assert_eq!(
+            monthly.next_occurrence(at(2024, 3, 31)),
+            Some(at(2024, 4, 30))
+        );
+        assert_eq!(
+            monthly.next_occurrence(at(2024, 12, 31)),
+            Some(at(2025, 1, 31))
+        );
+    }
+
+    #[test]
+    fn moves_the_leap_day_to_the_end_of_february() {
+        let yearly = rule("FREQ=YEARLY");
+
+        assert_eq!(
+            yearly.next_occurrence(at(2024, 2, 29)),
+            Some(at(2025, 2, 28))
+        );
+        assert_eq!(
+            rule("FREQ=YEARLY;INTERVAL=4").next_occurrence(at(2024, 2, 29)),
+            Some(at(2028, 2, 29))
+        );
+    }
+
+    #[test]
+    fn skips_the_interval() {
+        assert_eq!(
+            rule("FREQ=DAILY;INTERVAL=3").next_occurrence(at(2024, 2, 27)),
+            Some(at(2024, 3, 1))
+        );
+        assert_eq!(
+            rule("FREQ=WEEKLY;INTERVAL=2").next_occurrence(at(2024, 12, 25)),
+            Some(at(2025, 1, 8))
+        );
+        assert_eq!(
+            rule("FREQ=MONTHLY;INTERVAL=14").next_occurrence(at(2024, 11, 30)),
+            Some(at(2026, 1, 30))
+        );
+    }
+
+    #[test]
+    fn follows_the_weekdays_of_the_week() {
+        let weekly = rule("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE");
+
+        //Monday 29 January 2024: Wednesday of the same week, then Monday two weeks later.
+        assert_eq!(
+            weekly.next_occurrence(at(2024, 1, 29)),
+            Some(at(2024, 1, 31))
+        );
+        assert_eq!(
+            weekly.next_occurrence(at(2024, 1, 31)),
+            Some(at(2024, 2, 12))
+        );
+        //Sunday 29 December 2024: the end of the week has no Monday, the next week has.
+        assert_eq!(
+            rule("FREQ=WEEKLY;BYDAY=MO").next_occurrence(at(2024, 12, 29)),
+            Some(at(2024, 12, 30))
+        );
+    }
+
+    #[test]
+    fn writes_back_the_rule_it_parsed() {
+        let weekly = rule("RRULE:freq=weekly;interval=2;byday=mo,we");
+
+        assert_eq!(weekly.interval, 2);
+        assert_eq!(weekly.by_weekday, vec![Weekday::Mon, Weekday::Wed]);
+        assert_eq!(weekly.to_string(), "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE");
+        assert_eq!(rule("FREQ=DAILY;INTERVAL=0").interval, 1);
+        assert!(Recurrence::from_str("INTERVAL=2").is_err());
+        assert!(Recurrence::from_str("FREQ=HOURLY").is_err());
+        assert!(Recurrence::from_str("FREQ=WEEKLY;BYDAY=XX").is_err());
+    }
+
+    #[test]
+    fn refuses_intervals_too_large() {
+        assert!(Recurrence::from_str("FREQ=YEARLY;INTERVAL=4294967295").is_err());
+        assert!(Recurrence::from_str("FREQ=DAILY;INTERVAL=1001").is_err());
+        assert_eq!(rule("FREQ=DAILY;INTERVAL=1000").interval, MAX_INTERVAL);
+        assert_eq!(
+            Recurrence::new(Frequency::Yearly, u32::MAX, Vec::new(), None).interval,
+            MAX_INTERVAL
+        );
+    }
+
+    #[test]
+    fn stops_at_the_last_date() {
+        let last = chrono::MAX_DATE.and_hms(9, 30, 0);
+
+        assert_eq!(rule("FREQ=DAILY").next_occurrence(last), None);
+        assert_eq!(rule("FREQ=WEEKLY;BYDAY=MO").next_occurrence(last), None);
+        assert_eq!(rule("FREQ=MONTHLY").next_occurrence(last), None);
+        assert_eq!(
+            rule("FREQ=YEARLY;INTERVAL=1000").next_occurrence(at(2024, 2, 29)),
+            Some(at(3024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn comes_back_to_the_day_of_the_rule() {
+        let monthly = rule("FREQ=MONTHLY;BYMONTHDAY=31");
+        assert_eq!(
+            monthly.next_occurrence(at(2024, 2, 29)),
+            Some(at(2024, 3, 31))
+        );
+        assert_eq!(
+            monthly.next_occurrence(at(2024, 4, 30)),
+            Some(at(2024, 5, 31))
+        );
+
+        let mut yearly = rule("FREQ=YEARLY");
+        yearly.anchor(at(2024, 2, 29));
+        assert_eq!(yearly.to_string(), "FREQ=YEARLY;INTERVAL=1;BYMONTHDAY=29");
+        assert_eq!(
+            yearly.next_occurrence(at(2027, 2, 28)),
+            Some(at(2028, 2, 29))
+        );
+
+        let mut weekly = rule("FREQ=WEEKLY");
+        weekly.anchor(at(2024, 2, 29));
+        assert_eq!(weekly.by_month_day, None);
+        assert!(Recurrence::from_str("FREQ=MONTHLY;BYMONTHDAY=32").is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use crate::core::entry::Entry;
+use chrono::prelude::*;
+use std::collections::HashMap;
+use uuid;
+
+//Part of an entry a search matched in.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchSource {
+    Body,
+    Attachment,
+}
+
+//Extract of a field of an entry around what the query matched.
+//The matched words are between <em> and </em>, the rest of the text is HTML escaped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Highlight {
+    pub field: String,
+    pub snippet: String,
+}
+
+impl Highlight {
+    pub fn new(field: String, snippet: String) -> Highlight {
+        Highlight {
+            field: field,
+            snippet: snippet,
+        }
+    }
+}
+
+//Entry found by the search engine, with where the query matched.
+//The score goes from 0 to 1, the best hits first. It comes from the position of the hit
+//in the results, not from its relevance: scores of two searches do not compare.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SearchHit {
+    pub uuid: uuid::Uuid,
+    pub sources: Vec<MatchSource>,
+    pub score: f64,
+    pub highlights: Vec<Highlight>,
+}
+
+impl SearchHit {
+    pub fn new(
+        uuid: uuid::Uuid,
+        sources: Vec<MatchSource>,
+        score: f64,
+        highlights: Vec<Highlight>,
+    ) -> SearchHit {
+        SearchHit {
+            uuid: uuid,
+            sources: sources,
+            score: score,
+            highlights: highlights,
+        }
+    }
+}
+
+//Search sent to the search engine: the text, and the filters the entries match.
+//Boards and labels are given by identifier, colors in lower case.
+//An entry has all the labels, and one of the boards and colors, if any.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchRequest {
+    pub text: String,
+    pub boards: Vec<uuid::Uuid>,
+    pub labels: Vec<String>,
+    pub colors: Vec<String>,
+    pub archived: Option<bool>,
+    pub created: Option<DateRange>,
+    pub updated: Option<DateRange>,
+    pub due: Option<DateRange>,
+    pub has_attachment: Option<bool>,
+    pub offset: usize,
+    pub limit: usize,
+}
+
+//Number of matching entries per label id, board uuid and color, over all the pages.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchFacets {
+    pub labels: HashMap<String, usize>,
+    pub boards: HashMap<String, usize>,
+    pub colors: HashMap<String, usize>,
+}
+
+//Page of hits of a search, "total" counting the hits of all the pages.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub total: usize,
+    pub hits: Vec<SearchHit>,
+    pub facets: SearchFacets,
+}
+
+//Facet value with its display name: the label name or the board title.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FacetCount {
+    pub value: String,
+    pub name: String,
+    pub count: usize,
+}
+
+impl FacetCount {
+    pub fn new(value: String, name: String, count: usize) -> FacetCount {
+        FacetCount {
+            value: value,
+            name: name,
+            count: count,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EntryFacets {
+    pub labels: Vec<FacetCount>,
+    pub boards: Vec<FacetCount>,
+    pub colors: Vec<FacetCount>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntrySearchHit {
+    pub entry: Entry,
+    pub score: f64,
+    pub highlights: Vec<Highlight>,
+}
+
+//Result of a search of the user: a page of entries, the best first, and the facets.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct EntrySearchResults {
+    pub total: usize,
+    pub offset: usize,
+    pub hits: Vec<EntrySearchHit>,
+    pub facets: EntryFacets,
+}
+
+//Days between two dates, both included. A missing bound leaves the range open.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DateRange {
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn new(from: Option<NaiveDate>, to: Option<NaiveDate>) -> DateRange {
+        DateRange { from: from, to: to }
+    }
+
+    //A missing date is never in a range.
+    pub fn contains(&self, date: Option<DateTime<Utc>>) -> bool {
+        let day = match date {
+            Some(date) => date.naive_utc().date(),
+            None => return false,
+        };
+        self.from.map(|from| day >= from).unwrap_or(true)
+            && self.to.map(|to| day <= to).unwrap_or(true)
+    }
+}
+
+//Criteria of a query like `label:work archived:false "release"`: an entry matches
+//all of them. It has all the labels, and one of the boards and colors, if any.
+//Texts, labels, boards and colors are kept in lower case.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntryQuery {
+    //Words and quoted phrases found in the title or the content.
+    pub terms: Vec<String>,
+    pub labels: Vec<String>,
+    pub boards: Vec<String>,
+    pub colors: Vec<String>,
+    pub archived: Option<bool>,
+    pub created: Option<DateRange>,
+    pub updated: Option<DateRange>,
+    pub due: Option<DateRange>,
+    pub has_attachment: Option<bool>,
+}
+
+//Query saved by a user, listed like a board of the entries it matches.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub uuid: Option<uuid::Uuid>,
+    pub name: String,
+    pub query: String,
+    pub color: Option<String>,
+    pub created_on: Option<DateTime<Utc>>,
+    pub updated_on: Option<DateTime<Utc>>,
+    pub owner: Option<uuid::Uuid>,
+}
+
+impl SavedSearch {
+    pub fn new(
+        uuid: Option<uuid::Uuid>,
+        name: String,
+        query: String,
+        color: Option<String>,
+        created_on: Option<DateTime<Utc>>,
+        updated_on: Option<DateTime<Utc>>,
+        owner: Option<uuid::Uuid>,
+    ) -> SavedSearch {
+        SavedSearch {
+            uuid: uuid,
+            name: name,
+            query: query,
+            color: color,
+            created_on: created_on,
+            updated_on: updated_on,
+            owner: owner,
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use crate::core::{board::Board, entry::Entry};
+use chrono::prelude::*;
+use std::fmt;
+use std::str::FromStr;
+use uuid;
+
+//Role granted on a shared item. Only the owner deletes or shares it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ShareRole {
+    Viewer,
+    Editor,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SharedItemKind {
+    Board,
+    Entry,
+}
+
+//Grant of a role on a board, and its entries, or on a single entry.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Share {
+    pub item: uuid::Uuid,
+    pub kind: SharedItemKind,
+    pub owner: uuid::Uuid,
+    pub recipient: uuid::Uuid,
+    pub role: ShareRole,
+    pub created_on: Option<DateTime<Utc>>,
+}
+
+impl Share {
+    pub fn new(
+        item: uuid::Uuid,
+        kind: SharedItemKind,
+        owner: uuid::Uuid,
+        recipient: uuid::Uuid,
+        role: ShareRole,
+        created_on: Option<DateTime<Utc>>,
+    ) -> Share {
+        Share {
+            item: item,
+            kind: kind,
+            owner: owner,
+            recipient: recipient,
+            role: role,
+            created_on: created_on,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedBoard {
+    pub board: Board,
+    pub role: ShareRole,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SharedEntry {
+    pub entry: Entry,
+    pub role: ShareRole,
+}
+
+//Unguessable link giving a read-only access to an item, without authentication.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PublicLink {
+    pub token: String,
+    pub item: uuid::Uuid,
+    pub kind: SharedItemKind,
+    pub owner: uuid::Uuid,
+    //Bcrypt hash of the password, never sent.
+    #[serde(skip)]
+    pub password_hash: Option<String>,
+    pub has_password: bool,
+    pub expires_on: Option<DateTime<Utc>>,
+    pub created_on: Option<DateTime<Utc>>,
+}
+
+impl PublicLink {
+    pub fn new(
+        token: String,
+        item: uuid::Uuid,
+        kind: SharedItemKind,
+        owner: uuid::Uuid,
+        password_hash: Option<String>,
+        expires_on: Option<DateTime<Utc>>,
+        created_on: Option<DateTime<Utc>>,
+    ) -> PublicLink {
+        PublicLink {
+            token: token,
+            item: item,
+            kind: kind,
+            owner: owner,
+            has_password: password_hash.is_some(),
+            password_hash: password_hash,
+            expires_on: expires_on,
+            created_on: created_on,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        match self.expires_on {
+            Some(expires_on) => expires_on <= now,
+            None => false,
+        }
+    }
+}
+
+//Content served by a public link, without the owner.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum PublicItem {
+    Board { board: Board, entries: Vec<Entry> },
+    Entry { entry: Entry },
+}
+
+//"Shared with me" section: items of other users the user has a role on.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SharedWithMe {
+    pub boards: Vec<SharedBoard>,
+    pub entries: Vec<SharedEntry>,
+}
+
+impl fmt::Display for ShareRole {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShareRole::Viewer => write!(f, "viewer"),
+            ShareRole::Editor => write!(f, "editor"),
+        }
+    }
+}
+
+impl FromStr for ShareRole {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "viewer" => Ok(ShareRole::Viewer),
+            "editor" => Ok(ShareRole::Editor),
+            _ => Err(format!("Unknown share role: {}", value)),
+        }
+    }
+}
+
+impl fmt::Display for SharedItemKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SharedItemKind::Board => write!(f, "board"),
+            SharedItemKind::Entry => write!(f, "entry"),
+        }
+    }
+}
+
+impl FromStr for SharedItemKind {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "board" => Ok(SharedItemKind::Board),
+            "entry" => Ok(SharedItemKind::Entry),
+            _ => Err(format!("Unknown shared item kind: {}", value)),
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::core::event::{ChangeKind, ChangedItem};
+use crate::core::{board::Board, entry::Entry, label::Label};
+use chrono::prelude::*;
+use uuid;
+
+//Line of the change log of a user. The sequence only grows: it is the sync cursor.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeLogEntry {
+    pub seq: i64,
+    pub user: uuid::Uuid,
+    pub owner: uuid::Uuid,
+    pub item: ChangedItem,
+    pub id: String,
+    pub kind: ChangeKind,
+    pub occurred_on: DateTime<Utc>,
+}
+
+impl ChangeLogEntry {
+    pub fn new(
+        seq: i64,
+        user: uuid::Uuid,
+        owner: uuid::Uuid,
+        item: ChangedItem,
+        id: String,
+        kind: ChangeKind,
+        occurred_on: DateTime<Utc>,
+    ) -> ChangeLogEntry {
+        ChangeLogEntry {
+            seq: seq,
+            user: user,
+            owner: owner,
+            item: item,
+            id: id,
+            kind: kind,
+            occurred_on: occurred_on,
+        }
+    }
+}
+
+//Change made offline by a client. "base" is the cursor the client last saw the item at,
+//the cursor of the request when missing: a later change of the item is a conflict.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum SyncMutation {
+    PutEntry { entry: Entry, base: Option<i64> },
+    DeleteEntry { uuid: uuid::Uuid, base: Option<i64> },
+    PutBoard { board: Board, base: Option<i64> },
+    DeleteBoard { uuid: uuid::Uuid, base: Option<i64> },
+}
+
+impl SyncMutation {
+    pub fn item_id(&self) -> Option<String> {
+        match self {
+            SyncMutation::PutEntry { entry, .. } => entry.uuid.map(|uuid| uuid.to_string()),
+            SyncMutation::PutBoard { board, .. } => board.uuid.map(|uuid| uuid.to_string()),
+            SyncMutation::DeleteEntry { uuid, .. } | SyncMutation::DeleteBoard { uuid, .. } => {
+                Some(uuid.to_string())
+            }
+        }
+    }
+}
+
+//No cursor asks for every item of the user.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SyncRequest {
+    pub cursor: Option<i64>,
+    #[serde(default)]
+    pub mutations: Vec<SyncMutation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MutationStatus {
+    Applied,
+    Conflict,
+    Rejected,
+}
+
+//Outcome of the mutation at "index" in the request.
+//A created item gets a new uuid: "client_id" is the one the client gave it.
+//A conflict gives the current item, none when it was deleted.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MutationResult {
+    pub index: usize,
+    pub status: MutationStatus,
+    pub id: Option<String>,
+    pub client_id: Option<String>,
+    pub message: Option<String>,
+    pub entry: Option<Entry>,
+    pub board: Option<Board>,
+}
+
+impl MutationResult {
+    pub fn new(index: usize, status: MutationStatus, id: Option<String>) -> MutationResult {
+        MutationResult {
+            index: index,
+            status: status,
+            id: id,
+            client_id: None,
+            message: None,
+            entry: None,
+            board: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeletedItem {
+    pub item: ChangedItem,
+    pub id: String,
+}
+
+//Items changed since the cursor of the request, as they are now.
+//"has_more" asks for another sync from the new cursor.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncResponse {
+    pub cursor: i64,
+    pub has_more: bool,
+    pub entries: Vec<Entry>,
+    pub boards: Vec<Board>,
+    pub labels: Vec<Label>,
+    pub deleted: Vec<DeletedItem>,
+    pub results: Vec<MutationResult>,
+}
@@ -0,0 +1,66 @@
+use chrono::prelude::*;
+use std::collections::HashMap;
+use uuid;
+
+//Layout of entries created again and again, like meeting notes. The title, the content
+//and the checklist items hold placeholders, "{{date}}", "{{board}}" or "{{user}}",
+//expanded when an entry is created from the template.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Template {
+    pub uuid: Option<uuid::Uuid>,
+    pub name: String,
+    pub title: String,
+    pub content: Option<String>,
+    pub color: Option<String>,
+    //Identifiers of labels of the owner.
+    #[serde(default)]
+    pub labels: Vec<String>,
+    pub board: Option<uuid::Uuid>,
+    #[serde(default)]
+    pub checklist: Vec<String>,
+    pub created_on: Option<DateTime<Utc>>,
+    pub updated_on: Option<DateTime<Utc>>,
+    pub owner: Option<uuid::Uuid>,
+}
+
+impl Template {
+    pub fn new(
+        uuid: Option<uuid::Uuid>,
+        name: String,
+        title: String,
+        content: Option<String>,
+        color: Option<String>,
+        labels: Vec<String>,
+        board: Option<uuid::Uuid>,
+        checklist: Vec<String>,
+        created_on: Option<DateTime<Utc>>,
+        updated_on: Option<DateTime<Utc>>,
+        owner: Option<uuid::Uuid>,
+    ) -> Template {
+        Template {
+            uuid: uuid,
+            name: name,
+            title: title,
+            content: content,
+            color: color,
+            labels: labels,
+            board: board,
+            checklist: checklist,
+            created_on: created_on,
+            updated_on: updated_on,
+            owner: owner,
+        }
+    }
+}
+
+//Entry to create from a template. The board replaces the one of the template.
+//"date" is the day of the user, today in UTC otherwise, "user" is their name,
+//their uuid otherwise, and "values" fills the other placeholders.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TemplateEntryRequest {
+    pub board: Option<uuid::Uuid>,
+    pub date: Option<NaiveDate>,
+    pub user: Option<String>,
+    #[serde(default)]
+    pub values: HashMap<String, String>,
+}
@@ -0,0 +1,3 @@
+pub mod error;
+pub mod static_link_fetcher;
+pub mod traits;
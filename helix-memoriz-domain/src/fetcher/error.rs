@@ -0,0 +1,20 @@
+use thiserror::Error;
+
+//Define the possible errors
+#[derive(Error, Debug)]
+pub enum FetcherError {
+    #[error("NotImplemented")]
+    NotImplemented,
+    #[error("Page not found: {0}")]
+    NotFound(String),
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+    #[error("Forbidden address: {0}")]
+    ForbiddenAddress(String),
+    #[error("Not an HTML page: {0}")]
+    NotHtml(String),
+    #[error("Request failed: {0}")]
+    Request(String),
+}
+
+pub type FetcherResult<T> = std::result::Result<T, FetcherError>;
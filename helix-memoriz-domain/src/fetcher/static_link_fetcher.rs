@@ -0,0 +1,26 @@
+use crate::fetcher::error::{FetcherError, FetcherResult};
+use crate::fetcher::traits::LinkFetcherTrait;
+use async_trait::async_trait;
+use std::collections::HashMap;
+
+//Fetcher serving known pages without any request: for tests, and for servers
+//not allowed to reach the network, with no page at all.
+pub struct StaticLinkFetcher {
+    pages: HashMap<String, String>,
+}
+
+impl StaticLinkFetcher {
+    pub fn new(pages: HashMap<String, String>) -> Self {
+        StaticLinkFetcher { pages: pages }
+    }
+}
+
+#[async_trait]
+impl LinkFetcherTrait for StaticLinkFetcher {
+    async fn fetch_page(&self, url: &str) -> FetcherResult<String> {
+        match self.pages.get(url) {
+            Some(page) => Ok(page.to_owned()),
+            None => Err(FetcherError::NotFound(url.to_string())),
+        }
+    }
+}
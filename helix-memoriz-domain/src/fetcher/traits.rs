@@ -0,0 +1,9 @@
+use crate::fetcher::error::*;
+use async_trait::async_trait;
+
+//Fetches the pages of the URLs found in the entries, for their link previews.
+#[async_trait]
+pub trait LinkFetcherTrait: Send + Sync {
+    //HTML of the page, or its beginning for a big one: the metadata is in the head.
+    async fn fetch_page(&self, url: &str) -> FetcherResult<String>;
+}
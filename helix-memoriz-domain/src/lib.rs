@@ -1,6 +1,9 @@
 #[macro_use]
 extern crate serde_derive;
 
+pub mod blob;
 pub mod business;
 pub mod core;
+pub mod fetcher;
+pub mod notifier;
 pub mod storage;
@@ -0,0 +1,3 @@
+pub mod error;
+pub mod log_notifier;
+pub mod traits;
@@ -0,0 +1,14 @@
+use thiserror::Error;
+
+//Define the possible errors
+#[derive(Error, Debug)]
+pub enum NotifierError {
+    #[error("NotImplemented")]
+    NotImplemented,
+    #[error("Missing recipient")]
+    MissingRecipient,
+    #[error("Delivery failed: {0}")]
+    DeliveryFailed(String),
+}
+
+pub type NotifierResult<T> = std::result::Result<T, NotifierError>;
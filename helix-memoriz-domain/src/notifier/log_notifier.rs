@@ -0,0 +1,24 @@
+use crate::core::entry::Entry;
+use crate::notifier::error::NotifierResult;
+use crate::notifier::traits::NotifierTrait;
+use async_trait::async_trait;
+
+//Notifier only writing the reminders on the standard output.
+pub struct LogNotifier {}
+
+impl LogNotifier {
+    pub fn new() -> Self {
+        LogNotifier {}
+    }
+}
+
+#[async_trait]
+impl NotifierTrait for LogNotifier {
+    async fn notify_reminder(&self, entry: &Entry) -> NotifierResult<()> {
+        println!(
+            "REMINDER {:?} [{}] owner:{:?} due_on:{:?}",
+            entry.uuid, entry.title, entry.owner, entry.due_on
+        );
+        Ok(())
+    }
+}
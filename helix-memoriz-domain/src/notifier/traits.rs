@@ -0,0 +1,15 @@
+use crate::core::entry::*;
+use crate::core::event::*;
+use crate::notifier::error::*;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait NotifierTrait: Send + Sync {
+    async fn notify_reminder(&self, entry: &Entry) -> NotifierResult<()>;
+}
+
+//Receives the changes made through the domain. Publishing must not block:
+//a slow or disconnected client never delays a change.
+pub trait EventPublisherTrait: Send + Sync {
+    fn publish(&self, recipients: &[uuid::Uuid], event: &ChangeEvent);
+}
@@ -1,6 +1,10 @@
-use crate::core::{board::*, entry::*, label::*};
+use crate::core::{
+    attachment::*, board::*, dump::CalendarToken, entry::*, event::*, label::*, link::*,
+    preview::*, search::*, share::*, sync::*, template::*,
+};
 use crate::storage::error::*;
 use async_trait::async_trait;
+use chrono::prelude::*;
 
 #[async_trait]
 pub trait StorageTrait: Send + Sync {
@@ -14,6 +18,22 @@ pub trait StorageTrait: Send + Sync {
     async fn create_label(&self, label: Label) -> StorageResult<Label>;
     async fn update_label(&self, label: Label) -> StorageResult<Label>;
     async fn delete_label(&self, label: &Label) -> StorageResult<()>;
+    async fn get_owner_labels(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Label>>;
+    async fn add_entry_label(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        label_id: String,
+    ) -> StorageResult<()>;
+    async fn get_all_entries_labels(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<(uuid::Uuid, Label)>>;
+    async fn get_entries_labels(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entries_uuid: Vec<uuid::Uuid>,
+    ) -> StorageResult<Vec<(uuid::Uuid, Label)>>;
 
     async fn create_entry(&self, entry: Entry) -> StorageResult<Entry>;
     async fn update_entry(&self, entry: Entry) -> StorageResult<Entry>;
@@ -21,6 +41,14 @@ pub trait StorageTrait: Send + Sync {
     async fn get_entry(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<Entry>;
 
     async fn get_all_entries(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Entry>>;
+    async fn get_owner_entries(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Entry>>;
+    //Entries ordered by uuid, starting after the given one.
+    async fn get_owner_entries_page(
+        &self,
+        owner_uuid: uuid::Uuid,
+        after_uuid: Option<uuid::Uuid>,
+        limit: i64,
+    ) -> StorageResult<Vec<Entry>>;
 
     async fn get_entries_by_ids(
         &self,
@@ -33,14 +61,223 @@ pub trait StorageTrait: Send + Sync {
         owner_uuid: uuid::Uuid,
         board_uuid: uuid::Uuid,
     ) -> StorageResult<Vec<Entry>>;
+
+    async fn get_entry_by_import_id(
+        &self,
+        owner_uuid: uuid::Uuid,
+        source: String,
+        source_id: String,
+    ) -> StorageResult<Option<Entry>>;
+
+    async fn get_entry_by_title(
+        &self,
+        owner_uuid: uuid::Uuid,
+        title: String,
+    ) -> StorageResult<Option<Entry>>;
+
+    async fn replace_entry_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        source_uuid: uuid::Uuid,
+        links: Vec<EntryLink>,
+    ) -> StorageResult<()>;
+    async fn get_entry_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        source_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<EntryLink>>;
+    async fn get_entry_backlinks(
+        &self,
+        owner_uuid: uuid::Uuid,
+        target_uuid: uuid::Uuid,
+        target_title: String,
+    ) -> StorageResult<Vec<EntryLink>>;
+
+    async fn get_entries_to_remind(&self, until: DateTime<Utc>) -> StorageResult<Vec<Entry>>;
+
+    async fn get_all_entries_with_due_date(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Entry>>;
+
+    async fn set_calendar_token(&self, owner_uuid: uuid::Uuid, token: String) -> StorageResult<()>;
+    async fn get_calendar_token_owner(&self, token: String) -> StorageResult<Option<uuid::Uuid>>;
+    async fn get_calendar_token(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Option<CalendarToken>>;
+    async fn restore_calendar_token(&self, token: CalendarToken) -> StorageResult<()>;
+    async fn restore_public_link(&self, link: PublicLink) -> StorageResult<()>;
+    async fn restore_attachment(&self, attachment: &Attachment) -> StorageResult<()>;
+
+    async fn upsert_share(&self, share: Share) -> StorageResult<Share>;
+    async fn delete_share(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+        recipient_uuid: uuid::Uuid,
+    ) -> StorageResult<()>;
+    async fn delete_item_shares(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<()>;
+    async fn get_item_shares(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Share>>;
+    async fn get_recipient_shares(&self, recipient_uuid: uuid::Uuid) -> StorageResult<Vec<Share>>;
+    //Shares of the recipient on the entry itself or on its board.
+    async fn get_recipient_entry_shares(
+        &self,
+        recipient_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Share>>;
+    async fn create_public_link(&self, link: PublicLink) -> StorageResult<PublicLink>;
+    async fn get_public_link(&self, token: String) -> StorageResult<Option<PublicLink>>;
+    async fn get_item_public_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<PublicLink>>;
+    async fn delete_public_link(&self, owner_uuid: uuid::Uuid, token: String) -> StorageResult<()>;
+    async fn delete_item_public_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<()>;
+    async fn append_change(
+        &self,
+        users_uuid: &[uuid::Uuid],
+        event: &ChangeEvent,
+    ) -> StorageResult<()>;
+    async fn get_changes(
+        &self,
+        user_uuid: uuid::Uuid,
+        since: i64,
+        limit: i64,
+    ) -> StorageResult<Vec<ChangeLogEntry>>;
+    async fn restore_change(&self, change: ChangeLogEntry) -> StorageResult<()>;
+    async fn get_last_change_seq(&self, user_uuid: uuid::Uuid) -> StorageResult<i64>;
+    async fn get_item_last_change_seq(
+        &self,
+        user_uuid: uuid::Uuid,
+        id: String,
+    ) -> StorageResult<Option<i64>>;
+    async fn get_entry_document(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Option<Vec<u8>>>;
+    async fn save_entry_document(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        document: Vec<u8>,
+    ) -> StorageResult<()>;
+    async fn delete_entry_document(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<()>;
+    async fn create_attachment(&self, attachment: Attachment) -> StorageResult<Attachment>;
+    async fn get_attachment(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<Option<Attachment>>;
+    async fn get_entry_attachments(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Attachment>>;
+    async fn get_owner_attachments(&self, owner_uuid: uuid::Uuid)
+        -> StorageResult<Vec<Attachment>>;
+    //Attachments not processed yet, of any owner, the least tried then the oldest first.
+    //Attachments failed max_attempts times, or since failed_before, are left out.
+    async fn get_unprocessed_attachments(
+        &self,
+        limit: i64,
+        max_attempts: i32,
+        failed_before: DateTime<Utc>,
+    ) -> StorageResult<Vec<Attachment>>;
+    async fn record_attachment_failure(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<()>;
+    async fn update_processed_attachment(&self, attachment: &Attachment) -> StorageResult<()>;
+    async fn save_attachment_text(
+        &self,
+        attachment: &Attachment,
+        text: String,
+    ) -> StorageResult<()>;
+    //Extracted text of the attachments of an entry, by attachment.
+    async fn get_entry_attachment_texts(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<(uuid::Uuid, String)>>;
+    async fn delete_attachment(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<()>;
+    async fn delete_entry_attachments(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<()>;
+    //URLs not known yet wait for their preview, the others are left as they are.
+    async fn queue_link_previews(&self, urls: Vec<String>) -> StorageResult<()>;
+    //Oldest URLs waiting for their preview.
+    async fn get_pending_link_previews(&self, limit: i64) -> StorageResult<Vec<String>>;
+    async fn save_link_preview(&self, preview: &LinkPreview) -> StorageResult<()>;
+    async fn get_link_previews(&self, urls: Vec<String>) -> StorageResult<Vec<LinkPreview>>;
+    async fn create_template(&self, template: Template) -> StorageResult<Template>;
+    async fn update_template(&self, template: Template) -> StorageResult<Template>;
+    async fn get_template(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<Option<Template>>;
+    async fn get_all_templates(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Template>>;
+    async fn delete_template(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<()>;
+    async fn create_saved_search(&self, saved_search: SavedSearch) -> StorageResult<SavedSearch>;
+    async fn update_saved_search(&self, saved_search: SavedSearch) -> StorageResult<SavedSearch>;
+    async fn get_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<Option<SavedSearch>>;
+    async fn get_all_saved_searches(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<SavedSearch>>;
+    async fn delete_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<()>;
+
+    //Dump and restore: records are written as they are, keeping uuids and timestamps.
+    async fn get_all_owners(&self) -> StorageResult<Vec<uuid::Uuid>>;
+    async fn restore_board(&self, board: Board) -> StorageResult<()>;
+    async fn restore_label(&self, label: Label) -> StorageResult<()>;
+    async fn restore_entry(&self, entry: Entry) -> StorageResult<()>;
+    async fn restore_template(&self, template: Template) -> StorageResult<()>;
+    async fn restore_saved_search(&self, saved_search: SavedSearch) -> StorageResult<()>;
 }
 
 #[async_trait]
 pub trait SearchEngineTrait: Send + Sync {
+    //The entry comes with its attachments, and the text extracted from them.
     async fn index_entry(&self, entry: &Entry) -> SearchEngineResult<()>;
     async fn search_entries(
         &self,
         owner_uuid: uuid::Uuid,
-        query: String,
-    ) -> SearchEngineResult<Vec<uuid::Uuid>>;
+        request: SearchRequest,
+    ) -> SearchEngineResult<SearchResponse>;
+    async fn delete_entry(&self, uuid: uuid::Uuid) -> SearchEngineResult<()>;
 }
@@ -0,0 +1,209 @@
+use async_trait::async_trait;
+use helix_memoriz_domain::core::entry::*;
+use helix_memoriz_domain::notifier::error::*;
+use helix_memoriz_domain::notifier::traits::NotifierTrait;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio02Executor};
+
+//Key of Entry.data used to override the default recipient.
+const RECIPIENT_DATA_KEY: &str = "reminder_email";
+
+pub struct SmtpNotifier {
+    from: String,
+    default_recipient: Option<String>,
+    transport: AsyncSmtpTransport<Tokio02Executor>,
+}
+
+impl SmtpNotifier {
+    pub fn new(
+        host: String,
+        port: u16,
+        tls: bool,
+        credentials: Option<(String, String)>,
+        from: String,
+        default_recipient: Option<String>,
+    ) -> NotifierResult<SmtpNotifier> {
+        //Without TLS, the transport can target a local SMTP stand-in (mailhog, smtp4dev...).
+        //The transport is asynchronous: a slow server never blocks the runtime of the API.
+        let mut builder = match tls {
+            true => AsyncSmtpTransport::<Tokio02Executor>::relay(&host)
+                .map_err(|e| NotifierError::DeliveryFailed(e.to_string()))?,
+            false => AsyncSmtpTransport::<Tokio02Executor>::builder_dangerous(&host),
+        }
+        .port(port);
+
+        if let Some((user, password)) = credentials {
+            builder = builder.credentials(Credentials::new(user, password));
+        }
+
+        Ok(SmtpNotifier {
+            from,
+            default_recipient,
+            transport: builder.build(),
+        })
+    }
+
+    fn get_recipient(&self, entry: &Entry) -> NotifierResult<String> {
+        let data_recipient = entry
+            .data
+            .as_ref()
+            .and_then(|data| data.get(RECIPIENT_DATA_KEY))
+            .and_then(|value| value.as_str())
+            .map(|value| value.to_string());
+
+        match data_recipient.or_else(|| self.default_recipient.clone()) {
+            Some(recipient) => Ok(recipient),
+            None => Err(NotifierError::MissingRecipient),
+        }
+    }
+}
+
+#[async_trait]
+impl NotifierTrait for SmtpNotifier {
+    async fn notify_reminder(&self, entry: &Entry) -> NotifierResult<()> {
+        let recipient = self.get_recipient(entry)?;
+
+        let mut body = entry.content.clone().unwrap_or_default();
+        if let Some(due_on) = entry.due_on {
+            body = format!("Due on: {}\n\n{}", due_on.to_rfc2822(), body);
+        }
+
+        let email = Message::builder()
+            .from(
+                self.from
+                    .parse()
+                    .map_err(|_| NotifierError::DeliveryFailed(self.from.to_owned()))?,
+            )
+            .to(recipient
+                .parse()
+                .map_err(|_| NotifierError::DeliveryFailed(recipient.to_owned()))?)
+            .subject(format!("[Memoriz] Reminder: {}", entry.title))
+            .body(body)
+            .map_err(|e| NotifierError::DeliveryFailed(e.to_string()))?;
+
+        self.transport
+            .send(email)
+            .await
+            .map_err(|e| NotifierError::DeliveryFailed(e.to_string()))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::thread;
+
+    //Local SMTP stand-in accepting one message, sent back through the channel.
+    fn start_smtp_stand_in() -> (u16, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut writer = stream.try_clone().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut reply = |line: &str| writer.write_all(line.as_bytes()).unwrap();
+            reply("220 localhost SMTP stand-in\r\n");
+
+            let mut message = String::new();
+            let mut in_data = false;
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 {
+                if in_data {
+                    if line == ".\r\n" {
+                        in_data = false;
+                        reply("250 OK\r\n");
+                    } else {
+                        message.push_str(&line);
+                    }
+                } else {
+                    let command = line.to_uppercase();
+                    if command.starts_with("EHLO") || command.starts_with("HELO") {
+                        reply("250 localhost\r\n");
+                    } else if command.starts_with("DATA") {
+                        in_data = true;
+                        reply("354 End data with <CR><LF>.<CR><LF>\r\n");
+                    } else if command.starts_with("QUIT") {
+                        reply("221 Bye\r\n");
+                        break;
+                    } else {
+                        reply("250 OK\r\n");
+                    }
+                }
+                line.clear();
+            }
+            sender.send(message).unwrap();
+        });
+
+        (port, receiver)
+    }
+
+    fn entry(data: Option<serde_json::Value>) -> Entry {
+        Entry::new(
+            0,
+            Some(uuid::Uuid::new_v4()),
+            "Call the plumber".to_string(),
+            Some("Before the weekend.".to_string()),
+            data,
+            None,
+            false,
+            None,
+            None,
+            Some(uuid::Uuid::new_v4()),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    fn notifier(port: u16, default_recipient: Option<String>) -> SmtpNotifier {
+        SmtpNotifier::new(
+            "127.0.0.1".to_string(),
+            port,
+            false,
+            None,
+            "memoriz@localhost".to_string(),
+            default_recipient,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn sends_the_reminder_to_the_smtp_server() {
+        let (port, receiver) = start_smtp_stand_in();
+        let notifier = notifier(port, Some("default@localhost".to_string()));
+        let entry = entry(Some(
+            serde_json::json!({ "reminder_email": "me@localhost" }),
+        ));
+
+        notifier.notify_reminder(&entry).await.unwrap();
+        //Dropping the transport closes a connection it would keep open.
+        drop(notifier);
+
+        let message = receiver.recv().unwrap();
+        assert!(message.contains("Subject: [Memoriz] Reminder: Call the plumber"));
+        assert!(message.contains("To: me@localhost"));
+        assert!(message.contains("Before the weekend."));
+    }
+
+    #[tokio::test]
+    async fn fails_without_recipient() {
+        //Refused before any connection: no server is needed.
+        let notifier = notifier(25, None);
+
+        match notifier.notify_reminder(&entry(None)).await {
+            Err(NotifierError::MissingRecipient) => (),
+            other => panic!("Unexpected result: {:?}", other),
+        }
+    }
+}
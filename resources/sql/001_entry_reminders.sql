@@ -0,0 +1,5 @@
+-- Reminders and due dates on entries.
+ALTER TABLE memoriz.entry ADD COLUMN IF NOT EXISTS due_on timestamp with time zone NULL;
+ALTER TABLE memoriz.entry ADD COLUMN IF NOT EXISTS remind_at timestamp with time zone NULL;
+
+CREATE INDEX IF NOT EXISTS entry_remind_at_idx ON memoriz.entry (remind_at) WHERE remind_at IS NOT NULL;
@@ -0,0 +1,3 @@
+-- Recurring entries: RRULE-style rule and computed next occurrence.
+ALTER TABLE memoriz.entry ADD COLUMN IF NOT EXISTS recurrence text NULL;
+ALTER TABLE memoriz.entry ADD COLUMN IF NOT EXISTS next_occurrence timestamp with time zone NULL;
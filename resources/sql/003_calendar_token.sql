@@ -0,0 +1,6 @@
+-- Per-user token protecting the iCalendar feed.
+CREATE TABLE IF NOT EXISTS memoriz.calendar_token (
+    owner_ uuid NOT NULL PRIMARY KEY,
+    token varchar(64) NOT NULL UNIQUE,
+    created_on timestamp with time zone NOT NULL
+);
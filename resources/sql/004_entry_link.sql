@@ -0,0 +1,10 @@
+-- Wiki-style links between entries, parsed from the content.
+CREATE TABLE IF NOT EXISTS memoriz.entry_link (
+    source_ uuid NOT NULL REFERENCES memoriz.entry (uuid) ON DELETE CASCADE,
+    target_title text NOT NULL,
+    target_ uuid NULL REFERENCES memoriz.entry (uuid) ON DELETE SET NULL,
+    owner_ uuid NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS entry_link_source_idx ON memoriz.entry_link (source_);
+CREATE INDEX IF NOT EXISTS entry_link_target_idx ON memoriz.entry_link (target_);
@@ -0,0 +1,7 @@
+-- Association between entries and labels.
+CREATE TABLE IF NOT EXISTS memoriz.entry_label (
+    entry_ uuid NOT NULL REFERENCES memoriz.entry (uuid) ON DELETE CASCADE,
+    label_ varchar NOT NULL REFERENCES memoriz.label (id) ON DELETE CASCADE,
+    owner_ uuid NOT NULL,
+    PRIMARY KEY (entry_, label_)
+);
@@ -0,0 +1,3 @@
+-- Lookup of imported entries by origin, for idempotent imports.
+CREATE INDEX IF NOT EXISTS entry_import_idx
+    ON memoriz.entry ((data->'import'->>'source'), (data->'import'->>'id'));
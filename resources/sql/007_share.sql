@@ -0,0 +1,12 @@
+-- Roles granted by an owner on a board or an entry to another user.
+CREATE TABLE IF NOT EXISTS memoriz.share (
+    item_ uuid NOT NULL,
+    kind varchar(16) NOT NULL,
+    owner_ uuid NOT NULL,
+    recipient_ uuid NOT NULL,
+    role varchar(16) NOT NULL,
+    created_on timestamp with time zone NOT NULL,
+    PRIMARY KEY (item_, recipient_)
+);
+
+CREATE INDEX IF NOT EXISTS share_recipient_idx ON memoriz.share (recipient_);
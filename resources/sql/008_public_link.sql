@@ -0,0 +1,12 @@
+-- Unguessable read-only links to a board or an entry, served without authentication.
+CREATE TABLE IF NOT EXISTS memoriz.public_link (
+    token varchar(64) NOT NULL PRIMARY KEY,
+    item_ uuid NOT NULL,
+    kind varchar(16) NOT NULL,
+    owner_ uuid NOT NULL,
+    password_hash varchar(128),
+    expires_on timestamp with time zone,
+    created_on timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS public_link_item_idx ON memoriz.public_link (owner_, item_);
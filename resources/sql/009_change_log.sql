@@ -0,0 +1,14 @@
+-- Changes of the items each user reaches, read by the sync of their clients.
+-- The sequence only grows: unlike updated_on, clients cannot set it.
+CREATE TABLE IF NOT EXISTS memoriz.change_log (
+    seq bigserial NOT NULL PRIMARY KEY,
+    user_ uuid NOT NULL,
+    owner_ uuid NOT NULL,
+    item varchar(16) NOT NULL,
+    item_id varchar(64) NOT NULL,
+    kind varchar(16) NOT NULL,
+    occurred_on timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS change_log_user_idx ON memoriz.change_log (user_, seq);
+CREATE INDEX IF NOT EXISTS change_log_item_idx ON memoriz.change_log (user_, item_id, seq);
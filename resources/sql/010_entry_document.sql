@@ -0,0 +1,8 @@
+-- Automerge document of the content of entries edited collaboratively.
+-- entry.content stays a plain copy of its text, for search and listings.
+CREATE TABLE IF NOT EXISTS memoriz.entry_document (
+    entry_ uuid NOT NULL PRIMARY KEY,
+    owner_ uuid NOT NULL,
+    document bytea NOT NULL,
+    updated_on timestamp with time zone NOT NULL
+);
@@ -0,0 +1,12 @@
+-- Files attached to entries. Their content is in the blob store, under "owner_/uuid".
+CREATE TABLE IF NOT EXISTS memoriz.attachment (
+    uuid uuid NOT NULL PRIMARY KEY,
+    entry_ uuid NOT NULL,
+    owner_ uuid NOT NULL,
+    file_name varchar(255) NOT NULL,
+    content_type varchar(128) NOT NULL,
+    size bigint NOT NULL,
+    created_on timestamp with time zone NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS attachment_entry_idx ON memoriz.attachment (owner_, entry_);
@@ -0,0 +1,13 @@
+-- Size and thumbnails of image attachments, filled in the background.
+-- Attachments with nothing to process get processed_on on upload.
+ALTER TABLE memoriz.attachment ADD COLUMN IF NOT EXISTS width integer;
+ALTER TABLE memoriz.attachment ADD COLUMN IF NOT EXISTS height integer;
+ALTER TABLE memoriz.attachment ADD COLUMN IF NOT EXISTS content_hash varchar(64);
+ALTER TABLE memoriz.attachment ADD COLUMN IF NOT EXISTS thumbnail_sizes integer[] NOT NULL DEFAULT '{}';
+ALTER TABLE memoriz.attachment ADD COLUMN IF NOT EXISTS processed_on timestamp with time zone;
+
+UPDATE memoriz.attachment SET processed_on = created_on
+WHERE processed_on IS NULL AND content_type NOT LIKE 'image/%';
+
+CREATE INDEX IF NOT EXISTS attachment_unprocessed_idx ON memoriz.attachment (created_on)
+WHERE processed_on IS NULL;
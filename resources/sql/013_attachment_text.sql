@@ -0,0 +1,13 @@
+-- Text extracted from PDF and text attachments, sent to the search engine with their entry.
+CREATE TABLE IF NOT EXISTS memoriz.attachment_text (
+    attachment_ uuid NOT NULL PRIMARY KEY REFERENCES memoriz.attachment (uuid) ON DELETE CASCADE,
+    entry_ uuid NOT NULL,
+    owner_ uuid NOT NULL,
+    text text NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS attachment_text_entry_idx ON memoriz.attachment_text (owner_, entry_);
+
+-- Documents uploaded before are processed again to extract their text.
+UPDATE memoriz.attachment SET processed_on = NULL
+WHERE content_type = 'application/pdf' OR content_type LIKE 'text/%';
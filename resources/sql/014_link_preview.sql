@@ -0,0 +1,14 @@
+-- Previews of the URLs found in the content of the entries, shared by all users.
+-- A URL without "fetched_on" waits for the link preview processor.
+CREATE TABLE IF NOT EXISTS memoriz.link_preview (
+    url text NOT NULL PRIMARY KEY,
+    title text,
+    description text,
+    image text,
+    site_name text,
+    queued_on timestamp with time zone NOT NULL,
+    fetched_on timestamp with time zone
+);
+
+CREATE INDEX IF NOT EXISTS link_preview_pending_idx ON memoriz.link_preview (queued_on)
+WHERE fetched_on IS NULL;
@@ -0,0 +1,16 @@
+-- Layouts of entries, with placeholders expanded when an entry is created from them.
+CREATE TABLE IF NOT EXISTS memoriz.template (
+    uuid uuid NOT NULL PRIMARY KEY,
+    owner_ uuid NOT NULL,
+    name varchar(255) NOT NULL,
+    title text NOT NULL,
+    content text,
+    color varchar(32),
+    labels text[] NOT NULL DEFAULT '{}',
+    board_ uuid,
+    checklist text[] NOT NULL DEFAULT '{}',
+    created_on timestamp with time zone NOT NULL,
+    updated_on timestamp with time zone
+);
+
+CREATE INDEX IF NOT EXISTS template_owner_idx ON memoriz.template (owner_);
@@ -0,0 +1,12 @@
+-- Queries saved by the users, listed like boards of the entries they match.
+CREATE TABLE IF NOT EXISTS memoriz.saved_search (
+    uuid uuid NOT NULL PRIMARY KEY,
+    owner_ uuid NOT NULL,
+    name varchar(255) NOT NULL,
+    query text NOT NULL,
+    color varchar(32),
+    created_on timestamp with time zone NOT NULL,
+    updated_on timestamp with time zone
+);
+
+CREATE INDEX IF NOT EXISTS saved_search_owner_idx ON memoriz.saved_search (owner_);
@@ -0,0 +1,9 @@
+-- Failed processing attempts of the attachments: a failing attachment is retried
+-- later and given up after a few attempts, instead of holding the queue.
+ALTER TABLE memoriz.attachment ADD COLUMN IF NOT EXISTS processing_attempts integer NOT NULL DEFAULT 0;
+ALTER TABLE memoriz.attachment ADD COLUMN IF NOT EXISTS failed_on timestamp with time zone;
+
+DROP INDEX IF EXISTS memoriz.attachment_unprocessed_idx;
+CREATE INDEX IF NOT EXISTS attachment_unprocessed_idx
+ON memoriz.attachment (processing_attempts, created_on)
+WHERE processed_on IS NULL;
@@ -0,0 +1,8 @@
+-- Reminders already sent: the reminder date stays, so a recurring entry
+-- can move it with its next occurrence.
+ALTER TABLE memoriz.entry ADD COLUMN IF NOT EXISTS reminded_on timestamp with time zone NULL;
+
+DROP INDEX IF EXISTS memoriz.entry_remind_at_idx;
+CREATE INDEX IF NOT EXISTS entry_remind_at_idx
+ON memoriz.entry (remind_at)
+WHERE remind_at IS NOT NULL AND reminded_on IS NULL;
@@ -2,27 +2,82 @@
 extern crate serde_derive;
 
 use async_trait::async_trait;
-use helix_memoriz_domain::core::{board::*, entry::*, label::*};
+use helix_memoriz_domain::business::links::title_key;
+use helix_memoriz_domain::core::{board::*, entry::*, label::*, search::*};
 use helix_memoriz_domain::storage::error::*;
 use helix_memoriz_domain::storage::traits::SearchEngineTrait;
-use meilisearch_sdk::{client::*, document::*, search::*};
+use meilisearch_sdk::{client::*, document::*, indexes::*, search::*};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use uuid;
 
+//Attribute holding the names and text of the attachments of an entry.
+const ATTACHMENTS_ATTRIBUTE: &str = "attachments";
+//Attributes the facets are counted on, set on the index before its first use.
+const FACET_ATTRIBUTES: [&str; 3] = ["labels", "board", "color"];
+const HIGHLIGHT_ATTRIBUTES: [&str; 3] = ["title", "content", ATTACHMENTS_ATTRIBUTE];
+//Words around the matches kept in the snippets of the long attributes.
+const SNIPPET_LENGTH: usize = 20;
+
+//The filters only rely on the attributes: dates are timestamps, in seconds.
 #[derive(Serialize, Deserialize, Debug)]
 struct IndexedEntry {
     uuid: uuid::Uuid,
     title: String,
     content: String,
     owner_uuid: uuid::Uuid,
+    #[serde(default)]
+    attachments: String,
+    #[serde(default)]
+    board: Option<String>,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    color: Option<String>,
+    #[serde(default)]
+    archived: bool,
+    #[serde(default)]
+    created_on: Option<i64>,
+    #[serde(default)]
+    updated_on: Option<i64>,
+    #[serde(default)]
+    due_on: Option<i64>,
+    #[serde(default)]
+    has_attachment: bool,
 }
 
 impl IndexedEntry {
     fn from_entry(entry: &Entry) -> IndexedEntry {
+        //One attribute for all the documents: a match only has to tell it is in one of them.
+        let attachments: Vec<String> = entry
+            .attachments
+            .iter()
+            .flatten()
+            .map(|attachment| match &attachment.text {
+                Some(text) => format!("{}\n{}", attachment.file_name, text),
+                None => attachment.file_name.to_owned(),
+            })
+            .collect();
+
         IndexedEntry {
             uuid: entry.uuid.unwrap(),
             title: entry.title.to_owned(),
-            content: entry.content.as_ref().unwrap().to_owned(),
+            content: entry.content.to_owned().unwrap_or_default(),
             owner_uuid: entry.owner.unwrap(),
+            has_attachment: !attachments.is_empty(),
+            attachments: attachments.join("\n\n"),
+            board: entry.board.map(|board| board.to_string()),
+            labels: entry
+                .labels
+                .iter()
+                .flatten()
+                .map(|label| label.id.to_owned())
+                .collect(),
+            color: entry.color.as_deref().map(title_key),
+            archived: entry.archived,
+            created_on: entry.created_on.map(|date| date.timestamp()),
+            updated_on: entry.updated_on.map(|date| date.timestamp()),
+            due_on: entry.due_on.map(|date| date.timestamp()),
         }
     }
 }
@@ -38,6 +93,7 @@ impl Document for IndexedEntry {
 pub struct MsMemorizSearchEngine {
     index: String,
     client: Client,
+    faceting_set: AtomicBool,
 }
 
 impl MsMemorizSearchEngine {
@@ -51,39 +107,210 @@ impl MsMemorizSearchEngine {
         Ok(MsMemorizSearchEngine {
             index: index,
             client: Client::new(url, token),
+            faceting_set: AtomicBool::new(false),
         })
     }
+
+    async fn get_index(&self) -> SearchEngineResult<Index<'_>> {
+        let index = self.client.get_or_create(&self.index).await?;
+        if !self.faceting_set.load(Ordering::SeqCst) {
+            index.set_attributes_for_faceting(&FACET_ATTRIBUTES).await?;
+            self.faceting_set.store(true, Ordering::SeqCst);
+        }
+        Ok(index)
+    }
+}
+
+//Filter expression of the owner and of the criteria on values.
+fn search_filters(owner_uuid: uuid::Uuid, request: &SearchRequest) -> String {
+    let mut filters: Vec<String> = vec![format!("owner_uuid = \"{}\"", owner_uuid)];
+    if let Some(archived) = request.archived {
+        filters.push(format!("archived = {}", archived));
+    }
+    if let Some(has_attachment) = request.has_attachment {
+        filters.push(format!("has_attachment = {}", has_attachment));
+    }
+    for (attribute, range) in &[
+        ("created_on", request.created),
+        ("updated_on", request.updated),
+        ("due_on", request.due),
+    ] {
+        let range = match range {
+            Some(range) => range,
+            None => continue,
+        };
+        if let Some(from) = range.from {
+            filters.push(format!(
+                "{} >= {}",
+                attribute,
+                from.and_hms(0, 0, 0).timestamp()
+            ));
+        }
+        //The last day is included: until the start of the next one, if there is one.
+        if let Some(next_day) = range.to.and_then(|to| to.succ_opt()) {
+            filters.push(format!(
+                "{} < {}",
+                attribute,
+                next_day.and_hms(0, 0, 0).timestamp()
+            ));
+        }
+    }
+    filters.join(" AND ")
+}
+
+//Facet filters: every label, one of the boards and one of the colors.
+fn search_facet_filters(request: &SearchRequest) -> Vec<Vec<String>> {
+    let mut facet_filters: Vec<Vec<String>> = request
+        .labels
+        .iter()
+        .map(|label| vec![format!("labels:{}", label)])
+        .collect();
+    if !request.boards.is_empty() {
+        facet_filters.push(
+            request
+                .boards
+                .iter()
+                .map(|board| format!("board:{}", board))
+                .collect(),
+        );
+    }
+    if !request.colors.is_empty() {
+        facet_filters.push(
+            request
+                .colors
+                .iter()
+                .map(|color| format!("color:{}", color))
+                .collect(),
+        );
+    }
+    facet_filters
+}
+
+//Meilisearch puts the matches between <em> and </em> without escaping the text around.
+fn escape_snippet(formatted: &str) -> String {
+    formatted
+        .replace("<em>", "\u{E000}")
+        .replace("</em>", "\u{E001}")
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\u{E000}', "<em>")
+        .replace('\u{E001}', "</em>")
 }
 
 #[async_trait]
 impl SearchEngineTrait for MsMemorizSearchEngine {
     async fn index_entry(&self, entry: &Entry) -> SearchEngineResult<()> {
-        let index = self.client.get_or_create(&self.index).await?;
+        let index = self.get_index().await?;
         index
             .add_documents(&[IndexedEntry::from_entry(entry)], Some("uuid"))
             .await?;
         Ok(())
     }
 
+    async fn delete_entry(&self, uuid: uuid::Uuid) -> SearchEngineResult<()> {
+        let index = self.get_index().await?;
+        index.delete_document(uuid).await?;
+        Ok(())
+    }
+
     async fn search_entries(
         &self,
         owner_uuid: uuid::Uuid,
-        query: String,
-    ) -> SearchEngineResult<Vec<uuid::Uuid>> {
-        let index = self.client.get_or_create(&self.index).await?;
-        let search_results = index
-            .search()
-            .with_query(&query)
-            .execute::<IndexedEntry>()
-            .await
-            .unwrap()
-            .hits;
-
-        let mut ret: Vec<uuid::Uuid> = Vec::new();
-        for search_result in search_results {
-            ret.push(search_result.result.uuid);
+        request: SearchRequest,
+    ) -> SearchEngineResult<SearchResponse> {
+        let index = self.get_index().await?;
+
+        let filters = search_filters(owner_uuid, &request);
+        let facet_filters = search_facet_filters(&request);
+        let facet_filters: Vec<Vec<&str>> = facet_filters
+            .iter()
+            .map(|group| group.iter().map(|filter| filter.as_str()).collect())
+            .collect();
+        let facet_filters: Vec<&[&str]> =
+            facet_filters.iter().map(|group| group.as_slice()).collect();
+        let attributes_to_crop = [
+            ("content", Some(SNIPPET_LENGTH)),
+            (ATTACHMENTS_ATTRIBUTE, Some(SNIPPET_LENGTH)),
+        ];
+
+        let mut query = index.search();
+        query
+            .with_query(&request.text)
+            .with_offset(request.offset)
+            .with_limit(request.limit)
+            .with_filters(&filters)
+            .with_facets_distribution(Selectors::Some(&FACET_ATTRIBUTES[..]))
+            .with_attributes_to_highlight(Selectors::Some(&HIGHLIGHT_ATTRIBUTES[..]))
+            .with_attributes_to_crop(Selectors::Some(&attributes_to_crop[..]))
+            .with_matches(true);
+        if !facet_filters.is_empty() {
+            query.with_facet_filters(&facet_filters);
+        }
+        let results = query.execute::<IndexedEntry>().await?;
+
+        //Meilisearch ranks the hits without giving a score: it is derived from the rank,
+        //the position of the hit among all of them, and tells nothing of its relevance.
+        let total = results.nb_hits;
+        let mut hits: Vec<SearchHit> = Vec::new();
+        for (position, search_result) in results.hits.into_iter().enumerate() {
+            let score = 1.0 - (request.offset + position) as f64 / total.max(1) as f64;
+
+            //Without match details, the hit can only come from the body.
+            let mut sources: Vec<MatchSource> = Vec::new();
+            let mut highlights: Vec<Highlight> = Vec::new();
+            for attribute in search_result.matches_info.iter().flat_map(|m| m.keys()) {
+                let source = match attribute.as_str() {
+                    ATTACHMENTS_ATTRIBUTE => MatchSource::Attachment,
+                    _ => MatchSource::Body,
+                };
+                if !sources.contains(&source) {
+                    sources.push(source);
+                }
+
+                let formatted = search_result
+                    .formatted_result
+                    .as_ref()
+                    .and_then(|formatted| formatted.get(attribute))
+                    .and_then(|value| value.as_str());
+                if let Some(formatted) = formatted {
+                    highlights.push(Highlight::new(
+                        attribute.to_owned(),
+                        escape_snippet(formatted),
+                    ));
+                }
+            }
+            if sources.is_empty() {
+                sources.push(MatchSource::Body);
+            }
+            //Attributes come in no particular order.
+            highlights.sort_by_key(|highlight| {
+                HIGHLIGHT_ATTRIBUTES
+                    .iter()
+                    .position(|attribute| *attribute == highlight.field)
+            });
+
+            hits.push(SearchHit::new(
+                search_result.result.uuid,
+                sources,
+                score,
+                highlights,
+            ));
         }
 
-        Ok(ret)
+        let mut distribution = results.facets_distribution.unwrap_or_default();
+        let mut facet = |attribute: &str| -> HashMap<String, usize> {
+            distribution.remove(attribute).unwrap_or_default()
+        };
+        Ok(SearchResponse {
+            total: total,
+            hits: hits,
+            facets: SearchFacets {
+                labels: facet("labels"),
+                boards: facet("board"),
+                colors: facet("color"),
+            },
+        })
     }
 }
@@ -1,10 +1,15 @@
 use async_trait::async_trait;
 use chrono::prelude::*;
 use deadpool_postgres::{Config, ManagerConfig, Pool, RecyclingMethod};
-use helix_memoriz_domain::core::{board::*, entry::*, label::*};
+use helix_memoriz_domain::core::{
+    attachment::*, board::*, dump::CalendarToken, entry::*, event::*, label::*, link::*,
+    preview::*, recurrence::*, search::*, share::*, sync::*, template::*,
+};
 use helix_memoriz_domain::storage::error::*;
 use helix_memoriz_domain::storage::traits::StorageTrait;
+use std::str::FromStr;
 use tokio_postgres::tls::NoTls;
+use tokio_postgres::Row;
 use uuid;
 
 pub struct PgDbMemorizStorage {
@@ -33,6 +38,140 @@ impl PgDbMemorizStorage {
             pool: cfg.create_pool(NoTls).unwrap(),
         })
     }
+
+    fn entry_from_row(row: &Row) -> Entry {
+        Entry::new(
+            row.get("id"),
+            row.get("uuid"),
+            row.get("title"),
+            row.get("content"),
+            row.get("data"),
+            row.get("color"),
+            row.get("archived"),
+            row.get("created_on"),
+            row.get("updated_on"),
+            row.get("owner_"),
+            None,
+            row.get("board_"),
+            row.get("due_on"),
+            row.get("remind_at"),
+            row.get("reminded_on"),
+            row.get::<_, Option<String>>("recurrence")
+                .and_then(|recurrence| Recurrence::from_str(&recurrence).ok()),
+            row.get("next_occurrence"),
+        )
+    }
+
+    fn share_from_row(row: &Row) -> StorageResult<Share> {
+        let kind =
+            SharedItemKind::from_str(row.get("kind")).map_err(|_| StorageError::AnotherError)?;
+        let role = ShareRole::from_str(row.get("role")).map_err(|_| StorageError::AnotherError)?;
+
+        Ok(Share::new(
+            row.get("item_"),
+            kind,
+            row.get("owner_"),
+            row.get("recipient_"),
+            role,
+            row.get("created_on"),
+        ))
+    }
+
+    fn public_link_from_row(row: &Row) -> StorageResult<PublicLink> {
+        let kind =
+            SharedItemKind::from_str(row.get("kind")).map_err(|_| StorageError::AnotherError)?;
+
+        Ok(PublicLink::new(
+            row.get("token"),
+            row.get("item_"),
+            kind,
+            row.get("owner_"),
+            row.get("password_hash"),
+            row.get("expires_on"),
+            row.get("created_on"),
+        ))
+    }
+
+    fn attachment_from_row(row: &Row) -> Attachment {
+        Attachment::new(
+            row.get("uuid"),
+            row.get("entry_"),
+            row.get("owner_"),
+            row.get("file_name"),
+            row.get("content_type"),
+            row.get("size"),
+            row.get("created_on"),
+            row.get("width"),
+            row.get("height"),
+            row.get("content_hash"),
+            row.get("thumbnail_sizes"),
+            row.get("processed_on"),
+        )
+    }
+
+    fn link_preview_from_row(row: &Row) -> LinkPreview {
+        LinkPreview::new(
+            row.get("url"),
+            row.get("title"),
+            row.get("description"),
+            row.get("image"),
+            row.get("site_name"),
+            row.get("fetched_on"),
+        )
+    }
+
+    fn saved_search_from_row(row: &Row) -> SavedSearch {
+        SavedSearch::new(
+            row.get("uuid"),
+            row.get("name"),
+            row.get("query"),
+            row.get("color"),
+            row.get("created_on"),
+            row.get("updated_on"),
+            row.get("owner_"),
+        )
+    }
+
+    fn template_from_row(row: &Row) -> Template {
+        Template::new(
+            row.get("uuid"),
+            row.get("name"),
+            row.get("title"),
+            row.get("content"),
+            row.get("color"),
+            row.get("labels"),
+            row.get("board_"),
+            row.get("checklist"),
+            row.get("created_on"),
+            row.get("updated_on"),
+            row.get("owner_"),
+        )
+    }
+
+    fn change_from_row(row: &Row) -> StorageResult<ChangeLogEntry> {
+        let item =
+            ChangedItem::from_str(row.get("item")).map_err(|_| StorageError::AnotherError)?;
+        let kind = ChangeKind::from_str(row.get("kind")).map_err(|_| StorageError::AnotherError)?;
+
+        Ok(ChangeLogEntry::new(
+            row.get("seq"),
+            row.get("user_"),
+            row.get("owner_"),
+            item,
+            row.get("item_id"),
+            kind,
+            row.get("occurred_on"),
+        ))
+    }
+
+    fn entry_link_from_row(row: &Row) -> EntryLink {
+        EntryLink::new(
+            row.get("source_"),
+            row.get("target_title"),
+            row.get("target_"),
+            row.get("owner_"),
+        )
+    }
 }
 
 #[async_trait]
@@ -60,7 +199,7 @@ impl StorageTrait for PgDbMemorizStorage {
             .await?;
 
         let row_data = row_inserted.iter().next().unwrap();
-        board.uuid = row_data.get("id");
+        board.uuid = row_data.get("uuid");
         Ok(board)
     }
 
@@ -90,7 +229,7 @@ impl StorageTrait for PgDbMemorizStorage {
         Ok(board)
     }
 
-    async fn get_board(&self, uuid: uuid::Uuid, owner_uuid: uuid::Uuid) -> StorageResult<Board> {
+    async fn get_board(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<Board> {
         let mut result: StorageResult<Board> = Err(StorageError::AnotherError);
 
         let query = "
@@ -102,7 +241,7 @@ impl StorageTrait for PgDbMemorizStorage {
 
         let client = self.pool.get().await.unwrap();
 
-        for row in &client.query(query, &[&uuid, &owner_uuid]).await? {
+        for row in &client.query(query, &[&owner_uuid, &uuid]).await? {
             result = Ok(Board::new(
                 row.get("uuid"),
                 row.get("title"),
@@ -146,7 +285,7 @@ impl StorageTrait for PgDbMemorizStorage {
         Ok(result)
     }
 
-    async fn delete_board(&self, uuid: uuid::Uuid, owner_uuid: uuid::Uuid) -> StorageResult<()> {
+    async fn delete_board(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<()> {
         let query = "DELETE FROM memoriz.board WHERE UUID = $1 AND owner_=$2;";
         let client = self.pool.get().await.unwrap();
         &client.execute(query, &[&uuid, &owner_uuid]).await?;
@@ -156,12 +295,15 @@ impl StorageTrait for PgDbMemorizStorage {
     async fn create_label(&self, label: Label) -> StorageResult<Label> {
         let query = "
         INSERT INTO memoriz.label
-        VALUES ($1,$2,$3);";
+        VALUES ($1,$2,$3,$4);";
 
         let client = self.pool.get().await.unwrap();
 
         client
-            .query(query, &[&label.id, &label.name, &label.description])
+            .query(
+                query,
+                &[&label.id, &label.name, &label.description, &label.owner],
+            )
             .await?;
 
         Ok(label)
@@ -214,12 +356,112 @@ impl StorageTrait for PgDbMemorizStorage {
         Ok(result)
     }
 
+    async fn get_owner_labels(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Label>> {
+        let mut result: Vec<Label> = Vec::new();
+
+        let query = "
+        select *
+        from memoriz.label
+        where label.owner = $1
+        order by name;
+        ";
+
+        let client = self.pool.get().await.unwrap();
+
+        for row in client.query(query, &[&owner_uuid]).await? {
+            result.push(Label::new(
+                row.get("id"),
+                row.get("name"),
+                row.get("description"),
+                row.get("owner"),
+            ));
+        }
+
+        Ok(result)
+    }
+
+    async fn add_entry_label(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        label_id: String,
+    ) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.entry_label
+        VALUES ($1,$2,$3)
+        ON CONFLICT DO NOTHING;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(query, &[&entry_uuid, &label_id, &owner_uuid])
+            .await?;
+        Ok(())
+    }
+
+    async fn get_all_entries_labels(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<(uuid::Uuid, Label)>> {
+        let mut result: Vec<(uuid::Uuid, Label)> = Vec::new();
+
+        let query = "
+        select entry_label.entry_, label.id, label.name, label.description, entry_label.owner_
+        from memoriz.entry_label
+        join memoriz.label on label.id = entry_label.label_
+        where entry_label.owner_ = $1;";
+
+        let client = self.pool.get().await.unwrap();
+
+        for row in client.query(query, &[&owner_uuid]).await? {
+            let label = Label::new(
+                row.get("id"),
+                row.get("name"),
+                row.get("description"),
+                row.get("owner_"),
+            );
+            result.push((row.get("entry_"), label));
+        }
+
+        Ok(result)
+    }
+
+    async fn get_entries_labels(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entries_uuid: Vec<uuid::Uuid>,
+    ) -> StorageResult<Vec<(uuid::Uuid, Label)>> {
+        let mut result: Vec<(uuid::Uuid, Label)> = Vec::new();
+
+        let query = "
+        select entry_label.entry_, label.id, label.name, label.description, entry_label.owner_
+        from memoriz.entry_label
+        join memoriz.label on label.id = entry_label.label_
+        where entry_label.owner_ = $1
+        and entry_label.entry_ = any($2);";
+
+        let client = self.pool.get().await.unwrap();
+
+        for row in client.query(query, &[&owner_uuid, &entries_uuid]).await? {
+            let label = Label::new(
+                row.get("id"),
+                row.get("name"),
+                row.get("description"),
+                row.get("owner_"),
+            );
+            result.push((row.get("entry_"), label));
+        }
+
+        Ok(result)
+    }
+
     async fn create_entry(&self, mut entry: Entry) -> StorageResult<Entry> {
-        entry.created_on = Some(Utc::now());
+        //Imported entries keep their original timestamps.
+        entry.created_on = entry.created_on.or_else(|| Some(Utc::now()));
+        let recurrence = entry.recurrence.as_ref().map(|r| r.to_string());
 
         let query = "
         INSERT INTO memoriz.entry
-        VALUES (DEFAULT,DEFAULT,$1,$2,$3,$4,false,$5,NULL,$6,$7)
+        VALUES (DEFAULT,DEFAULT,$1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14)
         RETURNING id, uuid;";
 
         let client = self.pool.get().await.unwrap();
@@ -232,9 +474,16 @@ impl StorageTrait for PgDbMemorizStorage {
                     &entry.content,
                     &entry.data,
                     &entry.color,
+                    &entry.archived,
                     &entry.created_on,
+                    &entry.updated_on,
                     &entry.owner,
                     &entry.board,
+                    &entry.due_on,
+                    &entry.remind_at,
+                    &recurrence,
+                    &entry.next_occurrence,
+                    &entry.reminded_on,
                 ],
             )
             .await?;
@@ -248,10 +497,11 @@ impl StorageTrait for PgDbMemorizStorage {
 
     async fn update_entry(&self, mut entry: Entry) -> StorageResult<Entry> {
         entry.updated_on = Some(Utc::now());
+        let recurrence = entry.recurrence.as_ref().map(|r| r.to_string());
 
         let query = "
-        UPDATE memoriz.entry SET (title, content, data, color, archived, updated_on, board_) 
-        = ($2,$3,$4,$5,$6,$7,$8)
+        UPDATE memoriz.entry SET (title, content, data, color, archived, updated_on, board_, due_on, remind_at, recurrence, next_occurrence, reminded_on) 
+        = ($2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13)
         WHERE ID = $1;";
 
         let client = self.pool.get().await.unwrap();
@@ -268,6 +518,11 @@ impl StorageTrait for PgDbMemorizStorage {
                     &entry.archived,
                     &entry.updated_on,
                     &entry.board,
+                    &entry.due_on,
+                    &entry.remind_at,
+                    &recurrence,
+                    &entry.next_occurrence,
+                    &entry.reminded_on,
                 ],
             )
             .await?;
@@ -277,14 +532,14 @@ impl StorageTrait for PgDbMemorizStorage {
         Ok(entry)
     }
 
-    async fn delete_entry(&self, uuid: uuid::Uuid, owner_uuid: uuid::Uuid) -> StorageResult<()> {
+    async fn delete_entry(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<()> {
         let query = "DELETE FROM memoriz.entry WHERE UUID = $1 AND owner_=$2;";
         let client = self.pool.get().await.unwrap();
         client.execute(query, &[&uuid, &owner_uuid]).await?;
         Ok(())
     }
 
-    async fn get_entry(&self, uuid: uuid::Uuid, owner_uuid: uuid::Uuid) -> StorageResult<Entry> {
+    async fn get_entry(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<Entry> {
         let mut result: StorageResult<Entry> = Err(StorageError::AnotherError);
 
         let query = "
@@ -297,20 +552,7 @@ impl StorageTrait for PgDbMemorizStorage {
         let client = self.pool.get().await.unwrap();
 
         for row in &client.query(query, &[&uuid, &owner_uuid]).await? {
-            result = Ok(Entry::new(
-                row.get("id"),
-                row.get("uuid"),
-                row.get("title"),
-                row.get("content"),
-                row.get("data"),
-                row.get("color"),
-                row.get("archived"),
-                row.get("created_on"),
-                row.get("updated_on"),
-                row.get("owner_"),
-                None,
-                row.get("board_"),
-            ));
+            result = Ok(PgDbMemorizStorage::entry_from_row(row));
         }
 
         result
@@ -329,20 +571,7 @@ impl StorageTrait for PgDbMemorizStorage {
         let client = self.pool.get().await.unwrap();
 
         for row in client.query(query, &[&owner_uuid]).await? {
-            let entry: Entry = Entry::new(
-                row.get("id"),
-                row.get("uuid"),
-                row.get("title"),
-                row.get("content"),
-                row.get("data"),
-                row.get("color"),
-                row.get("archived"),
-                row.get("created_on"),
-                row.get("updated_on"),
-                row.get("owner_"),
-                None,
-                row.get("board_"),
-            );
+            let entry: Entry = PgDbMemorizStorage::entry_from_row(&row);
 
             result.push(entry);
         }
@@ -350,6 +579,52 @@ impl StorageTrait for PgDbMemorizStorage {
         Ok(result)
     }
 
+    async fn get_owner_entries_page(
+        &self,
+        owner_uuid: uuid::Uuid,
+        after_uuid: Option<uuid::Uuid>,
+        limit: i64,
+    ) -> StorageResult<Vec<Entry>> {
+        let mut result: Vec<Entry> = Vec::new();
+
+        let query = "
+        select *
+        from memoriz.entry
+        where entry.owner_ = $1
+        and ($2::uuid is NULL or entry.uuid > $2)
+        order by entry.uuid
+        limit $3;";
+
+        let client = self.pool.get().await.unwrap();
+
+        for row in client
+            .query(query, &[&owner_uuid, &after_uuid, &limit])
+            .await?
+        {
+            result.push(PgDbMemorizStorage::entry_from_row(&row));
+        }
+
+        Ok(result)
+    }
+
+    async fn get_owner_entries(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Entry>> {
+        let mut result: Vec<Entry> = Vec::new();
+
+        let query = "
+        select *
+        from memoriz.entry
+        where entry.owner_ = $1
+        order by entry.updated_on desc;";
+
+        let client = self.pool.get().await.unwrap();
+
+        for row in client.query(query, &[&owner_uuid]).await? {
+            result.push(PgDbMemorizStorage::entry_from_row(&row));
+        }
+
+        Ok(result)
+    }
+
     async fn get_all_entries_by_board(
         &self,
         owner_uuid: uuid::Uuid,
@@ -366,20 +641,7 @@ impl StorageTrait for PgDbMemorizStorage {
         let client = self.pool.get().await.unwrap();
 
         for row in client.query(query, &[&owner_uuid, &board_uuid]).await? {
-            let entry: Entry = Entry::new(
-                row.get("id"),
-                row.get("uuid"),
-                row.get("title"),
-                row.get("content"),
-                row.get("data"),
-                row.get("color"),
-                row.get("archived"),
-                row.get("created_on"),
-                row.get("updated_on"),
-                row.get("owner_"),
-                None,
-                row.get("board_"),
-            );
+            let entry: Entry = PgDbMemorizStorage::entry_from_row(&row);
 
             result.push(entry);
         }
@@ -403,24 +665,1286 @@ impl StorageTrait for PgDbMemorizStorage {
 
         let client = self.pool.get().await.unwrap();
         for row in client.query(query, &[&ids, &owner_uuid]).await? {
-            let entry: Entry = Entry::new(
-                row.get("id"),
-                row.get("uuid"),
-                row.get("title"),
-                row.get("content"),
-                row.get("data"),
-                row.get("color"),
-                row.get("archived"),
-                row.get("created_on"),
-                row.get("updated_on"),
-                row.get("owner_"),
-                None,
-                row.get("board_"),
-            );
+            let entry: Entry = PgDbMemorizStorage::entry_from_row(&row);
 
             result.push(entry);
         }
 
         Ok(result)
     }
+
+    async fn get_entries_to_remind(&self, until: DateTime<Utc>) -> StorageResult<Vec<Entry>> {
+        let mut result: Vec<Entry> = Vec::new();
+
+        let query = "
+        select *
+        from memoriz.entry
+        where entry.remind_at <= $1
+        and entry.reminded_on is null
+        and entry.archived = false
+        order by entry.remind_at;";
+
+        let client = self.pool.get().await.unwrap();
+
+        for row in client.query(query, &[&until]).await? {
+            result.push(PgDbMemorizStorage::entry_from_row(&row));
+        }
+
+        Ok(result)
+    }
+
+    async fn get_all_entries_with_due_date(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Entry>> {
+        let mut result: Vec<Entry> = Vec::new();
+
+        let query = "
+        select *
+        from memoriz.entry
+        where entry.owner_ = $1
+        and entry.due_on is not NULL
+        order by entry.due_on;";
+
+        let client = self.pool.get().await.unwrap();
+
+        for row in client.query(query, &[&owner_uuid]).await? {
+            result.push(PgDbMemorizStorage::entry_from_row(&row));
+        }
+
+        Ok(result)
+    }
+
+    async fn set_calendar_token(&self, owner_uuid: uuid::Uuid, token: String) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.calendar_token
+        VALUES ($1,$2,$3)
+        ON CONFLICT (owner_) DO UPDATE SET (token, created_on) = ($2,$3);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(query, &[&owner_uuid, &token, &Utc::now()])
+            .await?;
+        Ok(())
+    }
+
+    async fn get_calendar_token_owner(&self, token: String) -> StorageResult<Option<uuid::Uuid>> {
+        let query = "
+        select owner_
+        from memoriz.calendar_token
+        where calendar_token.token = $1;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&token]).await?;
+        Ok(rows.iter().next().map(|row| row.get("owner_")))
+    }
+
+    async fn get_calendar_token(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Option<CalendarToken>> {
+        let query = "
+        select *
+        from memoriz.calendar_token
+        where calendar_token.owner_ = $1;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&owner_uuid]).await?;
+        Ok(rows.iter().next().map(|row| {
+            CalendarToken::new(row.get("owner_"), row.get("token"), row.get("created_on"))
+        }))
+    }
+
+    async fn restore_calendar_token(&self, token: CalendarToken) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.calendar_token
+        VALUES ($1,$2,$3)
+        ON CONFLICT (owner_) DO UPDATE SET (token, created_on) = ($2,$3);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(query, &[&token.owner, &token.token, &token.created_on])
+            .await?;
+        Ok(())
+    }
+
+    async fn restore_public_link(&self, link: PublicLink) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.public_link
+        VALUES ($1,$2,$3,$4,$5,$6,$7)
+        ON CONFLICT (token) DO UPDATE SET
+        (item_, kind, owner_, password_hash, expires_on, created_on)
+        = (EXCLUDED.item_, EXCLUDED.kind, EXCLUDED.owner_,
+        EXCLUDED.password_hash, EXCLUDED.expires_on, EXCLUDED.created_on);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &link.token,
+                    &link.item,
+                    &link.kind.to_string(),
+                    &link.owner,
+                    &link.password_hash,
+                    &link.expires_on,
+                    &link.created_on,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn upsert_share(&self, mut share: Share) -> StorageResult<Share> {
+        share.created_on = Some(Utc::now());
+        let query = "
+        INSERT INTO memoriz.share
+        VALUES ($1,$2,$3,$4,$5,$6)
+        ON CONFLICT (item_, recipient_) DO UPDATE SET role = EXCLUDED.role;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &share.item,
+                    &share.kind.to_string(),
+                    &share.owner,
+                    &share.recipient,
+                    &share.role.to_string(),
+                    &share.created_on,
+                ],
+            )
+            .await?;
+        Ok(share)
+    }
+
+    async fn delete_share(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+        recipient_uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let query = "
+        DELETE FROM memoriz.share
+        WHERE item_ = $1 AND recipient_ = $2 AND owner_ = $3;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(query, &[&item_uuid, &recipient_uuid, &owner_uuid])
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_item_shares(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let query = "DELETE FROM memoriz.share WHERE item_ = $1 AND owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client.execute(query, &[&item_uuid, &owner_uuid]).await?;
+        Ok(())
+    }
+
+    async fn get_item_shares(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Share>> {
+        let query = "
+        select *
+        from memoriz.share
+        where share.owner_ = $1
+        and share.item_ = $2
+        order by share.created_on;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .query(query, &[&owner_uuid, &item_uuid])
+            .await?
+            .iter()
+            .map(PgDbMemorizStorage::share_from_row)
+            .collect()
+    }
+
+    async fn get_recipient_shares(&self, recipient_uuid: uuid::Uuid) -> StorageResult<Vec<Share>> {
+        let query = "
+        select *
+        from memoriz.share
+        where share.recipient_ = $1
+        order by share.created_on;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .query(query, &[&recipient_uuid])
+            .await?
+            .iter()
+            .map(PgDbMemorizStorage::share_from_row)
+            .collect()
+    }
+
+    async fn get_recipient_entry_shares(
+        &self,
+        recipient_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Share>> {
+        let query = "
+        select share.*
+        from memoriz.share
+        join memoriz.entry on entry.uuid = $2 and entry.owner_ = share.owner_
+        where share.recipient_ = $1
+        and (share.item_ = entry.uuid or share.item_ = entry.board_);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .query(query, &[&recipient_uuid, &entry_uuid])
+            .await?
+            .iter()
+            .map(PgDbMemorizStorage::share_from_row)
+            .collect()
+    }
+
+    async fn create_public_link(&self, link: PublicLink) -> StorageResult<PublicLink> {
+        let query = "
+        INSERT INTO memoriz.public_link
+        VALUES ($1,$2,$3,$4,$5,$6,$7);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &link.token,
+                    &link.item,
+                    &link.kind.to_string(),
+                    &link.owner,
+                    &link.password_hash,
+                    &link.expires_on,
+                    &link.created_on,
+                ],
+            )
+            .await?;
+        Ok(link)
+    }
+
+    async fn get_public_link(&self, token: String) -> StorageResult<Option<PublicLink>> {
+        let query = "
+        select *
+        from memoriz.public_link
+        where public_link.token = $1;";
+
+        let client = self.pool.get().await.unwrap();
+        match client.query(query, &[&token]).await?.iter().next() {
+            Some(row) => Ok(Some(PgDbMemorizStorage::public_link_from_row(row)?)),
+            None => Ok(None),
+        }
+    }
+
+    async fn get_item_public_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<PublicLink>> {
+        let query = "
+        select *
+        from memoriz.public_link
+        where public_link.owner_ = $1
+        and public_link.item_ = $2
+        order by public_link.created_on;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .query(query, &[&owner_uuid, &item_uuid])
+            .await?
+            .iter()
+            .map(PgDbMemorizStorage::public_link_from_row)
+            .collect()
+    }
+
+    async fn delete_public_link(&self, owner_uuid: uuid::Uuid, token: String) -> StorageResult<()> {
+        let query = "DELETE FROM memoriz.public_link WHERE token = $1 AND owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client.execute(query, &[&token, &owner_uuid]).await?;
+        Ok(())
+    }
+
+    async fn delete_item_public_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let query = "DELETE FROM memoriz.public_link WHERE item_ = $1 AND owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client.execute(query, &[&item_uuid, &owner_uuid]).await?;
+        Ok(())
+    }
+
+    async fn append_change(
+        &self,
+        users_uuid: &[uuid::Uuid],
+        event: &ChangeEvent,
+    ) -> StorageResult<()> {
+        //One line by user, all in one statement.
+        let query = "
+        INSERT INTO memoriz.change_log (user_, owner_, item, item_id, kind, occurred_on)
+        SELECT unnest($1::uuid[]), $2, $3, $4, $5, $6;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &users_uuid.to_vec(),
+                    &event.owner,
+                    &event.item.to_string(),
+                    &event.id,
+                    &event.kind.to_string(),
+                    &event.occurred_on,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_changes(
+        &self,
+        user_uuid: uuid::Uuid,
+        since: i64,
+        limit: i64,
+    ) -> StorageResult<Vec<ChangeLogEntry>> {
+        let query = "
+        select *
+        from memoriz.change_log
+        where change_log.user_ = $1
+        and change_log.seq > $2
+        order by change_log.seq
+        limit $3;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .query(query, &[&user_uuid, &since, &limit])
+            .await?
+            .iter()
+            .map(PgDbMemorizStorage::change_from_row)
+            .collect()
+    }
+
+    async fn restore_change(&self, change: ChangeLogEntry) -> StorageResult<()> {
+        //The sequence is kept, the cursors of the clients stay valid.
+        let query = "
+        INSERT INTO memoriz.change_log
+        VALUES ($1,$2,$3,$4,$5,$6,$7)
+        ON CONFLICT (seq) DO NOTHING;";
+        //The next changes must come after the restored ones.
+        let sequence_query = "
+        SELECT setval(pg_get_serial_sequence('memoriz.change_log', 'seq'), max(seq))
+        FROM memoriz.change_log;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &change.seq,
+                    &change.user,
+                    &change.owner,
+                    &change.item.to_string(),
+                    &change.id,
+                    &change.kind.to_string(),
+                    &change.occurred_on,
+                ],
+            )
+            .await?;
+        client.query(sequence_query, &[]).await?;
+        Ok(())
+    }
+
+    async fn get_last_change_seq(&self, user_uuid: uuid::Uuid) -> StorageResult<i64> {
+        let query = "
+        select coalesce(max(seq), 0) as seq
+        from memoriz.change_log
+        where change_log.user_ = $1;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&user_uuid]).await?;
+        Ok(rows.iter().next().map(|row| row.get("seq")).unwrap_or(0))
+    }
+
+    async fn get_item_last_change_seq(
+        &self,
+        user_uuid: uuid::Uuid,
+        id: String,
+    ) -> StorageResult<Option<i64>> {
+        let query = "
+        select max(seq) as seq
+        from memoriz.change_log
+        where change_log.user_ = $1
+        and change_log.item_id = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&user_uuid, &id]).await?;
+        Ok(rows.iter().next().and_then(|row| row.get("seq")))
+    }
+
+    async fn get_entry_document(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Option<Vec<u8>>> {
+        let query = "
+        select document
+        from memoriz.entry_document
+        where entry_document.owner_ = $1
+        and entry_document.entry_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&owner_uuid, &entry_uuid]).await?;
+        Ok(rows.iter().next().map(|row| row.get("document")))
+    }
+
+    async fn save_entry_document(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        document: Vec<u8>,
+    ) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.entry_document
+        VALUES ($1,$2,$3,$4)
+        ON CONFLICT (entry_) DO UPDATE SET (document, updated_on) = ($3,$4)
+        WHERE entry_document.owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(query, &[&entry_uuid, &owner_uuid, &document, &Utc::now()])
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_entry_document(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let query = "DELETE FROM memoriz.entry_document WHERE entry_ = $1 AND owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client.execute(query, &[&entry_uuid, &owner_uuid]).await?;
+        Ok(())
+    }
+
+    async fn create_attachment(&self, attachment: Attachment) -> StorageResult<Attachment> {
+        let query = "
+        INSERT INTO memoriz.attachment
+        (uuid, entry_, owner_, file_name, content_type, size, created_on,
+        width, height, content_hash, thumbnail_sizes, processed_on)
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &attachment.uuid,
+                    &attachment.entry,
+                    &attachment.owner,
+                    &attachment.file_name,
+                    &attachment.content_type,
+                    &attachment.size,
+                    &attachment.created_on,
+                    &attachment.width,
+                    &attachment.height,
+                    &attachment.content_hash,
+                    &attachment.thumbnail_sizes(),
+                    &attachment.processed_on,
+                ],
+            )
+            .await?;
+        Ok(attachment)
+    }
+
+    async fn restore_attachment(&self, attachment: &Attachment) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.attachment
+        (uuid, entry_, owner_, file_name, content_type, size, created_on,
+        width, height, content_hash, thumbnail_sizes, processed_on)
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12)
+        ON CONFLICT (uuid) DO UPDATE SET
+        (entry_, owner_, file_name, content_type, size, created_on,
+        width, height, content_hash, thumbnail_sizes, processed_on)
+        = (EXCLUDED.entry_, EXCLUDED.owner_, EXCLUDED.file_name, EXCLUDED.content_type,
+        EXCLUDED.size, EXCLUDED.created_on, EXCLUDED.width, EXCLUDED.height,
+        EXCLUDED.content_hash, EXCLUDED.thumbnail_sizes, EXCLUDED.processed_on);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &attachment.uuid,
+                    &attachment.entry,
+                    &attachment.owner,
+                    &attachment.file_name,
+                    &attachment.content_type,
+                    &attachment.size,
+                    &attachment.created_on,
+                    &attachment.width,
+                    &attachment.height,
+                    &attachment.content_hash,
+                    &attachment.thumbnail_sizes(),
+                    &attachment.processed_on,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_attachment(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<Option<Attachment>> {
+        let query = "
+        select *
+        from memoriz.attachment
+        where attachment.owner_ = $1
+        and attachment.uuid = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&owner_uuid, &uuid]).await?;
+        Ok(rows
+            .iter()
+            .next()
+            .map(PgDbMemorizStorage::attachment_from_row))
+    }
+
+    async fn get_entry_attachments(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Attachment>> {
+        let query = "
+        select *
+        from memoriz.attachment
+        where attachment.owner_ = $1
+        and attachment.entry_ = $2
+        order by attachment.created_on;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&owner_uuid, &entry_uuid]).await?;
+        Ok(rows
+            .iter()
+            .map(PgDbMemorizStorage::attachment_from_row)
+            .collect())
+    }
+
+    async fn get_owner_attachments(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Attachment>> {
+        let query = "
+        select *
+        from memoriz.attachment
+        where attachment.owner_ = $1
+        order by attachment.created_on;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&owner_uuid]).await?;
+        Ok(rows
+            .iter()
+            .map(PgDbMemorizStorage::attachment_from_row)
+            .collect())
+    }
+
+    async fn get_unprocessed_attachments(
+        &self,
+        limit: i64,
+        max_attempts: i32,
+        failed_before: DateTime<Utc>,
+    ) -> StorageResult<Vec<Attachment>> {
+        let query = "
+        select *
+        from memoriz.attachment
+        where attachment.processed_on is null
+        and attachment.processing_attempts < $2
+        and (attachment.failed_on is null or attachment.failed_on < $3)
+        order by attachment.processing_attempts, attachment.created_on
+        limit $1;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client
+            .query(query, &[&limit, &max_attempts, &failed_before])
+            .await?;
+        Ok(rows
+            .iter()
+            .map(PgDbMemorizStorage::attachment_from_row)
+            .collect())
+    }
+
+    async fn record_attachment_failure(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let query = "
+        UPDATE memoriz.attachment
+        SET (processing_attempts, failed_on) = (processing_attempts + 1, $3)
+        WHERE uuid = $1 AND owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(query, &[&uuid, &owner_uuid, &Utc::now()])
+            .await?;
+        Ok(())
+    }
+
+    async fn update_processed_attachment(&self, attachment: &Attachment) -> StorageResult<()> {
+        let query = "
+        UPDATE memoriz.attachment SET (width, height, thumbnail_sizes, processed_on)
+        = ($3, $4, $5, $6)
+        WHERE uuid = $1 AND owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &attachment.uuid,
+                    &attachment.owner,
+                    &attachment.width,
+                    &attachment.height,
+                    &attachment.thumbnail_sizes(),
+                    &attachment.processed_on,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn save_attachment_text(
+        &self,
+        attachment: &Attachment,
+        text: String,
+    ) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.attachment_text
+        VALUES ($1,$2,$3,$4)
+        ON CONFLICT (attachment_) DO UPDATE SET text = $4;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &attachment.uuid,
+                    &attachment.entry,
+                    &attachment.owner,
+                    &text,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_entry_attachment_texts(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<(uuid::Uuid, String)>> {
+        let query = "
+        select attachment_, text
+        from memoriz.attachment_text
+        where attachment_text.owner_ = $1
+        and attachment_text.entry_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&owner_uuid, &entry_uuid]).await?;
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("attachment_"), row.get("text")))
+            .collect())
+    }
+
+    async fn delete_attachment(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let query = "DELETE FROM memoriz.attachment WHERE uuid = $1 AND owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client.execute(query, &[&uuid, &owner_uuid]).await?;
+        Ok(())
+    }
+
+    async fn delete_entry_attachments(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let query = "DELETE FROM memoriz.attachment WHERE entry_ = $1 AND owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client.execute(query, &[&entry_uuid, &owner_uuid]).await?;
+        Ok(())
+    }
+
+    async fn queue_link_previews(&self, urls: Vec<String>) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.link_preview (url, queued_on)
+        SELECT unnest($1::text[]), $2
+        ON CONFLICT (url) DO NOTHING;";
+
+        let client = self.pool.get().await.unwrap();
+        client.execute(query, &[&urls, &Utc::now()]).await?;
+        Ok(())
+    }
+
+    async fn get_pending_link_previews(&self, limit: i64) -> StorageResult<Vec<String>> {
+        let query = "
+        select url
+        from memoriz.link_preview
+        where link_preview.fetched_on is null
+        order by link_preview.queued_on
+        limit $1;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&limit]).await?;
+        Ok(rows.iter().map(|row| row.get("url")).collect())
+    }
+
+    async fn save_link_preview(&self, preview: &LinkPreview) -> StorageResult<()> {
+        let query = "
+        UPDATE memoriz.link_preview SET (title, description, image, site_name, fetched_on)
+        = ($2, $3, $4, $5, $6)
+        WHERE url = $1;";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &preview.url,
+                    &preview.title,
+                    &preview.description,
+                    &preview.image,
+                    &preview.site_name,
+                    &preview.fetched_on,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_link_previews(&self, urls: Vec<String>) -> StorageResult<Vec<LinkPreview>> {
+        let query = "
+        select *
+        from memoriz.link_preview
+        where link_preview.url = any($1);";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&urls]).await?;
+        Ok(rows
+            .iter()
+            .map(PgDbMemorizStorage::link_preview_from_row)
+            .collect())
+    }
+
+    async fn create_saved_search(
+        &self,
+        mut saved_search: SavedSearch,
+    ) -> StorageResult<SavedSearch> {
+        saved_search.created_on = Some(Utc::now());
+        let query = "
+        INSERT INTO memoriz.saved_search (uuid, owner_, name, query, color, created_on)
+        VALUES ($1,$2,$3,$4,$5,$6);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &saved_search.uuid,
+                    &saved_search.owner,
+                    &saved_search.name,
+                    &saved_search.query,
+                    &saved_search.color,
+                    &saved_search.created_on,
+                ],
+            )
+            .await?;
+        Ok(saved_search)
+    }
+
+    async fn update_saved_search(
+        &self,
+        mut saved_search: SavedSearch,
+    ) -> StorageResult<SavedSearch> {
+        saved_search.updated_on = Some(Utc::now());
+        let query = "
+        UPDATE memoriz.saved_search SET (name, query, color, updated_on)
+        = ($3,$4,$5,$6)
+        WHERE uuid = $1 AND owner_ = $2
+        RETURNING created_on;";
+
+        let client = self.pool.get().await.unwrap();
+        for row in client
+            .query(
+                query,
+                &[
+                    &saved_search.uuid,
+                    &saved_search.owner,
+                    &saved_search.name,
+                    &saved_search.query,
+                    &saved_search.color,
+                    &saved_search.updated_on,
+                ],
+            )
+            .await?
+        {
+            saved_search.created_on = row.get("created_on");
+        }
+        Ok(saved_search)
+    }
+
+    async fn get_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<Option<SavedSearch>> {
+        let query = "
+        select *
+        from memoriz.saved_search
+        where saved_search.owner_ = $1
+        and saved_search.uuid = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        Ok(client
+            .query(query, &[&owner_uuid, &uuid])
+            .await?
+            .iter()
+            .next()
+            .map(PgDbMemorizStorage::saved_search_from_row))
+    }
+
+    async fn get_all_saved_searches(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<SavedSearch>> {
+        let query = "
+        select *
+        from memoriz.saved_search
+        where saved_search.owner_ = $1
+        order by saved_search.name;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&owner_uuid]).await?;
+        Ok(rows
+            .iter()
+            .map(PgDbMemorizStorage::saved_search_from_row)
+            .collect())
+    }
+
+    async fn delete_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let query = "DELETE FROM memoriz.saved_search WHERE uuid = $1 AND owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client.execute(query, &[&uuid, &owner_uuid]).await?;
+        Ok(())
+    }
+
+    async fn create_template(&self, mut template: Template) -> StorageResult<Template> {
+        template.created_on = Some(Utc::now());
+        let query = "
+        INSERT INTO memoriz.template
+        (uuid, owner_, name, title, content, color, labels, board_, checklist, created_on)
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &template.uuid,
+                    &template.owner,
+                    &template.name,
+                    &template.title,
+                    &template.content,
+                    &template.color,
+                    &template.labels,
+                    &template.board,
+                    &template.checklist,
+                    &template.created_on,
+                ],
+            )
+            .await?;
+        Ok(template)
+    }
+
+    async fn update_template(&self, mut template: Template) -> StorageResult<Template> {
+        template.updated_on = Some(Utc::now());
+        let query = "
+        UPDATE memoriz.template
+        SET (name, title, content, color, labels, board_, checklist, updated_on)
+        = ($3,$4,$5,$6,$7,$8,$9,$10)
+        WHERE uuid = $1 AND owner_ = $2
+        RETURNING created_on;";
+
+        let client = self.pool.get().await.unwrap();
+        for row in client
+            .query(
+                query,
+                &[
+                    &template.uuid,
+                    &template.owner,
+                    &template.name,
+                    &template.title,
+                    &template.content,
+                    &template.color,
+                    &template.labels,
+                    &template.board,
+                    &template.checklist,
+                    &template.updated_on,
+                ],
+            )
+            .await?
+        {
+            template.created_on = row.get("created_on");
+        }
+        Ok(template)
+    }
+
+    async fn get_template(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<Option<Template>> {
+        let query = "
+        select *
+        from memoriz.template
+        where template.owner_ = $1
+        and template.uuid = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        Ok(client
+            .query(query, &[&owner_uuid, &uuid])
+            .await?
+            .iter()
+            .next()
+            .map(PgDbMemorizStorage::template_from_row))
+    }
+
+    async fn get_all_templates(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Template>> {
+        let query = "
+        select *
+        from memoriz.template
+        where template.owner_ = $1
+        order by template.name;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&owner_uuid]).await?;
+        Ok(rows
+            .iter()
+            .map(PgDbMemorizStorage::template_from_row)
+            .collect())
+    }
+
+    async fn delete_template(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<()> {
+        let query = "DELETE FROM memoriz.template WHERE uuid = $1 AND owner_ = $2;";
+
+        let client = self.pool.get().await.unwrap();
+        client.execute(query, &[&uuid, &owner_uuid]).await?;
+        Ok(())
+    }
+
+    async fn get_all_owners(&self) -> StorageResult<Vec<uuid::Uuid>> {
+        let query = "
+        select owner_ from memoriz.board where owner_ is not null
+        union
+        select owner_ from memoriz.entry where owner_ is not null
+        union
+        select owner from memoriz.label where owner is not null
+        union
+        select owner_ from memoriz.calendar_token
+        union
+        select owner_ from memoriz.template
+        union
+        select owner_ from memoriz.saved_search
+        union
+        select user_ from memoriz.change_log;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[]).await?;
+        Ok(rows.iter().map(|row| row.get(0)).collect())
+    }
+
+    async fn restore_board(&self, board: Board) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.board
+        VALUES ($1,$2,$3,$4,$5,$6,$7)
+        ON CONFLICT (uuid) DO UPDATE SET
+        (title, data, color, created_on, updated_on, owner_)
+        = (EXCLUDED.title, EXCLUDED.data, EXCLUDED.color,
+        EXCLUDED.created_on, EXCLUDED.updated_on, EXCLUDED.owner_);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &board.uuid,
+                    &board.title,
+                    &board.data,
+                    &board.color,
+                    &board.created_on,
+                    &board.updated_on,
+                    &board.owner,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn restore_label(&self, label: Label) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.label
+        VALUES ($1,$2,$3,$4)
+        ON CONFLICT (id) DO UPDATE SET
+        (name, description, owner) = (EXCLUDED.name, EXCLUDED.description, EXCLUDED.owner);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[&label.id, &label.name, &label.description, &label.owner],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn restore_entry(&self, entry: Entry) -> StorageResult<()> {
+        let recurrence = entry.recurrence.as_ref().map(|r| r.to_string());
+
+        //The serial id is local to the database, entries are identified by their uuid.
+        let query = "
+        INSERT INTO memoriz.entry
+        VALUES (DEFAULT,$1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11,$12,$13,$14,$15)
+        ON CONFLICT (uuid) DO UPDATE SET
+        (title, content, data, color, archived, created_on, updated_on, owner_, board_,
+        due_on, remind_at, recurrence, next_occurrence, reminded_on)
+        = (EXCLUDED.title, EXCLUDED.content, EXCLUDED.data, EXCLUDED.color,
+        EXCLUDED.archived, EXCLUDED.created_on, EXCLUDED.updated_on, EXCLUDED.owner_,
+        EXCLUDED.board_, EXCLUDED.due_on, EXCLUDED.remind_at, EXCLUDED.recurrence,
+        EXCLUDED.next_occurrence, EXCLUDED.reminded_on);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &entry.uuid,
+                    &entry.title,
+                    &entry.content,
+                    &entry.data,
+                    &entry.color,
+                    &entry.archived,
+                    &entry.created_on,
+                    &entry.updated_on,
+                    &entry.owner,
+                    &entry.board,
+                    &entry.due_on,
+                    &entry.remind_at,
+                    &recurrence,
+                    &entry.next_occurrence,
+                    &entry.reminded_on,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn restore_template(&self, template: Template) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.template
+        (uuid, owner_, name, title, content, color, labels, board_, checklist,
+        created_on, updated_on)
+        VALUES ($1,$2,$3,$4,$5,$6,$7,$8,$9,$10,$11)
+        ON CONFLICT (uuid) DO UPDATE SET
+        (owner_, name, title, content, color, labels, board_, checklist, created_on,
+        updated_on)
+        = (EXCLUDED.owner_, EXCLUDED.name, EXCLUDED.title, EXCLUDED.content,
+        EXCLUDED.color, EXCLUDED.labels, EXCLUDED.board_, EXCLUDED.checklist,
+        EXCLUDED.created_on, EXCLUDED.updated_on);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &template.uuid,
+                    &template.owner,
+                    &template.name,
+                    &template.title,
+                    &template.content,
+                    &template.color,
+                    &template.labels,
+                    &template.board,
+                    &template.checklist,
+                    &template.created_on,
+                    &template.updated_on,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn restore_saved_search(&self, saved_search: SavedSearch) -> StorageResult<()> {
+        let query = "
+        INSERT INTO memoriz.saved_search
+        (uuid, owner_, name, query, color, created_on, updated_on)
+        VALUES ($1,$2,$3,$4,$5,$6,$7)
+        ON CONFLICT (uuid) DO UPDATE SET
+        (owner_, name, query, color, created_on, updated_on)
+        = (EXCLUDED.owner_, EXCLUDED.name, EXCLUDED.query, EXCLUDED.color,
+        EXCLUDED.created_on, EXCLUDED.updated_on);";
+
+        let client = self.pool.get().await.unwrap();
+        client
+            .execute(
+                query,
+                &[
+                    &saved_search.uuid,
+                    &saved_search.owner,
+                    &saved_search.name,
+                    &saved_search.query,
+                    &saved_search.color,
+                    &saved_search.created_on,
+                    &saved_search.updated_on,
+                ],
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn get_entry_by_import_id(
+        &self,
+        owner_uuid: uuid::Uuid,
+        source: String,
+        source_id: String,
+    ) -> StorageResult<Option<Entry>> {
+        let query = "
+        select *
+        from memoriz.entry
+        where entry.owner_ = $1
+        and entry.data->'import'->>'source' = $2
+        and entry.data->'import'->>'id' = $3
+        limit 1;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client
+            .query(query, &[&owner_uuid, &source, &source_id])
+            .await?;
+        Ok(rows.iter().next().map(PgDbMemorizStorage::entry_from_row))
+    }
+
+    async fn get_entry_by_title(
+        &self,
+        owner_uuid: uuid::Uuid,
+        title: String,
+    ) -> StorageResult<Option<Entry>> {
+        let query = "
+        select *
+        from memoriz.entry
+        where entry.owner_ = $1
+        and lower(trim(entry.title)) = lower(trim($2))
+        order by entry.created_on
+        limit 1;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&owner_uuid, &title]).await?;
+        Ok(rows.iter().next().map(PgDbMemorizStorage::entry_from_row))
+    }
+
+    async fn replace_entry_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        source_uuid: uuid::Uuid,
+        links: Vec<EntryLink>,
+    ) -> StorageResult<()> {
+        let delete_query = "DELETE FROM memoriz.entry_link WHERE source_ = $1 AND owner_ = $2;";
+        let insert_query = "
+        INSERT INTO memoriz.entry_link
+        VALUES ($1,$2,$3,$4);";
+
+        let mut client = self.pool.get().await.unwrap();
+        let transaction = client.transaction().await?;
+
+        transaction
+            .execute(delete_query, &[&source_uuid, &owner_uuid])
+            .await?;
+        for link in &links {
+            transaction
+                .execute(
+                    insert_query,
+                    &[&link.source, &link.target_title, &link.target, &link.owner],
+                )
+                .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
+    async fn get_entry_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        source_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<EntryLink>> {
+        let query = "
+        select *
+        from memoriz.entry_link
+        where entry_link.owner_ = $1
+        and entry_link.source_ = $2
+        order by entry_link.target_title;";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client.query(query, &[&owner_uuid, &source_uuid]).await?;
+        Ok(rows
+            .iter()
+            .map(PgDbMemorizStorage::entry_link_from_row)
+            .collect())
+    }
+
+    async fn get_entry_backlinks(
+        &self,
+        owner_uuid: uuid::Uuid,
+        target_uuid: uuid::Uuid,
+        target_title: String,
+    ) -> StorageResult<Vec<EntryLink>> {
+        //Links written before the target existed are only known by title.
+        let query = "
+        select *
+        from memoriz.entry_link
+        where entry_link.owner_ = $1
+        and (entry_link.target_ = $2
+            or (entry_link.target_ is NULL
+                and lower(trim(entry_link.target_title)) = lower(trim($3))));";
+
+        let client = self.pool.get().await.unwrap();
+        let rows = client
+            .query(query, &[&owner_uuid, &target_uuid, &target_title])
+            .await?;
+        Ok(rows
+            .iter()
+            .map(PgDbMemorizStorage::entry_link_from_row)
+            .collect())
+    }
 }
@@ -0,0 +1,140 @@
+use async_trait::async_trait;
+use helix_memoriz_domain::blob::error::*;
+use helix_memoriz_domain::blob::traits::BlobStoreTrait;
+use s3::bucket::Bucket;
+use s3::creds::Credentials;
+use s3::region::Region;
+
+const NOT_FOUND_STATUS: u16 = 404;
+
+pub struct S3BlobStore {
+    bucket: Bucket,
+}
+
+impl S3BlobStore {
+    pub fn new(
+        bucket: String,
+        region: String,
+        endpoint: Option<String>,
+        access_key: String,
+        secret_key: String,
+    ) -> BlobResult<S3BlobStore> {
+        //With an endpoint, the store can target a local S3 stand-in (minio, localstack...),
+        //which are reached by path rather than by a bucket sub-domain.
+        let region = match endpoint {
+            Some(endpoint) => Region::Custom {
+                region: region,
+                endpoint: endpoint,
+            },
+            None => region
+                .parse()
+                .map_err(|e| BlobError::Store(e.to_string()))?,
+        };
+        let credentials = Credentials::new(Some(&access_key), Some(&secret_key), None, None, None)
+            .map_err(|e| BlobError::Store(e.to_string()))?;
+
+        let bucket = match region {
+            Region::Custom { .. } => Bucket::new_with_path_style(&bucket, region, credentials),
+            _ => Bucket::new(&bucket, region, credentials),
+        }
+        .map_err(|e| BlobError::Store(e.to_string()))?;
+
+        Ok(S3BlobStore { bucket: bucket })
+    }
+}
+
+fn check_status(key: &str, status: u16) -> BlobResult<()> {
+    match status {
+        200..=299 => Ok(()),
+        NOT_FOUND_STATUS => Err(BlobError::NotFound(key.to_string())),
+        _ => Err(BlobError::Store(format!("{} answered {}", key, status))),
+    }
+}
+
+#[async_trait]
+impl BlobStoreTrait for S3BlobStore {
+    async fn put(&self, key: &str, content: Vec<u8>, content_type: &str) -> BlobResult<()> {
+        let (_, status) = self
+            .bucket
+            .put_object_with_content_type(key, &content, content_type)
+            .await
+            .map_err(|e| BlobError::Store(e.to_string()))?;
+        check_status(key, status)
+    }
+
+    async fn get(&self, key: &str) -> BlobResult<Vec<u8>> {
+        let (content, status) = self
+            .bucket
+            .get_object(key)
+            .await
+            .map_err(|e| BlobError::Store(e.to_string()))?;
+        check_status(key, status)?;
+        Ok(content)
+    }
+
+    async fn delete(&self, key: &str) -> BlobResult<()> {
+        let (_, status) = self
+            .bucket
+            .delete_object(key)
+            .await
+            .map_err(|e| BlobError::Store(e.to_string()))?;
+        match check_status(key, status) {
+            Err(BlobError::NotFound(_)) => Ok(()),
+            result => result,
+        }
+    }
+}
+
+//Run against a local S3 stand-in, such as minio, with an existing bucket:
+//S3_TEST_ENDPOINT=http://localhost:9000 S3_TEST_BUCKET=memoriz \
+//S3_TEST_ACCESS_KEY=minioadmin S3_TEST_SECRET_KEY=minioadmin cargo test
+//Without S3_TEST_ENDPOINT, the tests pass without running.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    fn store() -> Option<S3BlobStore> {
+        let endpoint = env::var("S3_TEST_ENDPOINT").ok()?;
+        let variable = |name: &str| env::var(name).unwrap_or_else(|_| panic!("{} not set", name));
+        Some(
+            S3BlobStore::new(
+                variable("S3_TEST_BUCKET"),
+                "us-east-1".to_string(),
+                Some(endpoint),
+                variable("S3_TEST_ACCESS_KEY"),
+                variable("S3_TEST_SECRET_KEY"),
+            )
+            .unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn puts_gets_and_deletes_a_blob() {
+        let store = match store() {
+            Some(store) => store,
+            None => return,
+        };
+        let key = format!("{}/attachment", uuid::Uuid::new_v4());
+
+        store
+            .put(&key, b"content".to_vec(), "text/plain")
+            .await
+            .unwrap();
+        assert_eq!(store.get(&key).await.unwrap(), b"content");
+
+        store.delete(&key).await.unwrap();
+        assert!(matches!(store.get(&key).await, Err(BlobError::NotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn deleting_a_missing_blob_is_not_an_error() {
+        let store = match store() {
+            Some(store) => store,
+            None => return,
+        };
+        let key = format!("{}/missing", uuid::Uuid::new_v4());
+
+        store.delete(&key).await.unwrap();
+    }
+}
@@ -1,15 +1,64 @@
+#[macro_use]
+extern crate serde_derive;
+
+use async_trait::async_trait;
 use chrono::prelude::*;
-use helix_memoriz_domain::core::{board::*, entry::*, label::*};
+use helix_memoriz_domain::core::{
+    attachment::*, board::*, dump::CalendarToken, entry::*, event::*, label::*, link::*,
+    preview::*, search::*, share::*, sync::*, template::*,
+};
 use helix_memoriz_domain::storage::error::*;
 use helix_memoriz_domain::storage::traits::StorageTrait;
-use serde_cbor::ser::to_vec;
-use sled::Db;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_cbor::{from_slice, to_vec};
+use sled::{Batch, Db, Tree};
+use std::cmp::Reverse;
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::ops::Bound;
 use uuid::Uuid;
 
-//TODO: Define const for table names
-const OWNERSHIP_TREE: &str = "OWNERSHIP";
+//One tree by kind of record, the layout is described in arch.md.
 const BOARDS_TREE: &str = "BOARDS";
+const LABELS_TREE: &str = "LABELS";
 const ENTRIES_TREE: &str = "ENTRIES";
+const ENTRY_LABELS_TREE: &str = "ENTRY_LABELS";
+const ENTRY_LINKS_TREE: &str = "ENTRY_LINKS";
+const CALENDAR_TOKENS_TREE: &str = "CALENDAR_TOKENS";
+const SHARES_TREE: &str = "SHARES";
+const PUBLIC_LINKS_TREE: &str = "PUBLIC_LINKS";
+const CHANGES_TREE: &str = "CHANGES";
+const DOCUMENTS_TREE: &str = "DOCUMENTS";
+const ATTACHMENTS_TREE: &str = "ATTACHMENTS";
+const ATTACHMENT_TEXTS_TREE: &str = "ATTACHMENT_TEXTS";
+const LINK_PREVIEWS_TREE: &str = "LINK_PREVIEWS";
+const TEMPLATES_TREE: &str = "TEMPLATES";
+const SAVED_SEARCHES_TREE: &str = "SAVED_SEARCHES";
+const SEQUENCES_TREE: &str = "SEQUENCES";
+//Last sequence number given to the change log.
+const CHANGE_SEQ_KEY: &str = "change_log";
+
+//Processing state of an attachment, which Attachment does not carry.
+#[derive(Serialize, Deserialize)]
+struct StoredAttachment<A> {
+    attachment: A,
+    processing_attempts: i32,
+    failed_on: Option<DateTime<Utc>>,
+}
+
+//Public link with its password hash, which PublicLink never serializes.
+#[derive(Serialize, Deserialize)]
+struct StoredPublicLink<L> {
+    link: L,
+    password_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredLinkPreview {
+    preview: LinkPreview,
+    queued_on: DateTime<Utc>,
+}
 
 pub struct SledDbMemorizStorage {
     db: Db,
@@ -22,81 +71,1682 @@ impl SledDbMemorizStorage {
     }
 
     fn generate_uuid(&self) -> Uuid {
-        Uuid::new_v5(&Uuid::NAMESPACE_URL, Utc::now().to_rfc3339().as_bytes())
+        Uuid::new_v4()
+    }
+
+    fn tree(&self, name: &str) -> StorageResult<Tree> {
+        Ok(self.db.open_tree(name)?)
+    }
+
+    fn get_owner_entries_sorted(&self, owner_uuid: Uuid) -> StorageResult<Vec<Entry>> {
+        let mut entries: Vec<Entry> =
+            scan_records(&self.tree(ENTRIES_TREE)?, owner_uuid.as_bytes())?;
+        sort_by_update(&mut entries);
+        Ok(entries)
+    }
+
+    fn next_change_seq(&self) -> StorageResult<i64> {
+        let sequences = self.tree(SEQUENCES_TREE)?;
+        let seq = sequences.update_and_fetch(CHANGE_SEQ_KEY, |last| {
+            Some((read_seq(last) + 1).to_be_bytes().to_vec())
+        })?;
+        Ok(read_seq(seq.as_deref()))
+    }
+
+    //Labels of the entry_label keys, with the owner of the entry.
+    fn labels_of_keys(&self, owner_uuid: Uuid, prefix: &[u8]) -> StorageResult<Vec<(Uuid, Label)>> {
+        let labels = self.tree(LABELS_TREE)?;
+        let mut result: Vec<(Uuid, Label)> = Vec::new();
+
+        for item in self.tree(ENTRY_LABELS_TREE)?.scan_prefix(prefix) {
+            let (key, _) = item?;
+            let entry_uuid = uuid_at(&key, 16)?;
+            let label_id =
+                String::from_utf8(key[32..].to_vec()).map_err(|_| StorageError::AnotherError)?;
+            //A deleted label is no longer on the entries.
+            if let Some(label) = get_record::<Label>(&labels, label_id.as_bytes())? {
+                result.push((
+                    entry_uuid,
+                    Label::new(label.id, label.name, label.description, owner_uuid),
+                ));
+            }
+        }
+
+        Ok(result)
+    }
+
+    fn get_stored_attachment(
+        &self,
+        owner_uuid: Uuid,
+        uuid: Uuid,
+    ) -> StorageResult<Option<StoredAttachment<Attachment>>> {
+        get_record(
+            &self.tree(ATTACHMENTS_TREE)?,
+            &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+        )
+    }
+
+    fn put_attachment(
+        &self,
+        attachment: &Attachment,
+        processing_attempts: i32,
+        failed_on: Option<DateTime<Utc>>,
+    ) -> StorageResult<()> {
+        let stored = StoredAttachment {
+            attachment: attachment,
+            processing_attempts: processing_attempts,
+            failed_on: failed_on,
+        };
+        put_record(
+            &self.tree(ATTACHMENTS_TREE)?,
+            &key(&[attachment.owner.as_bytes(), attachment.uuid.as_bytes()]),
+            &stored,
+        )
+    }
+
+    fn put_public_link(&self, link: &PublicLink) -> StorageResult<()> {
+        let stored = StoredPublicLink {
+            link: link,
+            password_hash: link.password_hash.to_owned(),
+        };
+        put_record(
+            &self.tree(PUBLIC_LINKS_TREE)?,
+            link.token.as_bytes(),
+            &stored,
+        )
+    }
+
+    fn get_public_links(&self) -> StorageResult<Vec<PublicLink>> {
+        let stored: Vec<StoredPublicLink<PublicLink>> =
+            scan_records(&self.tree(PUBLIC_LINKS_TREE)?, &[])?;
+        Ok(stored
+            .into_iter()
+            .map(|stored| {
+                let mut link = stored.link;
+                link.has_password = stored.password_hash.is_some();
+                link.password_hash = stored.password_hash;
+                link
+            })
+            .collect())
+    }
+
+    fn put_entry(&self, entry: &mut Entry) -> StorageResult<()> {
+        let (owner_uuid, uuid) = match (entry.owner, entry.uuid) {
+            (Some(owner_uuid), Some(uuid)) => (owner_uuid, uuid),
+            _ => return Err(StorageError::AnotherError),
+        };
+
+        //Labels, attachments and previews are stored on their own, like in the other backends.
+        let labels = entry.labels.take();
+        let attachments = entry.attachments.take();
+        let previews = entry.previews.take();
+        let content_html = entry.content_html.take();
+        let matched_in = entry.matched_in.take();
+        let result = put_record(
+            &self.tree(ENTRIES_TREE)?,
+            &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            entry,
+        );
+        entry.labels = labels;
+        entry.attachments = attachments;
+        entry.previews = previews;
+        entry.content_html = content_html;
+        entry.matched_in = matched_in;
+        result
+    }
+
+    fn generate_entry_id(&self) -> StorageResult<i32> {
+        Ok(self.db.generate_id()? as i32 + 1)
+    }
+}
+
+//Flushed on the way out: a restore ends with everything on disk.
+impl Drop for SledDbMemorizStorage {
+    fn drop(&mut self) {
+        if let Err(e) = self.db.flush() {
+            println!("SLED storage not flushed: {}", e);
+        }
+    }
+}
+
+fn key(parts: &[&[u8]]) -> Vec<u8> {
+    parts.concat()
+}
+
+fn encode<T: Serialize + ?Sized>(value: &T) -> StorageResult<Vec<u8>> {
+    to_vec(value).map_err(|_| StorageError::AnotherError)
+}
+
+fn decode<T: DeserializeOwned>(bytes: &[u8]) -> StorageResult<T> {
+    from_slice(bytes).map_err(|_| StorageError::AnotherError)
+}
+
+fn get_record<T: DeserializeOwned>(tree: &Tree, key: &[u8]) -> StorageResult<Option<T>> {
+    match tree.get(key)? {
+        Some(bytes) => Ok(Some(decode(&bytes)?)),
+        None => Ok(None),
+    }
+}
+
+fn put_record<T: Serialize + ?Sized>(tree: &Tree, key: &[u8], value: &T) -> StorageResult<()> {
+    tree.insert(key, encode(value)?)?;
+    Ok(())
+}
+
+fn scan_records<T: DeserializeOwned>(tree: &Tree, prefix: &[u8]) -> StorageResult<Vec<T>> {
+    let mut records: Vec<T> = Vec::new();
+    for item in tree.scan_prefix(prefix) {
+        let (_, bytes) = item?;
+        records.push(decode(&bytes)?);
+    }
+    Ok(records)
+}
+
+fn remove_prefix(tree: &Tree, prefix: &[u8]) -> StorageResult<()> {
+    let mut batch = Batch::default();
+    for item in tree.scan_prefix(prefix) {
+        let (key, _) = item?;
+        batch.remove(key);
+    }
+    tree.apply_batch(batch)?;
+    Ok(())
+}
+
+fn uuid_at(key: &[u8], start: usize) -> StorageResult<Uuid> {
+    key.get(start..start + 16)
+        .and_then(|bytes| Uuid::from_slice(bytes).ok())
+        .ok_or(StorageError::AnotherError)
+}
+
+fn read_seq(bytes: Option<&[u8]>) -> i64 {
+    bytes
+        .and_then(|bytes| <[u8; 8]>::try_from(bytes).ok())
+        .map(i64::from_be_bytes)
+        .unwrap_or(0)
+}
+
+fn change_key(user_uuid: Uuid, seq: i64) -> Vec<u8> {
+    key(&[user_uuid.as_bytes(), &seq.to_be_bytes()])
+}
+
+//Like "order by updated_on desc": entries never updated first, then the latest updated.
+fn sort_by_update(entries: &mut [Entry]) {
+    entries.sort_by_key(|entry| Reverse((entry.updated_on.is_none(), entry.updated_on)));
+}
+
+fn same_title(title: &str, other: &str) -> bool {
+    title.trim().to_lowercase() == other.trim().to_lowercase()
+}
+
+//Text of a JSON value, like the ->> operator.
+fn json_text(value: Option<&serde_json::Value>) -> Option<String> {
+    match value? {
+        serde_json::Value::Null => None,
+        serde_json::Value::String(text) => Some(text.to_owned()),
+        value => Some(value.to_string()),
     }
 }
 
+#[async_trait]
 impl StorageTrait for SledDbMemorizStorage {
-    fn get_board(
-        &mut self,
-        owner_uuid: uuid::Uuid,
-        uuid: uuid::Uuid,
-    ) -> StorageResult<Option<Board>> {
-        Err(StorageError::NotImplemented)
+    async fn get_board(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<Board> {
+        get_record(
+            &self.tree(BOARDS_TREE)?,
+            &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+        )?
+        .ok_or(StorageError::AnotherError)
     }
 
-    fn create_board(&mut self, mut board: Board) -> StorageResult<Board> {
-        let boards = self.db.open_tree(BOARDS_TREE)?;
+    async fn create_board(&self, mut board: Board) -> StorageResult<Board> {
+        let owner_uuid = board.owner.ok_or(StorageError::CreationImpossible)?;
         board.uuid = Some(self.generate_uuid());
+        board.created_on = Some(Utc::now());
+
+        put_record(
+            &self.tree(BOARDS_TREE)?,
+            &key(&[owner_uuid.as_bytes(), board.uuid.unwrap().as_bytes()]),
+            &board,
+        )?;
+        Ok(board)
+    }
+
+    async fn get_all_boards(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Board>> {
+        let mut boards: Vec<Board> = scan_records(&self.tree(BOARDS_TREE)?, owner_uuid.as_bytes())?;
+        boards.sort_by_key(|board| Reverse((board.updated_on.is_none(), board.updated_on)));
+        Ok(boards)
+    }
 
-        boards.insert(&board.uuid.unwrap().to_string(), to_vec(&board).unwrap())?;
+    async fn update_board(&self, mut board: Board) -> StorageResult<Board> {
+        board.updated_on = Some(Utc::now());
+        let board_key = match (board.owner, board.uuid) {
+            (Some(owner_uuid), Some(uuid)) => key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            _ => return Err(StorageError::AnotherError),
+        };
+
+        let boards = self.tree(BOARDS_TREE)?;
+        if let Some(existing) = get_record::<Board>(&boards, &board_key)? {
+            let mut stored = board;
+            stored.created_on = existing.created_on;
+            put_record(&boards, &board_key, &stored)?;
+            board = stored;
+        }
         Ok(board)
     }
-    fn get_all_boards(&mut self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Board>> {
-        self.db.open_tree("")?;
-        Err(StorageError::NotImplemented)
+
+    async fn delete_board(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<()> {
+        self.tree(BOARDS_TREE)?
+            .remove(key(&[owner_uuid.as_bytes(), uuid.as_bytes()]))?;
+        Ok(())
     }
-    fn update_board(&mut self, board: Board) -> StorageResult<Board> {
-        Err(StorageError::NotImplemented)
+
+    async fn get_all_labels(&self) -> StorageResult<Vec<Label>> {
+        let mut labels: Vec<Label> = scan_records(&self.tree(LABELS_TREE)?, &[])?;
+        labels.sort_by(|label, other| label.name.cmp(&other.name));
+        Ok(labels)
     }
-    fn delete_board(&mut self, board: &Board) -> StorageResult<()> {
-        Err(StorageError::NotImplemented)
+
+    async fn create_label(&self, label: Label) -> StorageResult<Label> {
+        put_record(&self.tree(LABELS_TREE)?, label.id.as_bytes(), &label)?;
+        Ok(label)
     }
 
-    fn get_all_labels(&mut self) -> StorageResult<Vec<Label>> {
-        Err(StorageError::NotImplemented)
+    async fn update_label(&self, label: Label) -> StorageResult<Label> {
+        let labels = self.tree(LABELS_TREE)?;
+        if let Some(existing) = get_record::<Label>(&labels, label.id.as_bytes())? {
+            let stored = Label::new(
+                existing.id,
+                label.name.to_owned(),
+                label.description.to_owned(),
+                existing.owner,
+            );
+            put_record(&labels, label.id.as_bytes(), &stored)?;
+        }
+        Ok(label)
     }
-    fn create_label(&mut self, label: Label) -> StorageResult<Label> {
-        Err(StorageError::NotImplemented)
+
+    async fn delete_label(&self, label: &Label) -> StorageResult<()> {
+        self.tree(LABELS_TREE)?.remove(label.id.as_bytes())?;
+
+        //The label goes away from the entries too.
+        let entry_labels = self.tree(ENTRY_LABELS_TREE)?;
+        let mut batch = Batch::default();
+        for item in entry_labels.scan_prefix(label.owner.as_bytes()) {
+            let (key, _) = item?;
+            if key.get(32..) == Some(label.id.as_bytes()) {
+                batch.remove(key);
+            }
+        }
+        entry_labels.apply_batch(batch)?;
+        Ok(())
     }
-    fn update_label(&mut self, label: Label) -> StorageResult<Label> {
-        Err(StorageError::NotImplemented)
+
+    async fn get_owner_labels(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Label>> {
+        let mut labels: Vec<Label> = scan_records::<Label>(&self.tree(LABELS_TREE)?, &[])?
+            .into_iter()
+            .filter(|label| label.owner == owner_uuid)
+            .collect();
+        labels.sort_by(|label, other| label.name.cmp(&other.name));
+        Ok(labels)
     }
-    fn delete_label(&mut self, label: &Label) -> StorageResult<()> {
-        Err(StorageError::NotImplemented)
+
+    async fn add_entry_label(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        label_id: String,
+    ) -> StorageResult<()> {
+        self.tree(ENTRY_LABELS_TREE)?.insert(
+            key(&[
+                owner_uuid.as_bytes(),
+                entry_uuid.as_bytes(),
+                label_id.as_bytes(),
+            ]),
+            Vec::new(),
+        )?;
+        Ok(())
     }
 
-    fn create_entry(&mut self, mut entry: Entry) -> StorageResult<Entry> {
-        let entries = self.db.open_tree(ENTRIES_TREE)?;
+    async fn get_all_entries_labels(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<(uuid::Uuid, Label)>> {
+        self.labels_of_keys(owner_uuid, owner_uuid.as_bytes())
+    }
+
+    async fn get_entries_labels(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entries_uuid: Vec<uuid::Uuid>,
+    ) -> StorageResult<Vec<(uuid::Uuid, Label)>> {
+        let mut result: Vec<(uuid::Uuid, Label)> = Vec::new();
+        for entry_uuid in entries_uuid {
+            result.extend(self.labels_of_keys(
+                owner_uuid,
+                &key(&[owner_uuid.as_bytes(), entry_uuid.as_bytes()]),
+            )?);
+        }
+        Ok(result)
+    }
+
+    async fn create_entry(&self, mut entry: Entry) -> StorageResult<Entry> {
+        //Imported entries keep their original timestamps.
+        entry.created_on = entry.created_on.or_else(|| Some(Utc::now()));
         entry.uuid = Some(self.generate_uuid());
+        entry.id = self.generate_entry_id()?;
 
-        let compound_id = &entry.uuid.unwrap().to_string();
-        entries.insert(&compound_id, to_vec(&entry).unwrap())?;
+        self.put_entry(&mut entry)?;
         Ok(entry)
     }
-    fn update_entry(&mut self, entry: Entry) -> StorageResult<Entry> {
-        Err(StorageError::NotImplemented)
+
+    async fn update_entry(&self, mut entry: Entry) -> StorageResult<Entry> {
+        entry.updated_on = Some(Utc::now());
+        let (owner_uuid, uuid) = match (entry.owner, entry.uuid) {
+            (Some(owner_uuid), Some(uuid)) => (owner_uuid, uuid),
+            _ => return Err(StorageError::AnotherError),
+        };
+
+        let existing: Option<Entry> = get_record(
+            &self.tree(ENTRIES_TREE)?,
+            &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+        )?;
+        if let Some(existing) = existing {
+            let created_on = entry.created_on;
+            entry.id = existing.id;
+            entry.created_on = existing.created_on;
+            self.put_entry(&mut entry)?;
+            entry.created_on = created_on;
+        }
+        Ok(entry)
+    }
+
+    async fn delete_entry(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<()> {
+        let entry_key = key(&[owner_uuid.as_bytes(), uuid.as_bytes()]);
+        self.tree(ENTRIES_TREE)?.remove(&entry_key)?;
+
+        //Labels and links of the entry go with it, links to it only keep their title.
+        remove_prefix(&self.tree(ENTRY_LABELS_TREE)?, &entry_key)?;
+        let links = self.tree(ENTRY_LINKS_TREE)?;
+        remove_prefix(&links, &entry_key)?;
+
+        let mut batch = Batch::default();
+        for item in links.scan_prefix(owner_uuid.as_bytes()) {
+            let (link_key, bytes) = item?;
+            let mut link: EntryLink = decode(&bytes)?;
+            if link.target == Some(uuid) {
+                link.target = None;
+                batch.insert(link_key, encode(&link)?);
+            }
+        }
+        links.apply_batch(batch)?;
+        Ok(())
+    }
+
+    async fn get_entry(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<Entry> {
+        get_record(
+            &self.tree(ENTRIES_TREE)?,
+            &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+        )?
+        .ok_or(StorageError::AnotherError)
+    }
+
+    async fn get_all_entries(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Entry>> {
+        Ok(self
+            .get_owner_entries_sorted(owner_uuid)?
+            .into_iter()
+            .filter(|entry| entry.board.is_none())
+            .collect())
     }
-    fn delete_entry(&mut self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<()> {
-        Err(StorageError::NotImplemented)
+
+    async fn get_owner_entries(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Entry>> {
+        self.get_owner_entries_sorted(owner_uuid)
     }
-    fn get_entry(
-        &mut self,
+
+    async fn get_owner_entries_page(
+        &self,
         owner_uuid: uuid::Uuid,
-        uuid: uuid::Uuid,
-    ) -> StorageResult<Option<Entry>> {
-        Err(StorageError::NotImplemented)
+        after_uuid: Option<uuid::Uuid>,
+        limit: i64,
+    ) -> StorageResult<Vec<Entry>> {
+        //Keys are the owner then the entry uuid: they come in the order of the uuid.
+        let start = match after_uuid {
+            Some(after_uuid) => {
+                Bound::Excluded(key(&[owner_uuid.as_bytes(), after_uuid.as_bytes()]))
+            }
+            None => Bound::Included(owner_uuid.as_bytes().to_vec()),
+        };
+
+        let mut result: Vec<Entry> = Vec::new();
+        for item in self
+            .tree(ENTRIES_TREE)?
+            .range::<Vec<u8>, _>((start, Bound::Unbounded))
+        {
+            let (entry_key, bytes) = item?;
+            if !entry_key.starts_with(owner_uuid.as_bytes()) || result.len() as i64 >= limit {
+                break;
+            }
+            result.push(decode(&bytes)?);
+        }
+        Ok(result)
     }
 
-    fn get_all_entries(&mut self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Entry>> {
-        Err(StorageError::NotImplemented)
+    async fn get_entries_by_ids(
+        &self,
+        owner_uuid: uuid::Uuid,
+        ids: Vec<uuid::Uuid>,
+    ) -> StorageResult<Vec<Entry>> {
+        let entries_tree = self.tree(ENTRIES_TREE)?;
+        let mut entries: Vec<Entry> = Vec::new();
+        for uuid in ids {
+            if let Some(entry) = get_record(
+                &entries_tree,
+                &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            )? {
+                entries.push(entry);
+            }
+        }
+        sort_by_update(&mut entries);
+        Ok(entries)
     }
 
-    fn get_all_entries_by_board(
-        &mut self,
+    async fn get_all_entries_by_board(
+        &self,
         owner_uuid: uuid::Uuid,
         board_uuid: uuid::Uuid,
     ) -> StorageResult<Vec<Entry>> {
-        Err(StorageError::NotImplemented)
+        Ok(self
+            .get_owner_entries_sorted(owner_uuid)?
+            .into_iter()
+            .filter(|entry| entry.board == Some(board_uuid))
+            .collect())
+    }
+
+    async fn get_entry_by_import_id(
+        &self,
+        owner_uuid: uuid::Uuid,
+        source: String,
+        source_id: String,
+    ) -> StorageResult<Option<Entry>> {
+        let entries: Vec<Entry> = scan_records(&self.tree(ENTRIES_TREE)?, owner_uuid.as_bytes())?;
+        Ok(entries.into_iter().find(|entry| {
+            let import = entry.data.as_ref().and_then(|data| data.get("import"));
+            json_text(import.and_then(|import| import.get("source"))).as_ref() == Some(&source)
+                && json_text(import.and_then(|import| import.get("id"))).as_ref()
+                    == Some(&source_id)
+        }))
+    }
+
+    async fn get_entry_by_title(
+        &self,
+        owner_uuid: uuid::Uuid,
+        title: String,
+    ) -> StorageResult<Option<Entry>> {
+        let entries: Vec<Entry> = scan_records(&self.tree(ENTRIES_TREE)?, owner_uuid.as_bytes())?;
+        Ok(entries
+            .into_iter()
+            .filter(|entry| same_title(&entry.title, &title))
+            .min_by_key(|entry| entry.created_on))
+    }
+
+    async fn replace_entry_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        source_uuid: uuid::Uuid,
+        links: Vec<EntryLink>,
+    ) -> StorageResult<()> {
+        //One batch: the links of the entry are replaced all at once.
+        let links_tree = self.tree(ENTRY_LINKS_TREE)?;
+        let mut batch = Batch::default();
+        for item in links_tree.scan_prefix(key(&[owner_uuid.as_bytes(), source_uuid.as_bytes()])) {
+            let (link_key, _) = item?;
+            batch.remove(link_key);
+        }
+        for link in &links {
+            batch.insert(
+                key(&[
+                    link.owner.as_bytes(),
+                    link.source.as_bytes(),
+                    link.target_title.as_bytes(),
+                ]),
+                encode(link)?,
+            );
+        }
+        links_tree.apply_batch(batch)?;
+        Ok(())
+    }
+
+    async fn get_entry_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        source_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<EntryLink>> {
+        //Keys end with the target title: the links come in its order.
+        scan_records(
+            &self.tree(ENTRY_LINKS_TREE)?,
+            &key(&[owner_uuid.as_bytes(), source_uuid.as_bytes()]),
+        )
+    }
+
+    async fn get_entry_backlinks(
+        &self,
+        owner_uuid: uuid::Uuid,
+        target_uuid: uuid::Uuid,
+        target_title: String,
+    ) -> StorageResult<Vec<EntryLink>> {
+        //Links written before the target existed are only known by title.
+        let links: Vec<EntryLink> =
+            scan_records(&self.tree(ENTRY_LINKS_TREE)?, owner_uuid.as_bytes())?;
+        Ok(links
+            .into_iter()
+            .filter(|link| match link.target {
+                Some(target) => target == target_uuid,
+                None => same_title(&link.target_title, &target_title),
+            })
+            .collect())
+    }
+
+    async fn get_entries_to_remind(&self, until: DateTime<Utc>) -> StorageResult<Vec<Entry>> {
+        let mut entries: Vec<Entry> = scan_records::<Entry>(&self.tree(ENTRIES_TREE)?, &[])?
+            .into_iter()
+            .filter(|entry| {
+                !entry.archived
+                    && entry.reminded_on.is_none()
+                    && entry.remind_at.map(|at| at <= until).unwrap_or(false)
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.remind_at);
+        Ok(entries)
+    }
+
+    async fn get_all_entries_with_due_date(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Entry>> {
+        let mut entries: Vec<Entry> =
+            scan_records::<Entry>(&self.tree(ENTRIES_TREE)?, owner_uuid.as_bytes())?
+                .into_iter()
+                .filter(|entry| entry.due_on.is_some())
+                .collect();
+        entries.sort_by_key(|entry| entry.due_on);
+        Ok(entries)
+    }
+
+    async fn set_calendar_token(&self, owner_uuid: uuid::Uuid, token: String) -> StorageResult<()> {
+        put_record(
+            &self.tree(CALENDAR_TOKENS_TREE)?,
+            owner_uuid.as_bytes(),
+            &CalendarToken::new(owner_uuid, token, Utc::now()),
+        )
+    }
+
+    async fn get_calendar_token_owner(&self, token: String) -> StorageResult<Option<uuid::Uuid>> {
+        let tokens: Vec<CalendarToken> = scan_records(&self.tree(CALENDAR_TOKENS_TREE)?, &[])?;
+        Ok(tokens
+            .into_iter()
+            .find(|calendar_token| calendar_token.token == token)
+            .map(|calendar_token| calendar_token.owner))
+    }
+
+    async fn get_calendar_token(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Option<CalendarToken>> {
+        get_record(&self.tree(CALENDAR_TOKENS_TREE)?, owner_uuid.as_bytes())
+    }
+
+    async fn restore_calendar_token(&self, token: CalendarToken) -> StorageResult<()> {
+        put_record(
+            &self.tree(CALENDAR_TOKENS_TREE)?,
+            token.owner.as_bytes(),
+            &token,
+        )
+    }
+
+    async fn restore_public_link(&self, link: PublicLink) -> StorageResult<()> {
+        self.put_public_link(&link)
+    }
+
+    async fn restore_attachment(&self, attachment: &Attachment) -> StorageResult<()> {
+        //The processing state is local to the storage: kept, or started over.
+        let (attempts, failed_on) =
+            match self.get_stored_attachment(attachment.owner, attachment.uuid)? {
+                Some(existing) => (existing.processing_attempts, existing.failed_on),
+                None => (0, None),
+            };
+        self.put_attachment(attachment, attempts, failed_on)
+    }
+
+    async fn upsert_share(&self, mut share: Share) -> StorageResult<Share> {
+        share.created_on = Some(Utc::now());
+        let shares = self.tree(SHARES_TREE)?;
+        let share_key = key(&[share.item.as_bytes(), share.recipient.as_bytes()]);
+
+        //An existing share only changes its role.
+        match get_record::<Share>(&shares, &share_key)? {
+            Some(mut existing) => {
+                existing.role = share.role;
+                put_record(&shares, &share_key, &existing)?;
+            }
+            None => put_record(&shares, &share_key, &share)?,
+        }
+        Ok(share)
+    }
+
+    async fn delete_share(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+        recipient_uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let shares = self.tree(SHARES_TREE)?;
+        let share_key = key(&[item_uuid.as_bytes(), recipient_uuid.as_bytes()]);
+        if let Some(share) = get_record::<Share>(&shares, &share_key)? {
+            if share.owner == owner_uuid {
+                shares.remove(&share_key)?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_item_shares(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let shares = self.tree(SHARES_TREE)?;
+        let mut batch = Batch::default();
+        for item in shares.scan_prefix(item_uuid.as_bytes()) {
+            let (share_key, bytes) = item?;
+            if decode::<Share>(&bytes)?.owner == owner_uuid {
+                batch.remove(share_key);
+            }
+        }
+        shares.apply_batch(batch)?;
+        Ok(())
+    }
+
+    async fn get_item_shares(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Share>> {
+        let mut shares: Vec<Share> =
+            scan_records::<Share>(&self.tree(SHARES_TREE)?, item_uuid.as_bytes())?
+                .into_iter()
+                .filter(|share| share.owner == owner_uuid)
+                .collect();
+        shares.sort_by_key(|share| share.created_on);
+        Ok(shares)
+    }
+
+    async fn get_recipient_shares(&self, recipient_uuid: uuid::Uuid) -> StorageResult<Vec<Share>> {
+        let mut shares: Vec<Share> = scan_records::<Share>(&self.tree(SHARES_TREE)?, &[])?
+            .into_iter()
+            .filter(|share| share.recipient == recipient_uuid)
+            .collect();
+        shares.sort_by_key(|share| share.created_on);
+        Ok(shares)
+    }
+
+    async fn get_recipient_entry_shares(
+        &self,
+        recipient_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Share>> {
+        let entries = self.tree(ENTRIES_TREE)?;
+        let mut result: Vec<Share> = Vec::new();
+
+        //The entry is looked for under the owner of each share.
+        for share in self.get_recipient_shares(recipient_uuid).await? {
+            let entry: Option<Entry> = get_record(
+                &entries,
+                &key(&[share.owner.as_bytes(), entry_uuid.as_bytes()]),
+            )?;
+            if let Some(entry) = entry {
+                if share.item == entry_uuid || Some(share.item) == entry.board {
+                    result.push(share);
+                }
+            }
+        }
+        Ok(result)
+    }
+
+    async fn create_public_link(&self, link: PublicLink) -> StorageResult<PublicLink> {
+        self.put_public_link(&link)?;
+        Ok(link)
+    }
+
+    async fn get_public_link(&self, token: String) -> StorageResult<Option<PublicLink>> {
+        let stored: Option<StoredPublicLink<PublicLink>> =
+            get_record(&self.tree(PUBLIC_LINKS_TREE)?, token.as_bytes())?;
+        Ok(stored.map(|stored| {
+            let mut link = stored.link;
+            link.has_password = stored.password_hash.is_some();
+            link.password_hash = stored.password_hash;
+            link
+        }))
+    }
+
+    async fn get_item_public_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<PublicLink>> {
+        let mut links: Vec<PublicLink> = self
+            .get_public_links()?
+            .into_iter()
+            .filter(|link| link.owner == owner_uuid && link.item == item_uuid)
+            .collect();
+        links.sort_by_key(|link| link.created_on);
+        Ok(links)
+    }
+
+    async fn delete_public_link(&self, owner_uuid: uuid::Uuid, token: String) -> StorageResult<()> {
+        if let Some(link) = self.get_public_link(token.to_owned()).await? {
+            if link.owner == owner_uuid {
+                self.tree(PUBLIC_LINKS_TREE)?.remove(token.as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn delete_item_public_links(
+        &self,
+        owner_uuid: uuid::Uuid,
+        item_uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let mut batch = Batch::default();
+        for link in self.get_public_links()? {
+            if link.owner == owner_uuid && link.item == item_uuid {
+                batch.remove(link.token.as_bytes());
+            }
+        }
+        self.tree(PUBLIC_LINKS_TREE)?.apply_batch(batch)?;
+        Ok(())
+    }
+
+    async fn append_change(
+        &self,
+        users_uuid: &[uuid::Uuid],
+        event: &ChangeEvent,
+    ) -> StorageResult<()> {
+        //One line by user, each with its own sequence number.
+        let changes = self.tree(CHANGES_TREE)?;
+        for user_uuid in users_uuid {
+            let change = ChangeLogEntry::new(
+                self.next_change_seq()?,
+                *user_uuid,
+                event.owner,
+                event.item,
+                event.id.to_owned(),
+                event.kind,
+                event.occurred_on,
+            );
+            put_record(&changes, &change_key(*user_uuid, change.seq), &change)?;
+        }
+        Ok(())
+    }
+
+    async fn get_changes(
+        &self,
+        user_uuid: uuid::Uuid,
+        since: i64,
+        limit: i64,
+    ) -> StorageResult<Vec<ChangeLogEntry>> {
+        let start = change_key(user_uuid, since.max(0));
+
+        let mut result: Vec<ChangeLogEntry> = Vec::new();
+        for item in self
+            .tree(CHANGES_TREE)?
+            .range::<Vec<u8>, _>((Bound::Excluded(start), Bound::Unbounded))
+        {
+            let (change_key, bytes) = item?;
+            if !change_key.starts_with(user_uuid.as_bytes()) || result.len() as i64 >= limit {
+                break;
+            }
+            result.push(decode(&bytes)?);
+        }
+        Ok(result)
+    }
+
+    async fn restore_change(&self, change: ChangeLogEntry) -> StorageResult<()> {
+        //The sequence is kept, the cursors of the clients stay valid.
+        let changes = self.tree(CHANGES_TREE)?;
+        let restored_key = change_key(change.user, change.seq);
+        if !changes.contains_key(&restored_key)? {
+            put_record(&changes, &restored_key, &change)?;
+        }
+
+        //The next changes must come after the restored ones.
+        self.tree(SEQUENCES_TREE)?
+            .update_and_fetch(CHANGE_SEQ_KEY, |last| {
+                Some(read_seq(last).max(change.seq).to_be_bytes().to_vec())
+            })?;
+        Ok(())
+    }
+
+    async fn get_last_change_seq(&self, user_uuid: uuid::Uuid) -> StorageResult<i64> {
+        match self
+            .tree(CHANGES_TREE)?
+            .scan_prefix(user_uuid.as_bytes())
+            .next_back()
+        {
+            Some(item) => {
+                let (_, bytes) = item?;
+                Ok(decode::<ChangeLogEntry>(&bytes)?.seq)
+            }
+            None => Ok(0),
+        }
+    }
+
+    async fn get_item_last_change_seq(
+        &self,
+        user_uuid: uuid::Uuid,
+        id: String,
+    ) -> StorageResult<Option<i64>> {
+        for item in self
+            .tree(CHANGES_TREE)?
+            .scan_prefix(user_uuid.as_bytes())
+            .rev()
+        {
+            let (_, bytes) = item?;
+            let change: ChangeLogEntry = decode(&bytes)?;
+            if change.id == id {
+                return Ok(Some(change.seq));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn get_entry_document(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Option<Vec<u8>>> {
+        Ok(self
+            .tree(DOCUMENTS_TREE)?
+            .get(key(&[owner_uuid.as_bytes(), entry_uuid.as_bytes()]))?
+            .map(|document| document.to_vec()))
+    }
+
+    async fn save_entry_document(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+        document: Vec<u8>,
+    ) -> StorageResult<()> {
+        self.tree(DOCUMENTS_TREE)?.insert(
+            key(&[owner_uuid.as_bytes(), entry_uuid.as_bytes()]),
+            document,
+        )?;
+        Ok(())
+    }
+
+    async fn delete_entry_document(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        self.tree(DOCUMENTS_TREE)?
+            .remove(key(&[owner_uuid.as_bytes(), entry_uuid.as_bytes()]))?;
+        Ok(())
+    }
+
+    async fn create_attachment(&self, attachment: Attachment) -> StorageResult<Attachment> {
+        self.put_attachment(&attachment, 0, None)?;
+        Ok(attachment)
+    }
+
+    async fn get_attachment(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<Option<Attachment>> {
+        Ok(self
+            .get_stored_attachment(owner_uuid, uuid)?
+            .map(|stored| stored.attachment))
+    }
+
+    async fn get_entry_attachments(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Attachment>> {
+        Ok(self
+            .get_owner_attachments(owner_uuid)
+            .await?
+            .into_iter()
+            .filter(|attachment| attachment.entry == entry_uuid)
+            .collect())
+    }
+
+    async fn get_owner_attachments(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<Attachment>> {
+        let stored: Vec<StoredAttachment<Attachment>> =
+            scan_records(&self.tree(ATTACHMENTS_TREE)?, owner_uuid.as_bytes())?;
+        let mut attachments: Vec<Attachment> =
+            stored.into_iter().map(|stored| stored.attachment).collect();
+        attachments.sort_by_key(|attachment| attachment.created_on);
+        Ok(attachments)
+    }
+
+    async fn get_unprocessed_attachments(
+        &self,
+        limit: i64,
+        max_attempts: i32,
+        failed_before: DateTime<Utc>,
+    ) -> StorageResult<Vec<Attachment>> {
+        let mut stored: Vec<StoredAttachment<Attachment>> =
+            scan_records::<StoredAttachment<Attachment>>(&self.tree(ATTACHMENTS_TREE)?, &[])?
+                .into_iter()
+                .filter(|stored| {
+                    stored.attachment.processed_on.is_none()
+                        && stored.processing_attempts < max_attempts
+                        && stored
+                            .failed_on
+                            .map(|failed_on| failed_on < failed_before)
+                            .unwrap_or(true)
+                })
+                .collect();
+        stored.sort_by_key(|stored| (stored.processing_attempts, stored.attachment.created_on));
+
+        Ok(stored
+            .into_iter()
+            .take(limit.max(0) as usize)
+            .map(|stored| stored.attachment)
+            .collect())
+    }
+
+    async fn record_attachment_failure(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        if let Some(stored) = self.get_stored_attachment(owner_uuid, uuid)? {
+            self.put_attachment(
+                &stored.attachment,
+                stored.processing_attempts + 1,
+                Some(Utc::now()),
+            )?;
+        }
+        Ok(())
+    }
+
+    async fn update_processed_attachment(&self, attachment: &Attachment) -> StorageResult<()> {
+        let stored = match self.get_stored_attachment(attachment.owner, attachment.uuid)? {
+            Some(stored) => stored,
+            None => return Ok(()),
+        };
+
+        let mut processed = stored.attachment;
+        processed.width = attachment.width;
+        processed.height = attachment.height;
+        processed.thumbnails = attachment
+            .thumbnail_sizes()
+            .into_iter()
+            .map(|size| Thumbnail::new(processed.entry, processed.uuid, size))
+            .collect();
+        processed.processed_on = attachment.processed_on;
+        self.put_attachment(&processed, stored.processing_attempts, stored.failed_on)
+    }
+
+    async fn save_attachment_text(
+        &self,
+        attachment: &Attachment,
+        text: String,
+    ) -> StorageResult<()> {
+        self.tree(ATTACHMENT_TEXTS_TREE)?.insert(
+            key(&[
+                attachment.owner.as_bytes(),
+                attachment.entry.as_bytes(),
+                attachment.uuid.as_bytes(),
+            ]),
+            text.into_bytes(),
+        )?;
+        Ok(())
+    }
+
+    async fn get_entry_attachment_texts(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<(uuid::Uuid, String)>> {
+        let mut texts: Vec<(uuid::Uuid, String)> = Vec::new();
+        for item in self
+            .tree(ATTACHMENT_TEXTS_TREE)?
+            .scan_prefix(key(&[owner_uuid.as_bytes(), entry_uuid.as_bytes()]))
+        {
+            let (text_key, text) = item?;
+            texts.push((
+                uuid_at(&text_key, 32)?,
+                String::from_utf8(text.to_vec()).map_err(|_| StorageError::AnotherError)?,
+            ));
+        }
+        Ok(texts)
+    }
+
+    async fn delete_attachment(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        //The extracted text goes with the attachment.
+        if let Some(stored) = self.get_stored_attachment(owner_uuid, uuid)? {
+            self.tree(ATTACHMENT_TEXTS_TREE)?.remove(key(&[
+                owner_uuid.as_bytes(),
+                stored.attachment.entry.as_bytes(),
+                uuid.as_bytes(),
+            ]))?;
+        }
+        self.tree(ATTACHMENTS_TREE)?
+            .remove(key(&[owner_uuid.as_bytes(), uuid.as_bytes()]))?;
+        Ok(())
+    }
+
+    async fn delete_entry_attachments(
+        &self,
+        owner_uuid: uuid::Uuid,
+        entry_uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        let mut batch = Batch::default();
+        for attachment in self.get_entry_attachments(owner_uuid, entry_uuid).await? {
+            batch.remove(key(&[owner_uuid.as_bytes(), attachment.uuid.as_bytes()]));
+        }
+        self.tree(ATTACHMENTS_TREE)?.apply_batch(batch)?;
+        remove_prefix(
+            &self.tree(ATTACHMENT_TEXTS_TREE)?,
+            &key(&[owner_uuid.as_bytes(), entry_uuid.as_bytes()]),
+        )
+    }
+
+    async fn queue_link_previews(&self, urls: Vec<String>) -> StorageResult<()> {
+        let previews = self.tree(LINK_PREVIEWS_TREE)?;
+        for url in urls {
+            if previews.contains_key(url.as_bytes())? {
+                continue;
+            }
+            let queued = StoredLinkPreview {
+                preview: LinkPreview::new(url.to_owned(), None, None, None, None, None),
+                queued_on: Utc::now(),
+            };
+            put_record(&previews, url.as_bytes(), &queued)?;
+        }
+        Ok(())
+    }
+
+    async fn get_pending_link_previews(&self, limit: i64) -> StorageResult<Vec<String>> {
+        let mut pending: Vec<StoredLinkPreview> =
+            scan_records::<StoredLinkPreview>(&self.tree(LINK_PREVIEWS_TREE)?, &[])?
+                .into_iter()
+                .filter(|stored| stored.preview.fetched_on.is_none())
+                .collect();
+        pending.sort_by_key(|stored| stored.queued_on);
+
+        Ok(pending
+            .into_iter()
+            .take(limit.max(0) as usize)
+            .map(|stored| stored.preview.url)
+            .collect())
+    }
+
+    async fn save_link_preview(&self, preview: &LinkPreview) -> StorageResult<()> {
+        let previews = self.tree(LINK_PREVIEWS_TREE)?;
+        //Only queued URLs get their preview.
+        if let Some(stored) = get_record::<StoredLinkPreview>(&previews, preview.url.as_bytes())? {
+            let fetched = StoredLinkPreview {
+                preview: preview.clone(),
+                queued_on: stored.queued_on,
+            };
+            put_record(&previews, preview.url.as_bytes(), &fetched)?;
+        }
+        Ok(())
+    }
+
+    async fn get_link_previews(&self, urls: Vec<String>) -> StorageResult<Vec<LinkPreview>> {
+        let previews = self.tree(LINK_PREVIEWS_TREE)?;
+        let mut result: Vec<LinkPreview> = Vec::new();
+        for url in urls {
+            if let Some(stored) = get_record::<StoredLinkPreview>(&previews, url.as_bytes())? {
+                result.push(stored.preview);
+            }
+        }
+        Ok(result)
+    }
+
+    async fn create_template(&self, mut template: Template) -> StorageResult<Template> {
+        template.created_on = Some(Utc::now());
+        let owner_uuid = template.owner.ok_or(StorageError::CreationImpossible)?;
+        let uuid = template.uuid.ok_or(StorageError::CreationImpossible)?;
+
+        put_record(
+            &self.tree(TEMPLATES_TREE)?,
+            &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            &template,
+        )?;
+        Ok(template)
+    }
+
+    async fn update_template(&self, mut template: Template) -> StorageResult<Template> {
+        template.updated_on = Some(Utc::now());
+        let template_key = match (template.owner, template.uuid) {
+            (Some(owner_uuid), Some(uuid)) => key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            _ => return Err(StorageError::AnotherError),
+        };
+
+        let templates = self.tree(TEMPLATES_TREE)?;
+        if let Some(existing) = get_record::<Template>(&templates, &template_key)? {
+            template.created_on = existing.created_on;
+            put_record(&templates, &template_key, &template)?;
+        }
+        Ok(template)
+    }
+
+    async fn get_template(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<Option<Template>> {
+        get_record(
+            &self.tree(TEMPLATES_TREE)?,
+            &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+        )
+    }
+
+    async fn get_all_templates(&self, owner_uuid: uuid::Uuid) -> StorageResult<Vec<Template>> {
+        let mut templates: Vec<Template> =
+            scan_records(&self.tree(TEMPLATES_TREE)?, owner_uuid.as_bytes())?;
+        templates.sort_by(|template, other| template.name.cmp(&other.name));
+        Ok(templates)
+    }
+
+    async fn delete_template(&self, owner_uuid: uuid::Uuid, uuid: uuid::Uuid) -> StorageResult<()> {
+        self.tree(TEMPLATES_TREE)?
+            .remove(key(&[owner_uuid.as_bytes(), uuid.as_bytes()]))?;
+        Ok(())
+    }
+
+    async fn create_saved_search(
+        &self,
+        mut saved_search: SavedSearch,
+    ) -> StorageResult<SavedSearch> {
+        saved_search.created_on = Some(Utc::now());
+        let owner_uuid = saved_search.owner.ok_or(StorageError::CreationImpossible)?;
+        let uuid = saved_search.uuid.ok_or(StorageError::CreationImpossible)?;
+
+        put_record(
+            &self.tree(SAVED_SEARCHES_TREE)?,
+            &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            &saved_search,
+        )?;
+        Ok(saved_search)
+    }
+
+    async fn update_saved_search(
+        &self,
+        mut saved_search: SavedSearch,
+    ) -> StorageResult<SavedSearch> {
+        saved_search.updated_on = Some(Utc::now());
+        let saved_search_key = match (saved_search.owner, saved_search.uuid) {
+            (Some(owner_uuid), Some(uuid)) => key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            _ => return Err(StorageError::AnotherError),
+        };
+
+        let saved_searches = self.tree(SAVED_SEARCHES_TREE)?;
+        if let Some(existing) = get_record::<SavedSearch>(&saved_searches, &saved_search_key)? {
+            saved_search.created_on = existing.created_on;
+            put_record(&saved_searches, &saved_search_key, &saved_search)?;
+        }
+        Ok(saved_search)
+    }
+
+    async fn get_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<Option<SavedSearch>> {
+        get_record(
+            &self.tree(SAVED_SEARCHES_TREE)?,
+            &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+        )
+    }
+
+    async fn get_all_saved_searches(
+        &self,
+        owner_uuid: uuid::Uuid,
+    ) -> StorageResult<Vec<SavedSearch>> {
+        let mut saved_searches: Vec<SavedSearch> =
+            scan_records(&self.tree(SAVED_SEARCHES_TREE)?, owner_uuid.as_bytes())?;
+        saved_searches.sort_by(|saved_search, other| saved_search.name.cmp(&other.name));
+        Ok(saved_searches)
+    }
+
+    async fn delete_saved_search(
+        &self,
+        owner_uuid: uuid::Uuid,
+        uuid: uuid::Uuid,
+    ) -> StorageResult<()> {
+        self.tree(SAVED_SEARCHES_TREE)?
+            .remove(key(&[owner_uuid.as_bytes(), uuid.as_bytes()]))?;
+        Ok(())
+    }
+
+    async fn get_all_owners(&self) -> StorageResult<Vec<uuid::Uuid>> {
+        let mut owners: BTreeSet<Uuid> = BTreeSet::new();
+
+        //Keys of these trees start with the owner, or the user of the change log.
+        for name in &[
+            BOARDS_TREE,
+            ENTRIES_TREE,
+            CALENDAR_TOKENS_TREE,
+            TEMPLATES_TREE,
+            SAVED_SEARCHES_TREE,
+            CHANGES_TREE,
+        ] {
+            for item in self.tree(name)?.iter() {
+                let (record_key, _) = item?;
+                owners.insert(uuid_at(&record_key, 0)?);
+            }
+        }
+        let labels: Vec<Label> = scan_records(&self.tree(LABELS_TREE)?, &[])?;
+        owners.extend(labels.into_iter().map(|label| label.owner));
+
+        Ok(owners.into_iter().collect())
+    }
+
+    async fn restore_board(&self, board: Board) -> StorageResult<()> {
+        let board_key = match (board.owner, board.uuid) {
+            (Some(owner_uuid), Some(uuid)) => key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            _ => return Err(StorageError::CreationImpossible),
+        };
+        put_record(&self.tree(BOARDS_TREE)?, &board_key, &board)
+    }
+
+    async fn restore_label(&self, label: Label) -> StorageResult<()> {
+        put_record(&self.tree(LABELS_TREE)?, label.id.as_bytes(), &label)
+    }
+
+    async fn restore_entry(&self, mut entry: Entry) -> StorageResult<()> {
+        //The id is local to the storage, entries are identified by their uuid.
+        let existing: Option<Entry> = match (entry.owner, entry.uuid) {
+            (Some(owner_uuid), Some(uuid)) => get_record(
+                &self.tree(ENTRIES_TREE)?,
+                &key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            )?,
+            _ => return Err(StorageError::CreationImpossible),
+        };
+        entry.id = match existing {
+            Some(existing) => existing.id,
+            None => self.generate_entry_id()?,
+        };
+        self.put_entry(&mut entry)
+    }
+
+    async fn restore_template(&self, template: Template) -> StorageResult<()> {
+        let template_key = match (template.owner, template.uuid) {
+            (Some(owner_uuid), Some(uuid)) => key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            _ => return Err(StorageError::CreationImpossible),
+        };
+        put_record(&self.tree(TEMPLATES_TREE)?, &template_key, &template)
+    }
+
+    async fn restore_saved_search(&self, saved_search: SavedSearch) -> StorageResult<()> {
+        let saved_search_key = match (saved_search.owner, saved_search.uuid) {
+            (Some(owner_uuid), Some(uuid)) => key(&[owner_uuid.as_bytes(), uuid.as_bytes()]),
+            _ => return Err(StorageError::CreationImpossible),
+        };
+        put_record(
+            &self.tree(SAVED_SEARCHES_TREE)?,
+            &saved_search_key,
+            &saved_search,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use helix_memoriz_domain::blob::fs_blob_store::FsBlobStore;
+    use helix_memoriz_domain::business::crdt::ContentDocument;
+    use helix_memoriz_domain::business::domain::MemorizDomain;
+    use helix_memoriz_domain::business::dump::{dump, restore};
+    use helix_memoriz_domain::business::traits::DomainTrait;
+    use helix_memoriz_domain::core::recurrence::Recurrence;
+    use helix_memoriz_domain::fetcher::static_link_fetcher::StaticLinkFetcher;
+    use helix_memoriz_domain::notifier::log_notifier::LogNotifier;
+    use helix_memoriz_domain::notifier::traits::EventPublisherTrait;
+    use helix_memoriz_domain::storage::traits::SearchEngineTrait;
+    use std::collections::HashMap;
+    use std::str::FromStr;
+
+    fn storage() -> SledDbMemorizStorage {
+        SledDbMemorizStorage {
+            db: sled::Config::new().temporary(true).open().unwrap(),
+        }
+    }
+
+    //The domain on this storage, without search engine nor events.
+    struct NoSearchEngine {}
+
+    #[async_trait]
+    impl SearchEngineTrait for NoSearchEngine {
+        async fn index_entry(&self, _entry: &Entry) -> SearchEngineResult<()> {
+            Ok(())
+        }
+        async fn search_entries(
+            &self,
+            _owner_uuid: Uuid,
+            _request: SearchRequest,
+        ) -> SearchEngineResult<SearchResponse> {
+            Ok(SearchResponse::default())
+        }
+        async fn delete_entry(&self, _uuid: Uuid) -> SearchEngineResult<()> {
+            Ok(())
+        }
+    }
+
+    struct NoPublisher {}
+
+    impl EventPublisherTrait for NoPublisher {
+        fn publish(&self, _recipients: &[Uuid], _event: &ChangeEvent) {}
+    }
+
+    fn domain() -> MemorizDomain {
+        let blobs = std::env::temp_dir().join(format!("memoriz-blobs-{}", Uuid::new_v4()));
+        MemorizDomain::new(
+            Box::new(storage()),
+            Box::new(NoSearchEngine {}),
+            Box::new(LogNotifier::new()),
+            Box::new(NoPublisher {}),
+            Box::new(FsBlobStore::new(blobs.to_string_lossy().to_string()).unwrap()),
+            Box::new(StaticLinkFetcher::new(HashMap::new())),
+        )
+    }
+
+    fn entry(owner_uuid: Uuid, title: &str) -> Entry {
+        Entry::new(
+            0,
+            None,
+            title.to_owned(),
+            Some(String::from("content")),
+            None,
+            None,
+            false,
+            None,
+            None,
+            Some(owner_uuid),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[tokio::test]
+    async fn keeps_the_recurrence_of_an_entry() {
+        let storage = storage();
+        let owner_uuid = Uuid::new_v4();
+
+        let mut created = entry(owner_uuid, "Standup");
+        created.recurrence =
+            Some(Recurrence::from_str("FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE").unwrap());
+        let created = storage.create_entry(created).await.unwrap();
+        let uuid = created.uuid.unwrap();
+
+        let mut updated = storage.get_entry(owner_uuid, uuid).await.unwrap();
+        updated.title = String::from("Weekly standup");
+        storage.update_entry(updated).await.unwrap();
+
+        let stored = storage.get_entry(owner_uuid, uuid).await.unwrap();
+        assert_eq!(stored.id, created.id);
+        assert_eq!(stored.title, "Weekly standup");
+        assert_eq!(stored.created_on, created.created_on);
+        assert!(stored.updated_on.is_some());
+        assert_eq!(stored.recurrence, created.recurrence);
+    }
+
+    #[tokio::test]
+    async fn continues_the_change_log_after_a_restore() {
+        let storage = storage();
+        let user_uuid = Uuid::new_v4();
+
+        storage
+            .restore_change(ChangeLogEntry::new(
+                41,
+                user_uuid,
+                user_uuid,
+                ChangedItem::Entry,
+                String::from("restored"),
+                ChangeKind::Created,
+                Utc::now(),
+            ))
+            .await
+            .unwrap();
+        let event = ChangeEvent::new(
+            ChangeKind::Updated,
+            ChangedItem::Entry,
+            String::from("restored"),
+            user_uuid,
+            Utc::now(),
+        );
+        storage.append_change(&[user_uuid], &event).await.unwrap();
+
+        assert_eq!(storage.get_last_change_seq(user_uuid).await.unwrap(), 42);
+        let changes = storage.get_changes(user_uuid, 41, 10).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Updated);
+        assert_eq!(
+            storage
+                .get_item_last_change_seq(user_uuid, String::from("restored"))
+                .await
+                .unwrap(),
+            Some(42)
+        );
+    }
+
+    #[tokio::test]
+    async fn removes_the_labels_and_links_of_a_deleted_entry() {
+        let storage = storage();
+        let owner_uuid = Uuid::new_v4();
+        let source = storage
+            .create_entry(entry(owner_uuid, "Source"))
+            .await
+            .unwrap();
+        let target = storage
+            .create_entry(entry(owner_uuid, "Target"))
+            .await
+            .unwrap();
+        let (source_uuid, target_uuid) = (source.uuid.unwrap(), target.uuid.unwrap());
+
+        let label = Label::new(
+            String::from("work"),
+            String::from("Work"),
+            String::new(),
+            owner_uuid,
+        );
+        storage.create_label(label).await.unwrap();
+        for uuid in &[source_uuid, target_uuid] {
+            storage
+                .add_entry_label(owner_uuid, *uuid, String::from("work"))
+                .await
+                .unwrap();
+        }
+        storage
+            .replace_entry_links(
+                owner_uuid,
+                source_uuid,
+                vec![EntryLink::new(
+                    source_uuid,
+                    String::from("Target"),
+                    Some(target_uuid),
+                    owner_uuid,
+                )],
+            )
+            .await
+            .unwrap();
+        storage
+            .replace_entry_links(
+                owner_uuid,
+                target_uuid,
+                vec![EntryLink::new(
+                    target_uuid,
+                    String::from("Source"),
+                    Some(source_uuid),
+                    owner_uuid,
+                )],
+            )
+            .await
+            .unwrap();
+
+        storage.delete_entry(owner_uuid, target_uuid).await.unwrap();
+
+        let labels = storage.get_all_entries_labels(owner_uuid).await.unwrap();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].0, source_uuid);
+        let links = storage
+            .get_entry_links(owner_uuid, source_uuid)
+            .await
+            .unwrap();
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].target, None);
+        assert!(storage
+            .get_entry_links(owner_uuid, target_uuid)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn retries_the_failed_attachments_later() {
+        let storage = storage();
+        let owner_uuid = Uuid::new_v4();
+        let attachment = Attachment::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            owner_uuid,
+            String::from("photo.png"),
+            String::from("image/png"),
+            1024,
+            Some(Utc::now()),
+            None,
+            None,
+            None,
+            Vec::new(),
+            None,
+        );
+        storage.create_attachment(attachment).await.unwrap();
+        let pending = storage
+            .get_unprocessed_attachments(10, 3, Utc::now())
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+
+        storage
+            .record_attachment_failure(owner_uuid, pending[0].uuid)
+            .await
+            .unwrap();
+        let failed_before = Utc::now() - chrono::Duration::minutes(5);
+        assert!(storage
+            .get_unprocessed_attachments(10, 3, failed_before)
+            .await
+            .unwrap()
+            .is_empty());
+        let retried = storage
+            .get_unprocessed_attachments(10, 3, Utc::now() + chrono::Duration::minutes(5))
+            .await
+            .unwrap();
+        assert_eq!(retried.len(), 1);
+        assert!(storage
+            .get_unprocessed_attachments(10, 1, Utc::now() + chrono::Duration::minutes(5))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn restores_its_own_dump() {
+        let source = storage();
+        let owner_uuid = Uuid::new_v4();
+        let entry = source
+            .create_entry(entry(owner_uuid, "Entry"))
+            .await
+            .unwrap();
+        let label = Label::new(
+            String::from("home"),
+            String::from("Home"),
+            String::new(),
+            owner_uuid,
+        );
+        source.create_label(label).await.unwrap();
+        source
+            .add_entry_label(owner_uuid, entry.uuid.unwrap(), String::from("home"))
+            .await
+            .unwrap();
+
+        let mut dumped: Vec<u8> = Vec::new();
+        let dumped_report = dump(&source, None, &mut dumped).await.unwrap();
+        let target = storage();
+        let restored_report = restore(&target, dumped.as_slice()).await.unwrap();
+
+        assert_eq!(restored_report.entries, 1);
+        assert_eq!(restored_report.entry_labels, dumped_report.entry_labels);
+        let restored = target
+            .get_entry(owner_uuid, entry.uuid.unwrap())
+            .await
+            .unwrap();
+        assert_eq!(restored.title, "Entry");
+        assert_eq!(restored.created_on, entry.created_on);
+        assert_eq!(
+            target
+                .get_all_entries_labels(owner_uuid)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn keeps_the_rewritten_links_after_a_merge() {
+        let domain = domain();
+        let owner_uuid = Uuid::new_v4();
+        let target = domain.create_entry(entry(owner_uuid, "Old")).await.unwrap();
+        let mut source = entry(owner_uuid, "Source");
+        source.content = Some(String::from("See [[Old]] here"));
+        let source_uuid = domain.create_entry(source).await.unwrap().uuid.unwrap();
+
+        //A client edits the document it got before the rename.
+        let known = domain
+            .get_entry_document(owner_uuid, source_uuid)
+            .await
+            .unwrap();
+        let mut client = ContentDocument::load(&known.document).unwrap();
+        client.set_content("See [[Old]] here, and there").unwrap();
+
+        let mut renamed = domain
+            .get_entry(owner_uuid, target.uuid.unwrap())
+            .await
+            .unwrap();
+        renamed.title = String::from("New");
+        domain.update_entry(renamed, true).await.unwrap();
+
+        let merged = domain
+            .merge_entry_document(
+                owner_uuid,
+                source_uuid,
+                client.changes_after(&known.heads),
+                known.heads,
+            )
+            .await
+            .unwrap();
+        assert_eq!(merged.content, "See [[New]] here, and there");
+        let source = domain.get_entry(owner_uuid, source_uuid).await.unwrap();
+        assert_eq!(
+            source.content.as_deref(),
+            Some("See [[New]] here, and there")
+        );
     }
 }